@@ -0,0 +1,1848 @@
+//! Install lifecycle shared by every managed adapter binary.
+//!
+//! An [`AdapterSpec`] describes one binary: where it is released, how its tags
+//! and assets are named, and which server launches it. Version caching,
+//! GitHub lookups, downloads, verification and health checks are written once
+//! against the spec, so the core adapter (also behind `vulnera-secrets` and
+//! `vulnera-iac`) and the container adapter share one pipeline.
+//!
+//! Releases are looked up with Zed's GitHub helpers (`latest_github_release`,
+//! `github_release_by_tag_name`) where they fit; the raw `/releases` list is
+//! the fallback for prerelease channels and for repositories publishing
+//! several binaries, and the conventional download URL the fallback when an
+//! asset cannot be looked up. Every release response is remembered by the
+//! [`releases`] module, so the asset list and release notes of a version
+//! found while resolving are not fetched again.
+//!
+//! A release may ship a `compat.json` asset naming the oldest extension it
+//! works with (`min_extension_version`); [`check_compatible`] keeps such a
+//! release from being installed unless the user pins it. A fresh install
+//! takes the [`newest_compatible`] older release instead.
+//!
+//! Each spec installs into its own directory: `<dir>/<version>/<name>[.exe]`,
+//! with `<dir>/cached-version.txt` and `<dir>/last-install-error.txt` beside
+//! the version directories.
+//!
+//! Release notes of an update are fetched when it is found, ahead of its
+//! download, and kept in `<dir>/release-notes/`. When a download replaces an
+//! earlier install, the notes are logged and recorded in
+//! `<dir>/last-update.txt` for `/vulnera-changelog`. With `update_mode`
+//! `notify` or `manual`, [`hold_update`] keeps the installed version and
+//! records a newer release in `<dir>/update-available.txt` instead.
+//!
+//! Every function here reaches the network, the work dir and the clock
+//! through a [`Host`], so tests run the lifecycle against in-memory state.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use serde::Deserialize;
+use zed_extension_api::http_client::{HttpMethod, HttpRequest, RedirectPolicy};
+use zed_extension_api::serde_json;
+use zed_extension_api::{self as zed, Architecture, Os, Result};
+
+use crate::channel::Channel;
+use crate::env_value;
+use crate::error::VulneraError;
+use crate::findings::compare_versions;
+use crate::host::Host;
+use crate::install::{self, InstallState, InstallSteps};
+use crate::metrics::{self, InstallStats};
+use crate::releases::{self, ReleaseInfo};
+use crate::state;
+
+// ── Constants ─────────────────────────────────────────────────────────────────
+
+/// Absolute minimum version used when the GitHub API is unreachable and no
+/// version has ever been cached locally.
+const MINIMUM_ADAPTER_VERSION: &str = "0.1.1";
+
+/// How many seconds a cached version stays fresh before we re-query GitHub.
+pub const VERSION_CACHE_TTL_SECS: u64 = 24 * 60 * 60; // 24 hours
+
+// ── Adapter specs ─────────────────────────────────────────────────────────────
+
+/// A native binary the extension downloads from GitHub Releases and keeps
+/// per version under its own directory.
+#[derive(Clone, Copy)]
+pub struct AdapterSpec {
+    /// Executable name.
+    pub name: &'static str,
+    /// GitHub repository publishing the releases.
+    pub repo: &'static str,
+    /// Release tag prefix in front of the version.
+    pub tag_prefix: &'static str,
+    /// Release asset filename; `{target}` is the Rust target triple and
+    /// `{exe}` is `.exe` on Windows.
+    pub asset_pattern: &'static str,
+    /// Language server ID that launches this binary by default.
+    pub server_id: &'static str,
+    /// Install directory of the stable channel, relative to the extension
+    /// work dir.
+    pub dir: &'static str,
+    /// Environment variable pinning the version.
+    pub version_env: &'static str,
+    /// Version used when GitHub is unreachable and nothing is cached.
+    pub minimum_version: &'static str,
+    /// Release channel followed.
+    pub channel: Channel,
+}
+
+/// The core `vulnera-adapter` language server.
+pub const CORE_ADAPTER: AdapterSpec = AdapterSpec {
+    name: "vulnera-adapter",
+    repo: "vulnera-rs/adapter",
+    tag_prefix: "adapter-v",
+    asset_pattern: "vulnera-adapter-{target}{exe}",
+    server_id: crate::SERVER_ID,
+    dir: "server",
+    version_env: "VULNERA_ADAPTER_VERSION",
+    minimum_version: MINIMUM_ADAPTER_VERSION,
+    channel: Channel::Stable,
+};
+
+/// The container-scanning adapter.
+pub const CONTAINER_ADAPTER: AdapterSpec = AdapterSpec {
+    name: "vulnera-container-adapter",
+    repo: "vulnera-rs/adapter",
+    tag_prefix: "container-adapter-v",
+    asset_pattern: "vulnera-container-adapter-{target}{exe}",
+    server_id: "vulnera-container",
+    dir: "container",
+    version_env: "VULNERA_CONTAINER_ADAPTER_VERSION",
+    minimum_version: "0.1.0",
+    channel: Channel::Stable,
+};
+
+/// Every managed binary, for reports.
+pub const ALL: &[&AdapterSpec] = &[&CORE_ADAPTER, &CONTAINER_ADAPTER];
+
+impl AdapterSpec {
+    /// Whether this is the core adapter, which honours the binary and
+    /// version overrides of `lsp.vulnera` and `.zed/vulnera.toml`.
+    pub fn is_core(&self) -> bool {
+        self.name == CORE_ADAPTER.name
+    }
+
+    /// This binary on `channel`.
+    pub fn on_channel(&self, channel: Channel) -> AdapterSpec {
+        AdapterSpec { channel, ..*self }
+    }
+
+    /// Directory holding this binary's versions and caches on its channel.
+    pub fn install_dir(&self) -> String {
+        self.channel.install_dir(self.dir)
+    }
+}
+
+// ── Platform resolution ───────────────────────────────────────────────────────
+
+/// Maps a (Os, Architecture) pair to the release asset metadata.
+#[derive(Debug, Clone, Copy)]
+pub struct PlatformInfo {
+    /// Rust target triple, e.g. `x86_64-unknown-linux-gnu`.
+    pub target_triple: &'static str,
+    /// Whether the platform requires a `.exe` suffix.
+    pub is_windows: bool,
+}
+
+impl PlatformInfo {
+    /// Filename of `spec`'s executable, with `.exe` on Windows.
+    fn executable(&self, spec: &AdapterSpec) -> String {
+        if self.is_windows {
+            format!("{}.exe", spec.name)
+        } else {
+            spec.name.to_string()
+        }
+    }
+
+    /// Full filename of `spec`'s release asset on the GitHub release page.
+    pub fn asset_name(&self, spec: &AdapterSpec) -> String {
+        spec.asset_pattern
+            .replace("{target}", self.target_triple)
+            .replace("{exe}", if self.is_windows { ".exe" } else { "" })
+    }
+
+    /// Whether the release asset `name` is `spec`'s executable for this
+    /// platform: the expected [`asset_name`](Self::asset_name), or a renamed
+    /// asset that still starts with the binary name and ends with the target
+    /// triple (e.g. with a version in between).
+    pub fn matches_asset(&self, spec: &AdapterSpec, name: &str) -> bool {
+        if name == self.asset_name(spec) {
+            return true;
+        }
+        let exe = if self.is_windows { ".exe" } else { "" };
+        name.starts_with(&format!("{}-", spec.name))
+            && name
+                .strip_suffix(exe)
+                .is_some_and(|stem| stem.ends_with(&format!("-{}", self.target_triple)))
+    }
+
+    /// Download URL of the first of `assets` (name, URL) that
+    /// [`matches_asset`](Self::matches_asset), preferring an exact name.
+    pub fn select_asset<'a>(
+        &self,
+        spec: &AdapterSpec,
+        assets: &'a [(String, String)],
+    ) -> Option<&'a str> {
+        let expected = self.asset_name(spec);
+        assets
+            .iter()
+            .find(|(name, _)| *name == expected)
+            .or_else(|| {
+                assets
+                    .iter()
+                    .find(|(name, _)| self.matches_asset(spec, name))
+            })
+            .map(|(_, url)| url.as_str())
+    }
+}
+
+pub fn resolve_platform(os: Os, arch: Architecture) -> Result<PlatformInfo, VulneraError> {
+    match (os, arch) {
+        (Os::Linux, Architecture::X8664) => Ok(PlatformInfo {
+            target_triple: "x86_64-unknown-linux-gnu",
+            is_windows: false,
+        }),
+        (Os::Linux, Architecture::Aarch64) => Ok(PlatformInfo {
+            target_triple: "aarch64-unknown-linux-gnu",
+            is_windows: false,
+        }),
+        (Os::Mac, Architecture::X8664) => Ok(PlatformInfo {
+            target_triple: "x86_64-apple-darwin",
+            is_windows: false,
+        }),
+        (Os::Mac, Architecture::Aarch64) => Ok(PlatformInfo {
+            target_triple: "aarch64-apple-darwin",
+            is_windows: false,
+        }),
+        (Os::Windows, Architecture::X8664) => Ok(PlatformInfo {
+            target_triple: "x86_64-pc-windows-msvc",
+            is_windows: true,
+        }),
+        _ => Err(VulneraError::UnsupportedPlatform {
+            os: format!("{:?}", os),
+            arch: format!("{:?}", arch),
+        }),
+    }
+}
+
+// ── Path helpers ──────────────────────────────────────────────────────────────
+
+fn version_dir(spec: &AdapterSpec, version: &str) -> String {
+    format!("{}/{}", spec.install_dir(), version)
+}
+
+pub fn binary_path(spec: &AdapterSpec, platform: &PlatformInfo, version: &str) -> String {
+    format!(
+        "{}/{}",
+        version_dir(spec, version),
+        platform.executable(spec)
+    )
+}
+
+fn installed_version_path(spec: &AdapterSpec, version: &str) -> String {
+    format!("{}/installed-version.txt", version_dir(spec, version))
+}
+
+fn cached_latest_version_path(spec: &AdapterSpec) -> String {
+    format!("{}/cached-version.txt", spec.install_dir())
+}
+
+fn cached_version_timestamp_path(spec: &AdapterSpec) -> String {
+    format!("{}/cached-version-timestamp.txt", spec.install_dir())
+}
+
+fn last_install_error_path(spec: &AdapterSpec) -> String {
+    format!("{}/last-install-error.txt", spec.install_dir())
+}
+
+fn release_notes_path(spec: &AdapterSpec, version: &str) -> String {
+    format!("{}/release-notes/{}.md", spec.install_dir(), version)
+}
+
+fn last_update_path(spec: &AdapterSpec) -> String {
+    format!("{}/last-update.txt", spec.install_dir())
+}
+
+fn bad_versions_path(spec: &AdapterSpec) -> String {
+    format!("{}/bad-versions.txt", spec.install_dir())
+}
+
+fn update_available_path(spec: &AdapterSpec) -> String {
+    format!("{}/update-available.txt", spec.install_dir())
+}
+
+// ── Installed-version marker ──────────────────────────────────────────────────
+
+/// The version recorded after a completed install into `version`'s
+/// directory; absent while a download is incomplete.
+pub fn read_installed_version(host: &Host, spec: &AdapterSpec, version: &str) -> Option<String> {
+    host.fs
+        .read_to_string(&installed_version_path(spec, version))
+        .ok()
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+}
+
+fn write_installed_version(host: &Host, spec: &AdapterSpec, version: &str) {
+    if let Err(e) = host
+        .fs
+        .write(&installed_version_path(spec, version), version)
+    {
+        warn!("Failed to write installed-version marker: {}", e);
+    }
+}
+
+// ── Latest-version cache (with TTL) ──────────────────────────────────────────
+
+pub fn read_cached_latest_version(host: &Host, spec: &AdapterSpec) -> Option<(String, u64)> {
+    let version = host
+        .fs
+        .read_to_string(&cached_latest_version_path(spec))
+        .ok()
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())?;
+
+    let timestamp: u64 = host
+        .fs
+        .read_to_string(&cached_version_timestamp_path(spec))
+        .ok()
+        .and_then(|s| s.trim().parse().ok())
+        .unwrap_or(0);
+
+    Some((version, timestamp))
+}
+
+fn write_cached_latest_version(host: &Host, spec: &AdapterSpec, version: &str) {
+    let dir = spec.install_dir();
+    if let Err(e) = host.fs.create_dir_all(&dir) {
+        warn!("Failed to create {}/: {}", dir, e);
+    }
+    if let Err(e) = host.fs.write(&cached_latest_version_path(spec), version) {
+        warn!("Failed to write cached-version: {}", e);
+    }
+    if let Err(e) = host.fs.write(
+        &cached_version_timestamp_path(spec),
+        &host.clock.now_secs().to_string(),
+    ) {
+        warn!("Failed to write cached-version timestamp: {}", e);
+    }
+}
+
+// ── GitHub version fetch ──────────────────────────────────────────────────────
+
+/// The version string (without the tag prefix) of `binary`'s latest release
+/// on its channel, or `None` if GitHub is unreachable or no matching release
+/// is found.
+///
+/// Stable releases are looked up with Zed's GitHub helper, which filters out
+/// drafts and prereleases. Other channels, and repositories whose newest
+/// release belongs to another binary, fall back to scanning the release list.
+fn fetch_latest_adapter_version_from_github(host: &Host, spec: &AdapterSpec) -> Option<String> {
+    if spec.channel == Channel::Stable
+        && let Some(version) = latest_stable_release(host, spec)
+    {
+        return Some(version);
+    }
+    fetch_latest_from_release_list(host, spec)
+}
+
+/// The newest stable release of `spec.repo` via Zed's `latest_github_release`,
+/// if it is one of `spec`'s releases.
+fn latest_stable_release(host: &Host, spec: &AdapterSpec) -> Option<String> {
+    let options = zed::GithubReleaseOptions {
+        require_assets: true,
+        pre_release: false,
+    };
+    let release = match host.http.latest_github_release(spec.repo, options) {
+        Ok(release) => release,
+        Err(e) => {
+            debug!("Latest {} release lookup failed: {}", spec.repo, e);
+            return None;
+        }
+    };
+    remember_github_release(host, spec, &release);
+    match release.version.strip_prefix(spec.tag_prefix) {
+        Some(version) if !version.is_empty() => Some(version.to_string()),
+        _ => {
+            debug!(
+                "Latest {} release {} is not a {} release",
+                spec.repo, release.version, spec.name
+            );
+            None
+        }
+    }
+}
+
+/// GET `url` from the GitHub API, returning the response body. The request
+/// carries `host`'s GitHub token, if any.
+fn github_api_get(host: &Host, url: String) -> Option<String> {
+    let mut headers = vec![
+        (
+            "User-Agent".to_string(),
+            "vulnera-zed-extension".to_string(),
+        ),
+        (
+            "Accept".to_string(),
+            "application/vnd.github+json".to_string(),
+        ),
+    ];
+    if let Some(token) = host.github_token {
+        headers.push(("Authorization".to_string(), format!("Bearer {}", token)));
+    }
+    let request = HttpRequest {
+        url: url.clone(),
+        method: HttpMethod::Get,
+        headers,
+        body: None,
+        redirect_policy: RedirectPolicy::FollowAll,
+    };
+
+    // `fetch` returns Err on transport failures and non-2xx HTTP errors.
+    let response = match host.http.fetch(&request) {
+        Ok(r) => r,
+        Err(e) => {
+            match VulneraError::download(&url, &e) {
+                limited @ VulneraError::RateLimited { .. } => log!("{}", limited),
+                _ => warn!("GitHub API request failed: {}", e),
+            }
+            return None;
+        }
+    };
+
+    match String::from_utf8(response.body) {
+        Ok(b) => Some(b),
+        Err(e) => {
+            warn!("Failed to decode GitHub API response: {}", e);
+            None
+        }
+    }
+}
+
+/// Find the latest release of `spec` in the full `/releases` list, caching
+/// its release notes.
+fn fetch_latest_from_release_list(host: &Host, spec: &AdapterSpec) -> Option<String> {
+    let body = github_api_get(
+        host,
+        format!("https://api.github.com/repos/{}/releases", spec.repo),
+    )?;
+
+    match parse_releases(&body) {
+        Ok(list) => releases::remember(
+            host,
+            spec.repo,
+            list.into_iter()
+                .filter(|release| !release.draft)
+                .map(Release::info)
+                .collect(),
+        ),
+        Err(e) => {
+            log!("GitHub API returned an unexpected body: {}", e);
+            return None;
+        }
+    }
+    let version = parse_latest_version(&body, spec.tag_prefix, spec.channel)?;
+    if let Some(notes) = parse_release_notes(&body, &format!("{}{}", spec.tag_prefix, version)) {
+        write_release_notes(host, spec, &version, &notes);
+    }
+    Some(version)
+}
+
+// ── Release list ──────────────────────────────────────────────────────────────
+
+/// The fields of a GitHub release the extension reads; the rest of the
+/// `/releases` response is ignored.
+#[derive(Debug, Deserialize)]
+struct Release {
+    tag_name: String,
+    #[serde(default)]
+    draft: bool,
+    #[serde(default)]
+    prerelease: bool,
+    #[serde(default)]
+    body: Option<String>,
+    #[serde(default)]
+    assets: Vec<ReleaseAsset>,
+}
+
+/// A file attached to a [`Release`].
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct ReleaseAsset {
+    name: String,
+    browser_download_url: String,
+}
+
+/// Parse a GitHub `/releases` response (a JSON array, newest first).
+fn parse_releases(json: &str) -> Result<Vec<Release>> {
+    serde_json::from_str(json).map_err(|e| e.to_string())
+}
+
+/// Extract the first `{tag_prefix}{VERSION}` tag of a non-draft release that
+/// `channel` accepts from a GitHub `/releases` response.
+///
+/// Returns the version number without the prefix on success.
+pub fn parse_latest_version(json: &str, tag_prefix: &str, channel: Channel) -> Option<String> {
+    parse_releases(json)
+        .ok()?
+        .into_iter()
+        .filter(|release| !release.draft)
+        .find_map(|release| {
+            let version = release.tag_name.strip_prefix(tag_prefix)?;
+            (!version.is_empty() && channel.accepts(version, release.prerelease))
+                .then(|| version.to_string())
+        })
+}
+
+/// Extract the `body` of the release tagged `tag` from the same response.
+fn parse_release_notes(json: &str, tag: &str) -> Option<String> {
+    parse_releases(json)
+        .ok()?
+        .into_iter()
+        .find(|release| release.tag_name == tag)?
+        .notes()
+}
+
+/// Remember a release returned by Zed's GitHub helpers.
+fn remember_github_release(host: &Host, spec: &AdapterSpec, release: &zed::GithubRelease) {
+    let info = ReleaseInfo {
+        tag: release.version.clone(),
+        assets: release
+            .assets
+            .iter()
+            .map(|a| (a.name.clone(), a.download_url.clone()))
+            .collect(),
+        ..ReleaseInfo::default()
+    };
+    releases::remember(host, spec.repo, vec![info]);
+}
+
+impl Release {
+    /// What the extension keeps of this release.
+    fn info(self) -> ReleaseInfo {
+        ReleaseInfo {
+            tag: self.tag_name.clone(),
+            prerelease: self.prerelease,
+            assets: self
+                .assets
+                .iter()
+                .map(|a| (a.name.clone(), a.browser_download_url.clone()))
+                .collect(),
+            notes: self.notes(),
+            ..ReleaseInfo::default()
+        }
+    }
+
+    /// The release body as plain text, if it has one.
+    fn notes(self) -> Option<String> {
+        // Bodies may carry escaped control characters (`\u001b[31m`, `\u0000`)
+        // that would garble the log and slash command output.
+        let notes: String = strip_escape_sequences(&self.body?)
+            .chars()
+            .filter(|c| !c.is_control() || matches!(c, '\n' | '\t'))
+            .collect();
+        let notes = notes.trim();
+        (!notes.is_empty()).then(|| notes.to_string())
+    }
+}
+
+/// `text` without terminal escape sequences: CSI (`ESC [ … final`), OSC
+/// (`ESC ] …` up to BEL or `ESC \`) and the shorter `ESC … final` escapes.
+fn strip_escape_sequences(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\u{1b}' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            // Parameter and intermediate bytes, then one final byte.
+            Some('[') => {
+                for c in chars.by_ref() {
+                    if ('\u{40}'..='\u{7e}').contains(&c) {
+                        break;
+                    }
+                }
+            }
+            Some(']') => {
+                while let Some(c) = chars.next() {
+                    if c == '\u{7}' || (c == '\u{1b}' && chars.next_if_eq(&'\\').is_some()) {
+                        break;
+                    }
+                }
+            }
+            // Intermediate bytes, then one final byte (`ESC ( B`).
+            Some(' '..='/') => {
+                for c in chars.by_ref() {
+                    if !(' '..='/').contains(&c) {
+                        break;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    out
+}
+
+/// Keep the release notes of `spec` at `version` in `release-notes/`, from
+/// the release cache or else fetched, so the install that follows can log
+/// them without a lookup of its own.
+pub fn fetch_release_notes(host: &Host, spec: &AdapterSpec, version: &str) -> Option<String> {
+    if let Ok(notes) = host.fs.read_to_string(&release_notes_path(spec, version)) {
+        return Some(notes);
+    }
+    let tag = format!("{}{}", spec.tag_prefix, version);
+    let notes = match releases::get(host, spec.repo, &tag).and_then(|release| release.notes) {
+        Some(notes) => notes,
+        None => {
+            let body = github_api_get(
+                host,
+                format!(
+                    "https://api.github.com/repos/{}/releases/tags/{}",
+                    spec.repo, tag
+                ),
+            )?;
+            let info = serde_json::from_str::<Release>(&body).ok()?.info();
+            releases::remember(host, spec.repo, vec![info.clone()]);
+            info.notes?
+        }
+    };
+    write_release_notes(host, spec, version, &notes);
+    Some(notes)
+}
+
+// ── Release notes ─────────────────────────────────────────────────────────────
+
+fn write_release_notes(host: &Host, spec: &AdapterSpec, version: &str, notes: &str) {
+    let path = release_notes_path(spec, version);
+    let written = host
+        .fs
+        .create_dir_all(&format!("{}/release-notes", spec.install_dir()))
+        .and_then(|()| host.fs.write(&path, notes));
+    if let Err(e) = written {
+        warn!("Failed to cache release notes for {}: {}", version, e);
+    }
+}
+
+/// The latest update of a managed binary.
+pub struct Update {
+    pub from: String,
+    pub to: String,
+    /// Release notes of `to`, if GitHub returned them.
+    pub notes: Option<String>,
+}
+
+/// Record that `version` replaced an earlier install and log its notes, if
+/// they were kept before the download; this never goes to GitHub.
+fn record_update(host: &Host, spec: &AdapterSpec, version: &str) {
+    if read_update_available(host, spec)
+        .is_some_and(|available| compare_versions(&available, version).is_le())
+    {
+        let _ = host.fs.remove_file(&update_available_path(spec));
+    }
+    let Some(previous) = newest_installed(host, spec, Some(version)) else {
+        return;
+    };
+
+    let notes = host
+        .fs
+        .read_to_string(&release_notes_path(spec, version))
+        .ok();
+    match notes {
+        Some(notes) => log!(
+            "{} updated from {} to {}. Release notes:\n{}",
+            spec.name,
+            previous,
+            version,
+            notes
+        ),
+        None => log!("{} updated from {} to {}", spec.name, previous, version),
+    }
+    if let Err(e) = host.fs.write(
+        &last_update_path(spec),
+        &format!("{}\n{}", previous, version),
+    ) {
+        warn!("Failed to record the update: {}", e);
+    }
+}
+
+/// The last recorded update of `spec`, with its release notes.
+pub fn last_update(host: &Host, spec: &AdapterSpec) -> Option<Update> {
+    let record = host.fs.read_to_string(&last_update_path(spec)).ok()?;
+    let (from, to) = record.trim().split_once('\n')?;
+    Some(Update {
+        from: from.to_string(),
+        to: to.to_string(),
+        notes: host.fs.read_to_string(&release_notes_path(spec, to)).ok(),
+    })
+}
+
+/// The version a server running `running` should switch to: the target of
+/// the last recorded update from `running`, if newer, once fully installed
+/// and not flagged bad.
+pub fn pending_swap(host: &Host, spec: &AdapterSpec, running: &str) -> Option<String> {
+    let update = last_update(host, spec)?;
+    (update.from == running
+        && compare_versions(&update.to, running).is_gt()
+        && read_installed_version(host, spec, &update.to).as_deref() == Some(update.to.as_str())
+        && !is_bad_version(host, spec, &update.to))
+    .then_some(update.to)
+}
+
+/// The newest completely installed version of `spec`, other than `except`.
+pub fn newest_installed(host: &Host, spec: &AdapterSpec, except: Option<&str>) -> Option<String> {
+    host.fs
+        .read_dir(&spec.install_dir())
+        .into_iter()
+        .filter(|v| Some(v.as_str()) != except)
+        .filter(|v| read_installed_version(host, spec, v).as_deref() == Some(v))
+        .max_by(|a, b| compare_versions(a, b))
+}
+
+/// The version to launch from local state alone: the cached latest release,
+/// whatever its age, if it is installed, else the newest installed version.
+/// `None` before the first install.
+pub fn local_version(host: &Host, spec: &AdapterSpec) -> Option<String> {
+    read_cached_latest_version(host, spec)
+        .map(|(version, _)| version)
+        .filter(|version| read_installed_version(host, spec, version).as_deref() == Some(version))
+        .or_else(|| newest_installed(host, spec, None))
+}
+
+/// The cached latest release of `spec` if it is newer than `current`, not
+/// installed yet and not flagged bad: an update found by an earlier check,
+/// installed at the next launch.
+pub fn cached_update(host: &Host, spec: &AdapterSpec, current: &str) -> Option<String> {
+    let (latest, _) = read_cached_latest_version(host, spec)?;
+    (compare_versions(&latest, current).is_gt()
+        && read_installed_version(host, spec, &latest).as_deref() != Some(latest.as_str())
+        && !is_bad_version(host, spec, &latest))
+    .then_some(latest)
+}
+
+/// A newer release recorded by [`hold_update`] and not installed yet.
+pub fn read_update_available(host: &Host, spec: &AdapterSpec) -> Option<String> {
+    host.fs
+        .read_to_string(&update_available_path(spec))
+        .ok()
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+}
+
+/// Keep running the installed version instead of `latest`, recording `latest`
+/// as available when it is newer. Without an install, `latest` is returned so
+/// the first install still happens.
+pub fn hold_update(host: &Host, spec: &AdapterSpec, latest: String, notify: bool) -> String {
+    let Some(installed) = newest_installed(host, spec, None) else {
+        return latest;
+    };
+    if compare_versions(&latest, &installed).is_gt() {
+        if read_update_available(host, spec).as_deref() != Some(latest.as_str()) {
+            if let Err(e) = host.fs.write(&update_available_path(spec), &latest) {
+                warn!("Failed to record the available update: {}", e);
+            }
+            if notify {
+                log!(
+                    "Vulnera: {} {} is available (running {}); run /vulnera-update to install it",
+                    spec.name,
+                    latest,
+                    installed
+                );
+            }
+        }
+        debug!("Holding {} at {} (latest {})", spec.name, installed, latest);
+    }
+    installed
+}
+
+/// Query GitHub for `spec`'s latest release on `spec.channel`, bypassing the
+/// version cache, and install it. Returns the previously newest installed version and
+/// the installed one.
+pub fn update_now(
+    host: &Host,
+    spec: &AdapterSpec,
+    platform: &PlatformInfo,
+) -> Result<(Option<String>, String)> {
+    let latest = fetch_latest_adapter_version_from_github(host, spec).ok_or_else(|| {
+        VulneraError::DownloadFailed {
+            url: format!("https://api.github.com/repos/{}/releases", spec.repo),
+            reason: format!(
+                "no {} release of {} could be fetched; see the Zed log",
+                spec.channel, spec.name
+            ),
+        }
+    })?;
+    write_cached_latest_version(host, spec, &latest);
+    let previous = newest_installed(host, spec, None);
+    if previous.as_deref() != Some(latest.as_str()) {
+        check_compatible(host, spec, &latest)?;
+        fetch_release_notes(host, spec, &latest);
+    }
+    ensure_binary(host, spec, platform, &latest, |state| debug!("{:?}", state))?;
+    clear_bad_version(host, spec, &latest);
+    // A re-install of the newest version does not trigger `record_update`.
+    let _ = host.fs.remove_file(&update_available_path(spec));
+    invalidate_session();
+    Ok((previous, latest))
+}
+
+// ── Bad releases ──────────────────────────────────────────────────────────────
+
+/// Whether `version` was flagged by [`mark_bad_version`].
+pub fn is_bad_version(host: &Host, spec: &AdapterSpec, version: &str) -> bool {
+    host.fs
+        .read_to_string(&bad_versions_path(spec))
+        .is_ok_and(|list| list.lines().any(|v| v.trim() == version))
+}
+
+/// Flag `version` as crashing on launch, so unpinned launches skip it until a
+/// newer release is resolved.
+pub fn mark_bad_version(host: &Host, spec: &AdapterSpec, version: &str) {
+    if is_bad_version(host, spec, version) {
+        return;
+    }
+    let mut list = host
+        .fs
+        .read_to_string(&bad_versions_path(spec))
+        .unwrap_or_default();
+    state::forget_known_good(host, &spec.install_dir(), version);
+    list.push_str(version);
+    list.push('\n');
+    if let Err(e) = host.fs.write(&bad_versions_path(spec), &list) {
+        warn!("Failed to flag {} {} as bad: {}", spec.name, version, e);
+    }
+}
+
+/// Lift the [`mark_bad_version`] flag from `version`: the user asked for it
+/// by pinning it or running `/vulnera-update`.
+pub fn clear_bad_version(host: &Host, spec: &AdapterSpec, version: &str) {
+    let Ok(list) = host.fs.read_to_string(&bad_versions_path(spec)) else {
+        return;
+    };
+    if !list.lines().any(|v| v.trim() == version) {
+        return;
+    }
+    let kept: String = list
+        .lines()
+        .filter(|v| v.trim() != version)
+        .map(|v| format!("{}\n", v))
+        .collect();
+    if let Err(e) = host.fs.write(&bad_versions_path(spec), &kept) {
+        warn!(
+            "Failed to clear the bad flag of {} {}: {}",
+            spec.name, version, e
+        );
+    }
+}
+
+/// The installed version to fall back to from `version`: the last-known-good
+/// one, else the one it replaced, else the newest other installed version not
+/// flagged bad.
+pub fn fallback_version(host: &Host, spec: &AdapterSpec, version: &str) -> Option<String> {
+    let installed_ok = |v: &str| {
+        read_installed_version(host, spec, v).as_deref() == Some(v)
+            && !is_bad_version(host, spec, v)
+    };
+    if let Some(good) = state::last_known_good(host, &spec.install_dir())
+        && good != version
+        && installed_ok(&good)
+    {
+        return Some(good);
+    }
+    if let Some(update) = last_update(host, spec)
+        && update.to == version
+        && installed_ok(&update.from)
+    {
+        return Some(update.from);
+    }
+    host.fs
+        .read_dir(&spec.install_dir())
+        .into_iter()
+        .filter(|v| v != version && installed_ok(v))
+        .max_by(|a, b| compare_versions(a, b))
+}
+
+// ── Session invalidation ──────────────────────────────────────────────────────
+
+/// Bumped whenever versions memoized for the session must be resolved again.
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// The current session generation; a memoized version is only valid while
+/// this is unchanged.
+pub fn generation() -> u64 {
+    GENERATION.load(Ordering::Relaxed)
+}
+
+/// Drop every version memoized for the session, e.g. after an explicit update,
+/// so the next launch re-runs [`resolve_adapter_version`].
+pub fn invalidate_session() {
+    GENERATION.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Whether `version` is a strict SemVer 2.0 version (`1.2.3`, `1.2.3-rc.1`,
+/// `1.2.3+build.5`). Pins are checked against this before they become part
+/// of an install path or a download URL.
+pub fn is_semver(version: &str) -> bool {
+    let numeric = |p: &str| {
+        !p.is_empty() && p.bytes().all(|b| b.is_ascii_digit()) && (p == "0" || !p.starts_with('0'))
+    };
+    let identifier =
+        |p: &str| !p.is_empty() && p.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-');
+    let (version, build) = match version.split_once('+') {
+        Some((version, build)) => (version, Some(build)),
+        None => (version, None),
+    };
+    let (core, pre) = match version.split_once('-') {
+        Some((core, pre)) => (core, Some(pre)),
+        None => (version, None),
+    };
+    let core: Vec<&str> = core.split('.').collect();
+    core.len() == 3
+        && core.iter().all(|p| numeric(p))
+        && pre.is_none_or(|pre| {
+            pre.split('.')
+                .all(|p| identifier(p) && (!p.bytes().all(|b| b.is_ascii_digit()) || numeric(p)))
+        })
+        && build.is_none_or(|build| build.split('.').all(identifier))
+}
+
+// ── Version resolution ────────────────────────────────────────────────────────
+
+/// Resolve the version of `binary` to use, applying the priority chain
+/// documented at the top of this module.
+pub fn resolve_adapter_version(
+    host: &Host,
+    spec: &AdapterSpec,
+    shell_env: &[(String, String)],
+    pinned: Option<&str>,
+) -> String {
+    // 1. Env var pin.
+    if let Some(v) = env_value(shell_env, spec.version_env) {
+        log!("{} version from {}: {}", spec.name, spec.version_env, v);
+        return v.to_string();
+    }
+
+    // 2. Worktree pin.
+    if let Some(v) = pinned.map(str::trim).filter(|v| !v.is_empty()) {
+        log!("Adapter version from worktree override: {}", v);
+        return v.to_string();
+    }
+
+    let now = host.clock.now_secs();
+
+    // 3. Fresh cache hit.
+    if let Some((cached, fetched_at)) = read_cached_latest_version(host, spec)
+        && now.saturating_sub(fetched_at) < spec.channel.cache_ttl_secs()
+    {
+        log!(
+            "{} version from cache (age {}s): {}",
+            spec.name,
+            now.saturating_sub(fetched_at),
+            cached
+        );
+        metrics::record_cache_lookup(host, true);
+        return cached;
+    }
+
+    // 4. Live fetch, unless GitHub just failed and a cached version can stand in.
+    metrics::record_cache_lookup(host, false);
+    if state::network_recently_unavailable(host, now)
+        && let Some((cached, _)) = read_cached_latest_version(host, spec)
+    {
+        log!(
+            "GitHub was unreachable moments ago; using stale cached {} version: {}",
+            spec.name,
+            cached
+        );
+        return cached;
+    }
+    log!("Fetching latest {} version from GitHub…", spec.name);
+    let fetched = fetch_latest_adapter_version_from_github(host, spec);
+    state::record_update_check(host, now, fetched.is_some());
+    if let Some(fetched) = fetched {
+        log!("Latest {} version from GitHub: {}", spec.name, fetched);
+        write_cached_latest_version(host, spec, &fetched);
+        state::clear_floor_use(host, &spec.install_dir());
+        return fetched;
+    }
+
+    // 5. Stale cache fallback.
+    if let Some((cached, _)) = read_cached_latest_version(host, spec) {
+        warn!(
+            "GitHub fetch failed; using stale cached version: {}",
+            cached
+        );
+        return cached;
+    }
+
+    // 6. Absolute floor.
+    if state::record_floor_use(host, &spec.install_dir(), now) {
+        warn!("{}", floor_warning(spec));
+    } else {
+        debug!(
+            "GitHub fetch failed and no cache; falling back to minimum: {}",
+            spec.minimum_version
+        );
+    }
+    spec.minimum_version.to_string()
+}
+
+/// Why running `spec`'s minimum version is a problem and how to get off it.
+pub fn floor_warning(spec: &AdapterSpec) -> String {
+    format!(
+        "Running {} {}, the oldest supported version, because GitHub could not be \
+         reached and no newer version is cached. Results may be incomplete: \
+         detections added since then are missing. Check the network or proxy \
+         (set VULNERA_GITHUB_TOKEN if GitHub is rate limiting you), or pin a \
+         version with {} or .zed/vulnera.toml.",
+        spec.name, spec.minimum_version, spec.version_env
+    )
+}
+
+// ── Compatibility ─────────────────────────────────────────────────────────────
+
+/// Release asset declaring which extensions a release works with.
+const COMPAT_ASSET: &str = "compat.json";
+
+/// This extension's version, compared against `min_extension_version`.
+pub const EXTENSION_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct Compat {
+    min_extension_version: Option<String>,
+}
+
+/// The `min_extension_version` of `spec`'s release of `version`, read from
+/// its `compat.json` asset once and then kept in the release cache, as is a
+/// release declaring none. A failed fetch is retried on the next check.
+fn min_extension_version(host: &Host, spec: &AdapterSpec, version: &str) -> Option<String> {
+    let tag = format!("{}{}", spec.tag_prefix, version);
+    if let Some(release) = releases::get(host, spec.repo, &tag)
+        && release.compat_checked
+    {
+        return release.min_extension_version;
+    }
+    let assets = release_assets(host, spec, version)?;
+    let required = match assets.iter().find(|(name, _)| name == COMPAT_ASSET) {
+        Some((_, url)) => {
+            let request = HttpRequest {
+                url: url.clone(),
+                method: HttpMethod::Get,
+                headers: vec![(
+                    "User-Agent".to_string(),
+                    "vulnera-zed-extension".to_string(),
+                )],
+                body: None,
+                redirect_policy: RedirectPolicy::FollowAll,
+            };
+            match host.http.fetch(&request) {
+                Ok(response) => serde_json::from_slice::<Compat>(&response.body)
+                    .ok()
+                    .and_then(|compat| compat.min_extension_version),
+                Err(e) => {
+                    debug!("Failed to fetch {} of {}: {}", COMPAT_ASSET, tag, e);
+                    return None;
+                }
+            }
+        }
+        None => None,
+    };
+    if let Some(release) = releases::get(host, spec.repo, &tag) {
+        let info = ReleaseInfo {
+            min_extension_version: required.clone(),
+            compat_checked: true,
+            ..release
+        };
+        releases::remember(host, spec.repo, vec![info]);
+    }
+    required
+}
+
+/// Releases of `spec` older than `rejected` tried by [`newest_compatible`].
+const COMPAT_FALLBACK_CANDIDATES: usize = 5;
+
+/// The newest release of `spec` on its channel, older than `rejected`, that
+/// works with this extension. Uses the remembered release list, fetching it
+/// once if it has no older release.
+pub fn newest_compatible(host: &Host, spec: &AdapterSpec, rejected: &str) -> Option<String> {
+    let older = |host: &Host| -> Vec<String> {
+        let mut versions: Vec<String> = releases::list(host, spec.repo)
+            .into_iter()
+            .filter_map(|release| {
+                let version = release.tag.strip_prefix(spec.tag_prefix)?.to_string();
+                (!version.is_empty()
+                    && spec.channel.accepts(&version, release.prerelease)
+                    && compare_versions(&version, rejected).is_lt())
+                .then_some(version)
+            })
+            .collect();
+        versions.sort_by(|a, b| compare_versions(b, a));
+        versions.dedup();
+        versions
+    };
+    let mut candidates = older(host);
+    if candidates.is_empty() {
+        fetch_latest_from_release_list(host, spec);
+        candidates = older(host);
+    }
+    candidates
+        .into_iter()
+        .take(COMPAT_FALLBACK_CANDIDATES)
+        .find(|version| check_compatible(host, spec, version).is_ok())
+}
+
+/// The refusal of the cached latest release of `spec`, if the release cache
+/// already knows it needs a newer extension. Reads only cached data.
+pub fn known_incompatible(host: &Host, spec: &AdapterSpec) -> Option<VulneraError> {
+    let (latest, _) = read_cached_latest_version(host, spec)?;
+    let tag = format!("{}{}", spec.tag_prefix, latest);
+    let required = releases::get(host, spec.repo, &tag)?.min_extension_version?;
+    compare_versions(&required, EXTENSION_VERSION)
+        .is_gt()
+        .then(|| VulneraError::ExtensionTooOld {
+            binary: spec.name.to_string(),
+            version: latest,
+            required,
+        })
+}
+
+/// Refuse to install `version` of `spec` when its release requires a newer
+/// extension. Releases without a `compat.json` asset, or whose asset cannot
+/// be read, are accepted.
+pub fn check_compatible(
+    host: &Host,
+    spec: &AdapterSpec,
+    version: &str,
+) -> Result<(), VulneraError> {
+    match min_extension_version(host, spec, version) {
+        Some(required) if compare_versions(&required, EXTENSION_VERSION).is_gt() => {
+            Err(VulneraError::ExtensionTooOld {
+                binary: spec.name.to_string(),
+                version: version.to_string(),
+                required,
+            })
+        }
+        _ => Ok(()),
+    }
+}
+
+// ── Download ──────────────────────────────────────────────────────────────────
+
+fn download_url(spec: &AdapterSpec, platform: &PlatformInfo, version: &str) -> String {
+    format!(
+        "https://github.com/{}/releases/download/{}{}/{}",
+        spec.repo,
+        spec.tag_prefix,
+        version,
+        platform.asset_name(spec)
+    )
+}
+
+/// The `(name, download URL)` assets of `spec`'s release of `version`: from
+/// the release cache, else via `github_release_by_tag_name`, else the raw
+/// `/releases/tags` endpoint.
+fn release_assets(host: &Host, spec: &AdapterSpec, version: &str) -> Option<Vec<(String, String)>> {
+    let tag = format!("{}{}", spec.tag_prefix, version);
+    if let Some(release) = releases::get(host, spec.repo, &tag)
+        && !release.assets.is_empty()
+    {
+        debug!("Release {} assets from the release cache", tag);
+        return Some(release.assets);
+    }
+    match host.http.github_release_by_tag_name(spec.repo, &tag) {
+        Ok(release) => {
+            remember_github_release(host, spec, &release);
+            return Some(
+                release
+                    .assets
+                    .into_iter()
+                    .map(|a| (a.name, a.download_url))
+                    .collect(),
+            );
+        }
+        Err(e) => debug!("Release {} lookup failed: {}", tag, e),
+    }
+    let body = github_api_get(
+        host,
+        format!(
+            "https://api.github.com/repos/{}/releases/tags/{}",
+            spec.repo, tag
+        ),
+    )?;
+    let info = serde_json::from_str::<Release>(&body).ok()?.info();
+    releases::remember(host, spec.repo, vec![info.clone()]);
+    Some(info.assets)
+}
+
+/// The download URL of this platform's asset of `version`, taken from the
+/// release's asset list. Errors before any download when the release exists
+/// but has no asset for the platform; falls back to the conventional URL only
+/// when the release cannot be looked up at all.
+fn release_asset_url(
+    host: &Host,
+    spec: &AdapterSpec,
+    platform: &PlatformInfo,
+    version: &str,
+) -> Result<String, VulneraError> {
+    let Some(assets) = release_assets(host, spec, version) else {
+        debug!("Release lookup failed; using the conventional download URL");
+        return Ok(download_url(spec, platform, version));
+    };
+    match platform.select_asset(spec, &assets) {
+        Some(url) => Ok(url.to_string()),
+        None => Err(VulneraError::AssetMissing {
+            binary: spec.name.to_string(),
+            version: version.to_string(),
+            expected: platform.asset_name(spec),
+            found: assets.into_iter().map(|(name, _)| name).collect(),
+        }),
+    }
+}
+
+/// Check that `path` starts like a native executable.
+fn check_executable(host: &Host, path: &str) -> Result<(), VulneraError> {
+    let magic = host.fs.read_prefix(path, 4).ok();
+    if let Some(Ok(magic)) = magic.as_deref().map(<&[u8; 4]>::try_from)
+        && is_executable_magic(magic)
+    {
+        Ok(())
+    } else {
+        Err(VulneraError::VerificationFailed {
+            path: path.to_string(),
+        })
+    }
+}
+
+/// ELF, PE (`MZ`) or Mach-O (32/64-bit, either byte order, or universal).
+pub fn is_executable_magic(magic: &[u8; 4]) -> bool {
+    magic == b"\x7fELF"
+        || magic.starts_with(b"MZ")
+        || matches!(
+            u32::from_be_bytes(*magic),
+            0xfeedface | 0xfeedfacf | 0xcefaedfe | 0xcffaedfe | 0xcafebabe
+        )
+}
+
+// ── Binary resolution ─────────────────────────────────────────────────────────
+
+/// Health check for `spec` at `version`: the installed binary's path, or why
+/// it cannot be used (not installed, incomplete or corrupt).
+pub fn health(
+    host: &Host,
+    spec: &AdapterSpec,
+    platform: &PlatformInfo,
+    version: &str,
+) -> Result<String> {
+    let dest = binary_path(spec, platform, version);
+    if read_installed_version(host, spec, version).as_deref() != Some(version)
+        || !host.fs.exists(&dest)
+    {
+        return Err(VulneraError::NotInstalled {
+            binary: spec.name.to_string(),
+            reason: format!("version {} has not been downloaded", version),
+        }
+        .into());
+    }
+    check_executable(host, &dest)?;
+    Ok(dest)
+}
+
+/// Install `version` of `spec` unless it is already healthy, reporting each
+/// [`InstallState`] entered; see the [`crate::install`] module.
+pub fn ensure_binary(
+    host: &Host,
+    spec: &AdapterSpec,
+    platform: &PlatformInfo,
+    version: &str,
+    report: impl FnMut(&InstallState),
+) -> Result<String> {
+    let mut steps = AdapterInstall {
+        host,
+        spec,
+        platform,
+        version,
+        installed_now: false,
+        stats: InstallStats::default(),
+    };
+    let state = install::run(&mut steps, report);
+    if steps.stats.attempts > 0 {
+        let ok = matches!(state, InstallState::Ready { .. });
+        metrics::record_install(host, spec.name, version, &steps.stats, ok);
+    }
+    match state {
+        InstallState::Ready { path, .. } => {
+            if steps.installed_now {
+                let _ = host.fs.remove_file(&last_install_error_path(spec));
+                record_update(host, spec, version);
+            }
+            Ok(path)
+        }
+        InstallState::Failed(e) => {
+            error!("{} {} was not installed: {}", spec.name, version, e);
+            write_last_install_error(host, spec, version, &e);
+            Err(e)
+        }
+        state => Err(VulneraError::Internal {
+            reason: format!("install stopped while {:?}", state),
+        }
+        .into()),
+    }
+}
+
+/// The real [`InstallSteps`] for `spec`: downloads from GitHub Releases into
+/// `<dir>/<version>/`. The version is resolved by the caller.
+struct AdapterInstall<'a> {
+    host: &'a Host<'a>,
+    spec: &'a AdapterSpec,
+    platform: &'a PlatformInfo,
+    version: &'a str,
+    /// Whether a download was installed, as opposed to an existing binary reused.
+    installed_now: bool,
+    /// Download and verification counts for [`metrics`].
+    stats: InstallStats,
+}
+
+impl InstallSteps for AdapterInstall<'_> {
+    fn resolve_version(&mut self) -> String {
+        self.version.to_string()
+    }
+
+    fn installed(&mut self, version: &str) -> Result<String> {
+        let result = health(self.host, self.spec, self.platform, version);
+        match &result {
+            Ok(dest) => log!(
+                "{} {} already installed ({})",
+                self.spec.name,
+                version,
+                dest
+            ),
+            Err(reason) => debug!("{}; downloading", reason),
+        }
+        result
+    }
+
+    fn download(&mut self, version: &str) -> Result<()> {
+        let dir = version_dir(self.spec, version);
+        self.host
+            .fs
+            .create_dir_all(&dir)
+            .map_err(|e| VulneraError::fs(format!("create {}/", dir), e))?;
+
+        let url = release_asset_url(self.host, self.spec, self.platform, version)?;
+        let dest = binary_path(self.spec, self.platform, version);
+        log!(
+            "Downloading {} {} ({}) from {}",
+            self.spec.name,
+            version,
+            self.platform.target_triple,
+            url
+        );
+        self.stats.attempts += 1;
+        let started = self.host.clock.now_millis();
+        self.host
+            .fs
+            .download_file(&url, &dest)
+            .map_err(|e| VulneraError::download(&url, e))?;
+        self.stats.downloads += 1;
+        self.stats.download_ms += self.host.clock.now_millis().saturating_sub(started);
+        self.stats.bytes += self.host.fs.size(&dest).unwrap_or(0);
+        Ok(())
+    }
+
+    fn verify(&mut self, version: &str) -> Result<()> {
+        // Never mark an HTML error page or a truncated file installed.
+        let verified = check_executable(self.host, &binary_path(self.spec, self.platform, version));
+        if verified.is_ok() {
+            self.stats.verifications_passed += 1;
+        } else {
+            self.stats.verifications_failed += 1;
+        }
+        Ok(verified?)
+    }
+
+    fn install(&mut self, version: &str) -> Result<String> {
+        let dest = binary_path(self.spec, self.platform, version);
+        if !self.platform.is_windows {
+            self.host
+                .fs
+                .make_executable(&dest)
+                .map_err(|e| VulneraError::fs(format!("chmod +x {}", dest), e))?;
+        }
+        write_installed_version(self.host, self.spec, version);
+        self.installed_now = true;
+        log!("{} {} installed at {}", self.spec.name, version, dest);
+        Ok(dest)
+    }
+
+    fn rollback(&mut self, version: &str) {
+        let _ = self
+            .host
+            .fs
+            .remove_file(&binary_path(self.spec, self.platform, version));
+    }
+}
+
+/// Remember why the last install failed, for `/vulnera-doctor`.
+fn write_last_install_error(host: &Host, spec: &AdapterSpec, version: &str, error: &str) {
+    let record = format!("{}\n{}\n{}", host.clock.now_secs(), version, error);
+    if let Err(e) = host.fs.write(&last_install_error_path(spec), &record) {
+        warn!("Failed to record install error: {}", e);
+    }
+}
+
+/// The last install failure as `(unix time, version, error)`.
+pub fn read_last_install_error(host: &Host, spec: &AdapterSpec) -> Option<(u64, String, String)> {
+    let record = host
+        .fs
+        .read_to_string(&last_install_error_path(spec))
+        .ok()?;
+    let mut lines = record.splitn(3, '\n');
+    let at = lines.next()?.parse().ok()?;
+    Some((at, lines.next()?.to_string(), lines.next()?.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        CONTAINER_ADAPTER, CORE_ADAPTER, Channel, InstallState, PlatformInfo,
+        VERSION_CACHE_TTL_SECS, ensure_binary, health, is_executable_magic, is_semver,
+        latest_stable_release, parse_latest_version, parse_release_notes, release_asset_url,
+        resolve_adapter_version, strip_escape_sequences,
+    };
+    use crate::error::VulneraError;
+    use crate::host::{FixedClock, FixedPlatform, Host, MemFs};
+    use crate::http::Canned;
+    use zed_extension_api::{Architecture, GithubRelease, GithubReleaseAsset, Os};
+
+    const LINUX: PlatformInfo = PlatformInfo {
+        target_triple: "x86_64-unknown-linux-gnu",
+        is_windows: false,
+    };
+
+    fn host<'a>(http: &'a Canned, fs: &'a MemFs, now: &'a FixedClock) -> Host<'a> {
+        Host {
+            http,
+            fs,
+            clock: now,
+            platform: &FixedPlatform(Os::Linux, Architecture::X8664),
+            releases: Box::leak(Box::default()),
+            github_token: None,
+        }
+    }
+
+    #[test]
+    fn parses_stable_release() {
+        let json = r#"[
+            {"tag_name":"adapter-v0.2.0","prerelease":false,"draft":false,"body":"notes"},
+            {"tag_name":"adapter-v0.1.1","prerelease":false,"draft":false,"body":"notes"}
+        ]"#;
+        assert_eq!(
+            parse_latest_version(json, "adapter-v", Channel::Stable),
+            Some("0.2.0".to_string())
+        );
+    }
+
+    #[test]
+    fn skips_prerelease() {
+        let json = r#"[
+            {"tag_name":"adapter-v0.2.0-rc1","prerelease":true,"draft":false,"body":"notes"},
+            {"tag_name":"adapter-v0.1.1","prerelease":false,"draft":false,"body":"notes"}
+        ]"#;
+        assert_eq!(
+            parse_latest_version(json, "adapter-v", Channel::Stable),
+            Some("0.1.1".to_string())
+        );
+    }
+
+    #[test]
+    fn skips_draft() {
+        let json = r#"[
+            {"tag_name":"adapter-v0.2.0","prerelease":false,"draft":true,"body":"notes"},
+            {"tag_name":"adapter-v0.1.1","prerelease":false,"draft":false,"body":"notes"}
+        ]"#;
+        assert_eq!(
+            parse_latest_version(json, "adapter-v", Channel::Stable),
+            Some("0.1.1".to_string())
+        );
+    }
+
+    #[test]
+    fn ignores_non_adapter_tags() {
+        let json = r#"[
+            {"tag_name":"v1.0.0","prerelease":false,"draft":false,"body":"notes"},
+            {"tag_name":"adapter-v0.1.1","prerelease":false,"draft":false,"body":"notes"}
+        ]"#;
+        assert_eq!(
+            parse_latest_version(json, "adapter-v", Channel::Stable),
+            Some("0.1.1".to_string())
+        );
+    }
+
+    #[test]
+    fn returns_none_on_empty_list() {
+        assert_eq!(
+            parse_latest_version("[]", "adapter-v", Channel::Stable),
+            None
+        );
+    }
+
+    #[test]
+    fn selects_tags_by_prefix() {
+        let json = r#"[
+            {"tag_name":"container-adapter-v0.3.0","prerelease":false,"draft":false},
+            {"tag_name":"adapter-v0.2.0","prerelease":false,"draft":false}
+        ]"#;
+        assert_eq!(
+            parse_latest_version(json, "adapter-v", Channel::Stable),
+            Some("0.2.0".to_string())
+        );
+        assert_eq!(
+            parse_latest_version(json, "container-adapter-v", Channel::Stable),
+            Some("0.3.0".to_string())
+        );
+    }
+
+    #[test]
+    fn selects_releases_by_channel() {
+        let json = r#"[
+            {"tag_name":"adapter-v0.3.0-nightly.20261016","prerelease":true,"draft":false},
+            {"tag_name":"adapter-v0.3.0-rc.1","prerelease":true,"draft":false},
+            {"tag_name":"adapter-v0.2.0","prerelease":false,"draft":false}
+        ]"#;
+        let latest = |channel| parse_latest_version(json, "adapter-v", channel);
+        assert_eq!(latest(Channel::Stable), Some("0.2.0".to_string()));
+        assert_eq!(latest(Channel::Beta), Some("0.3.0-rc.1".to_string()));
+        assert_eq!(
+            latest(Channel::Nightly),
+            Some("0.3.0-nightly.20261016".to_string())
+        );
+    }
+
+    #[test]
+    fn parses_formatted_and_reordered_releases() {
+        let json = r#"[
+            {
+                "draft": false,
+                "prerelease": true,
+                "tag_name": "adapter-v0.3.0-rc1",
+                "body": "Pre-release of \"tag_name\":\"adapter-v9.9.9\""
+            },
+            {
+                "body": "Mentions \"prerelease\":true and \"draft\":true",
+                "tag_name": "adapter-v0.2.0",
+                "prerelease": false,
+                "draft": false
+            }
+        ]"#;
+        assert_eq!(
+            parse_latest_version(json, "adapter-v", Channel::Stable),
+            Some("0.2.0".to_string())
+        );
+        assert_eq!(
+            parse_latest_version("<html>rate limited</html>", "adapter-v", Channel::Stable),
+            None
+        );
+    }
+
+    #[test]
+    fn extracts_release_notes() {
+        let json = r###"[
+            {"tag_name":"adapter-v0.3.0","draft":false,"assets":[{"id":1},{"id":2}],
+             "body":"## Changes\r\n- New \"pnpm\" rules\r\n"},
+            {"tag_name":"adapter-v0.2.0","draft":false,"assets":[],"body":null}
+        ]"###;
+        assert_eq!(
+            parse_release_notes(json, "adapter-v0.3.0"),
+            Some("## Changes\n- New \"pnpm\" rules".to_string())
+        );
+        assert_eq!(parse_release_notes(json, "adapter-v0.2.0"), None);
+    }
+
+    #[test]
+    fn accepts_only_strict_semver_pins() {
+        for version in ["0.4.0", "1.10.2-rc.1", "1.0.0-alpha-1+build.5", "0.0.0"] {
+            assert!(is_semver(version), "{}", version);
+        }
+        for version in [
+            "0.4",
+            "v0.4.0",
+            "0.04.0",
+            "0.4.0-rc.01",
+            "0.4.0-",
+            "../../bin/sh",
+            "0.4.0/../../x",
+            "0.4.0?x=1",
+            "",
+        ] {
+            assert!(!is_semver(version), "{}", version);
+        }
+    }
+
+    #[test]
+    fn handles_escapes_in_release_bodies() {
+        // Bodies that fooled the old `},{` object boundary and quote scanning.
+        let json = r###"[
+            {"tag_name":"adapter-v0.4.0","draft":true,"prerelease":false,
+             "body":"Draft \\\"},{\"tag_name\":\"adapter-v0.3.9\",\"draft\":false"},
+            {"tag_name":"adapter-v0.3.0","prerelease":false,"draft":false,
+             "body":"Caf\u00e9 fixes \ud83d\ude80\r\n- Path C:\\tools\\\r\n- \u001b[31mred\u001b[0m\u0000"}
+        ]"###;
+        assert_eq!(
+            parse_latest_version(json, "adapter-v", Channel::Stable),
+            Some("0.3.0".to_string())
+        );
+        assert_eq!(
+            parse_release_notes(json, "adapter-v0.3.0"),
+            Some("Café fixes 🚀\n- Path C:\\tools\\\n- red".to_string())
+        );
+        assert_eq!(parse_release_notes(json, "adapter-v0.3.9"), None);
+        assert_eq!(
+            strip_escape_sequences(
+                "\u{1b}]8;;https://x\u{7}link\u{1b}]8;;\u{1b}\\ \u{1b}[1;4mbold\u{1b}(B"
+            ),
+            "link bold"
+        );
+    }
+
+    #[test]
+    fn selects_platform_assets() {
+        let linux = PlatformInfo {
+            target_triple: "x86_64-unknown-linux-gnu",
+            is_windows: false,
+        };
+        let asset = |name: &str| (name.to_string(), format!("https://dl/{}", name));
+        let renamed = [
+            asset("vulnera-adapter-x86_64-unknown-linux-gnu.sha256"),
+            asset("vulnera-container-adapter-x86_64-unknown-linux-gnu"),
+            asset("vulnera-adapter-0.3.0-x86_64-unknown-linux-gnu"),
+        ];
+        assert_eq!(
+            linux.select_asset(&CORE_ADAPTER, &renamed),
+            Some("https://dl/vulnera-adapter-0.3.0-x86_64-unknown-linux-gnu")
+        );
+        let exact = [
+            asset("vulnera-adapter-0.3.0-x86_64-unknown-linux-gnu"),
+            asset("vulnera-adapter-x86_64-unknown-linux-gnu"),
+        ];
+        assert_eq!(
+            linux.select_asset(&CORE_ADAPTER, &exact),
+            Some("https://dl/vulnera-adapter-x86_64-unknown-linux-gnu")
+        );
+        assert_eq!(
+            linux.select_asset(
+                &CORE_ADAPTER,
+                &[asset("vulnera-adapter-aarch64-apple-darwin")]
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn looks_up_releases_through_http_fetch() {
+        let other_binary = Canned {
+            releases: vec![(
+                "vulnera-rs/adapter".to_string(),
+                GithubRelease {
+                    version: "container-adapter-v0.4.0".to_string(),
+                    assets: Vec::new(),
+                },
+            )],
+            ..Default::default()
+        };
+        let (fs, now) = (MemFs::default(), FixedClock(0));
+        assert_eq!(
+            latest_stable_release(&host(&other_binary, &fs, &now), &CORE_ADAPTER),
+            None
+        );
+
+        // Without Zed's helper, assets come from the raw tag endpoint.
+        let linux = PlatformInfo {
+            target_triple: "x86_64-unknown-linux-gnu",
+            is_windows: false,
+        };
+        let http = Canned::default().with(
+            "https://api.github.com/repos/vulnera-rs/adapter/releases/tags/adapter-v0.3.0",
+            Ok(r#"{"tag_name":"adapter-v0.3.0","assets":[
+                {"name":"vulnera-adapter-x86_64-unknown-linux-gnu",
+                 "browser_download_url":"https://dl/linux"}]}"#),
+        );
+        assert_eq!(
+            release_asset_url(&host(&http, &fs, &now), &CORE_ADAPTER, &linux, "0.3.0"),
+            Ok("https://dl/linux".to_string())
+        );
+        let windows = PlatformInfo {
+            target_triple: "x86_64-pc-windows-msvc",
+            is_windows: true,
+        };
+        let missing = release_asset_url(&host(&http, &fs, &now), &CORE_ADAPTER, &windows, "0.3.0")
+            .unwrap_err();
+        assert!(matches!(
+            missing,
+            VulneraError::AssetMissing { found, .. }
+                if found == ["vulnera-adapter-x86_64-unknown-linux-gnu"]
+        ));
+    }
+
+    #[test]
+    fn cached_version_expires_after_ttl() {
+        let fetched_at = 1_000_000;
+        let cached = || {
+            MemFs::default()
+                .with_file("server/cached-version.txt", b"0.3.0\n")
+                .with_file(
+                    "server/cached-version-timestamp.txt",
+                    fetched_at.to_string().as_bytes(),
+                )
+        };
+        let github = Canned {
+            releases: vec![(
+                "vulnera-rs/adapter".to_string(),
+                GithubRelease {
+                    version: "adapter-v0.4.0".to_string(),
+                    assets: Vec::new(),
+                },
+            )],
+            ..Default::default()
+        };
+
+        let (fs, fresh) = (cached(), FixedClock(fetched_at + 60));
+        assert_eq!(
+            resolve_adapter_version(&host(&github, &fs, &fresh), &CORE_ADAPTER, &[], None),
+            "0.3.0"
+        );
+
+        let expired = FixedClock(fetched_at + VERSION_CACHE_TTL_SECS);
+        assert_eq!(
+            resolve_adapter_version(&host(&github, &fs, &expired), &CORE_ADAPTER, &[], None),
+            "0.4.0"
+        );
+        assert_eq!(
+            fs.file("server/cached-version-timestamp.txt").as_deref(),
+            Some(expired.0.to_string().as_str())
+        );
+
+        // Offline with an expired cache: the stale version beats the floor.
+        let (fs, offline) = (cached(), Canned::default());
+        assert_eq!(
+            resolve_adapter_version(&host(&offline, &fs, &expired), &CORE_ADAPTER, &[], None),
+            "0.3.0"
+        );
+        // A corrupt timestamp counts as expired.
+        let fs = cached().with_file("server/cached-version-timestamp.txt", b"\0\0garbage");
+        assert_eq!(
+            resolve_adapter_version(&host(&github, &fs, &fresh), &CORE_ADAPTER, &[], None),
+            "0.4.0"
+        );
+    }
+
+    #[test]
+    fn reinstalls_over_a_corrupt_marker() {
+        let asset_url = "https://dl/vulnera-adapter-x86_64-unknown-linux-gnu";
+        let github = Canned {
+            releases: vec![(
+                "vulnera-rs/adapter@adapter-v0.4.0".to_string(),
+                GithubRelease {
+                    version: "adapter-v0.4.0".to_string(),
+                    assets: vec![GithubReleaseAsset {
+                        name: "vulnera-adapter-x86_64-unknown-linux-gnu".to_string(),
+                        download_url: asset_url.to_string(),
+                    }],
+                },
+            )],
+            ..Default::default()
+        };
+        let binary = "server/0.4.0/vulnera-adapter";
+        let marker = "server/0.4.0/installed-version.txt";
+        let now = FixedClock(0);
+
+        // An interrupted install left the binary but an empty marker.
+        let fs = MemFs {
+            downloads: vec![(asset_url.to_string(), b"\x7fELF\x02\x01".to_vec())],
+            ..Default::default()
+        }
+        .with_file(binary, b"\x7fELF")
+        .with_file(marker, b"");
+        let host = host(&github, &fs, &now);
+        assert!(health(&host, &CORE_ADAPTER, &LINUX, "0.4.0").is_err());
+
+        let mut states = Vec::new();
+        assert_eq!(
+            ensure_binary(&host, &CORE_ADAPTER, &LINUX, "0.4.0", |state| {
+                states.push(state.clone())
+            }),
+            Ok(binary.to_string())
+        );
+        assert!(
+            states
+                .iter()
+                .any(|s| matches!(s, InstallState::Downloading { .. }))
+        );
+        assert_eq!(fs.file(marker).as_deref(), Some("0.4.0"));
+        assert_eq!(
+            health(&host, &CORE_ADAPTER, &LINUX, "0.4.0"),
+            Ok(binary.to_string())
+        );
+    }
+
+    #[test]
+    fn rolls_back_a_download_that_is_not_executable() {
+        let asset_url = "https://dl/vulnera-adapter-x86_64-unknown-linux-gnu";
+        let github = Canned {
+            releases: vec![(
+                "vulnera-rs/adapter@adapter-v0.4.0".to_string(),
+                GithubRelease {
+                    version: "adapter-v0.4.0".to_string(),
+                    assets: vec![GithubReleaseAsset {
+                        name: "vulnera-adapter-x86_64-unknown-linux-gnu".to_string(),
+                        download_url: asset_url.to_string(),
+                    }],
+                },
+            )],
+            ..Default::default()
+        };
+        let fs = MemFs {
+            downloads: vec![(asset_url.to_string(), b"<!DOCTYPE html>".to_vec())],
+            ..Default::default()
+        };
+        let now = FixedClock(0);
+        let host = host(&github, &fs, &now);
+
+        let error = ensure_binary(&host, &CORE_ADAPTER, &LINUX, "0.4.0", |_| {}).unwrap_err();
+        assert!(error.contains("not a native executable"), "{}", error);
+        assert!(fs.file("server/0.4.0/vulnera-adapter").is_none());
+        assert!(fs.file("server/0.4.0/installed-version.txt").is_none());
+        assert!(fs.file("server/last-install-error.txt").is_some());
+    }
+
+    #[test]
+    fn recognises_executable_formats() {
+        assert!(is_executable_magic(b"\x7fELF"));
+        assert!(is_executable_magic(b"MZ\x90\0"));
+        assert!(is_executable_magic(&[0xcf, 0xfa, 0xed, 0xfe]));
+        assert!(!is_executable_magic(b"<!DO"));
+        assert!(!is_executable_magic(b"Not "));
+    }
+
+    #[test]
+    fn expands_asset_patterns() {
+        let windows = PlatformInfo {
+            target_triple: "x86_64-pc-windows-msvc",
+            is_windows: true,
+        };
+        assert_eq!(
+            windows.asset_name(&CORE_ADAPTER),
+            "vulnera-adapter-x86_64-pc-windows-msvc.exe"
+        );
+        let linux = PlatformInfo {
+            target_triple: "aarch64-unknown-linux-gnu",
+            is_windows: false,
+        };
+        assert_eq!(
+            linux.asset_name(&CONTAINER_ADAPTER),
+            "vulnera-container-adapter-aarch64-unknown-linux-gnu"
+        );
+    }
+}
@@ -0,0 +1,155 @@
+//! The `vulnera-advisories` docs provider.
+//!
+//! Suggests the worktree's locked dependencies as packages (see
+//! [`slash_commands::read_dependencies`]) and indexes the OSV advisories
+//! for a package into Zed's docs database: one overview entry plus one entry
+//! per advisory. OSV responses are cached per package under `docs/` for
+//! [`DOCS_CACHE_TTL_SECS`], so re-indexing only refetches stale packages and
+//! falls back to the stale copy when OSV is unreachable.
+
+use std::fs;
+
+use zed_extension_api::serde_json::{self, Value, json};
+use zed_extension_api::{KeyValueStore, Result};
+
+use crate::error::VulneraError;
+use crate::http::ZedHttp;
+use crate::lockfiles::Dependency;
+use crate::{now_secs, osv, slash_commands};
+
+/// Provider name registered in `extension.toml`.
+pub const PROVIDER: &str = "vulnera-advisories";
+
+/// How long a package's advisories are served from the cache.
+const DOCS_CACHE_TTL_SECS: u64 = 6 * 60 * 60;
+
+/// Package names to suggest in the docs picker.
+pub fn suggest_packages() -> Vec<String> {
+    let mut names: Vec<String> = slash_commands::read_dependencies()
+        .into_iter()
+        .map(|dep| dep.name)
+        .collect();
+    names.sort();
+    names.dedup();
+    names
+}
+
+/// Index the advisories for `package` into `database`.
+pub fn index(package: &str, database: &KeyValueStore) -> Result<()> {
+    let deps: Vec<Dependency> = slash_commands::read_dependencies()
+        .into_iter()
+        .filter(|dep| dep.name == package)
+        .collect();
+    let ecosystem = deps.first().map(Dependency::osv_ecosystem).ok_or_else(|| {
+        VulneraError::BadArgument {
+            command: "docs".to_string(),
+            reason: format!(
+                "'{}' is not a locked dependency of the open project",
+                package
+            ),
+        }
+        .to_string()
+    })?;
+
+    let vulns = cached_or_query(package, ecosystem)?;
+    let versions: Vec<&str> = deps.iter().map(|dep| dep.version.as_str()).collect();
+    database.insert(package, &render_overview(package, &versions, &vulns))?;
+    for vuln in &vulns {
+        if let Some(id) = vuln["id"].as_str() {
+            database.insert(
+                &format!("{}/{}", package, id),
+                &slash_commands::render_advisory(vuln),
+            )?;
+        }
+    }
+    Ok(())
+}
+
+fn cache_path(package: &str, ecosystem: &str) -> String {
+    let file: String = format!("{}-{}", ecosystem, package)
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '.' || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    format!("docs/{}.json", file)
+}
+
+fn cached_or_query(package: &str, ecosystem: &str) -> Result<Vec<Value>> {
+    let path = cache_path(package, ecosystem);
+    let cached: Option<Value> = fs::read_to_string(&path)
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok());
+    let fetched_at = cached
+        .as_ref()
+        .and_then(|c| c["fetchedAt"].as_u64())
+        .unwrap_or(0);
+    let cached_vulns = || {
+        cached
+            .as_ref()
+            .and_then(|c| c["vulns"].as_array().cloned())
+            .unwrap_or_default()
+    };
+
+    if now_secs().saturating_sub(fetched_at) < DOCS_CACHE_TTL_SECS {
+        return Ok(cached_vulns());
+    }
+    match osv::query(&ZedHttp, package, ecosystem, None) {
+        Ok(vulns) => {
+            let entry = json!({ "fetchedAt": now_secs(), "vulns": vulns });
+            if let Err(e) =
+                fs::create_dir_all("docs").and_then(|()| fs::write(&path, entry.to_string()))
+            {
+                warn!("Failed to cache advisories for {}: {}", package, e);
+            }
+            Ok(vulns)
+        }
+        Err(e) if cached.is_some() => {
+            log!("{}; using cached advisories for {}", e, package);
+            Ok(cached_vulns())
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// The package's entry: the locked versions and one line per advisory.
+fn render_overview(package: &str, versions: &[&str], vulns: &[Value]) -> String {
+    let mut out = format!(
+        "# {}\n\nLocked versions: {}\n\n## Advisories\n\n",
+        package,
+        versions.join(", ")
+    );
+    if vulns.is_empty() {
+        out.push_str("No known advisories.\n");
+    }
+    for vuln in vulns {
+        out.push_str(&format!(
+            "- {}: {}\n",
+            vuln["id"].as_str().unwrap_or("?"),
+            vuln["summary"].as_str().unwrap_or("no summary"),
+        ));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{cache_path, render_overview};
+    use zed_extension_api::serde_json::json;
+
+    #[test]
+    fn renders_package_overview() {
+        let vulns =
+            [json!({ "id": "GHSA-jf85-cpcp-j695", "summary": "Prototype Pollution in lodash" })];
+        assert_eq!(
+            render_overview("lodash", &["4.17.10"], &vulns),
+            "# lodash\n\nLocked versions: 4.17.10\n\n## Advisories\n\n\
+             - GHSA-jf85-cpcp-j695: Prototype Pollution in lodash\n"
+        );
+        assert_eq!(cache_path("@scope/pkg", "npm"), "docs/npm-_scope_pkg.json");
+    }
+}
@@ -0,0 +1,100 @@
+//! Release channels of the managed adapters.
+//!
+//! `stable` follows full releases, `beta` adds `-beta`/`-rc` prereleases and
+//! `nightly` takes any published release. Each channel keeps its own install
+//! directory, so version caches and installed-version markers never mix:
+//! `stable` uses the adapter's directory as before (`server/`), other channels
+//! a sibling (`server-beta/`, `server-nightly/`).
+//! Switching channels needs no migration: the session's memoized versions
+//! are keyed by channel too, and binaries already installed on either
+//! channel are kept for switching back.
+
+use std::fmt;
+
+use serde::Deserialize;
+
+use crate::adapters::VERSION_CACHE_TTL_SECS;
+
+/// Which releases of an adapter are installed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Channel {
+    /// Full releases only.
+    #[default]
+    Stable,
+    /// Full releases and `-beta` / `-rc` prereleases.
+    Beta,
+    /// Every published release.
+    Nightly,
+}
+
+impl Channel {
+    /// Every channel, stable first.
+    pub const ALL: [Channel; 3] = [Channel::Stable, Channel::Beta, Channel::Nightly];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Channel::Stable => "stable",
+            Channel::Beta => "beta",
+            Channel::Nightly => "nightly",
+        }
+    }
+
+    /// Install directory for an adapter whose stable directory is `base`.
+    pub fn install_dir(self, base: &str) -> String {
+        match self {
+            Channel::Stable => base.to_string(),
+            channel => format!("{}-{}", base, channel.name()),
+        }
+    }
+
+    /// How long a cached latest version stays fresh; prerelease channels
+    /// publish more often and are checked more often.
+    pub fn cache_ttl_secs(self) -> u64 {
+        match self {
+            Channel::Stable => VERSION_CACHE_TTL_SECS,
+            Channel::Beta => 6 * 60 * 60,
+            Channel::Nightly => 60 * 60,
+        }
+    }
+
+    /// Whether a non-draft release of `version` belongs to this channel.
+    pub fn accepts(self, version: &str, prerelease: bool) -> bool {
+        match self {
+            Channel::Stable => !prerelease,
+            Channel::Beta => !prerelease || version.contains("-beta") || version.contains("-rc"),
+            Channel::Nightly => true,
+        }
+    }
+}
+
+impl fmt::Display for Channel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Channel;
+
+    #[test]
+    fn scopes_directories_by_channel() {
+        assert_eq!(Channel::Stable.install_dir("server"), "server");
+        assert_eq!(Channel::Beta.install_dir("server"), "server-beta");
+        assert_eq!(
+            Channel::Nightly.install_dir("container"),
+            "container-nightly"
+        );
+        assert!(Channel::Nightly.cache_ttl_secs() < Channel::Stable.cache_ttl_secs());
+    }
+
+    #[test]
+    fn channels_accept_their_releases() {
+        assert!(Channel::Stable.accepts("0.2.0", false));
+        assert!(!Channel::Stable.accepts("0.3.0-rc.1", true));
+        assert!(Channel::Beta.accepts("0.3.0-rc.1", true));
+        assert!(!Channel::Beta.accepts("0.3.0-nightly.20261016", true));
+        assert!(Channel::Nightly.accepts("0.3.0-nightly.20261016", true));
+    }
+}
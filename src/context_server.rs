@@ -0,0 +1,178 @@
+//! The `vulnera` MCP context server for Zed's agent.
+//!
+//! The adapter doubles as an MCP server (`vulnera-adapter mcp`, stdio
+//! transport) exposing the [`TOOLS`] below, so the agent can scan the project
+//! and look up advisories itself during agentic sessions — including vetting a
+//! dependency with `check_dependency` before writing it into a manifest. The
+//! SBOMs kept by the [`sbom`](crate::sbom) module are served as resources.
+//! Options come from `context_servers.vulnera.settings`; a `command` there
+//! replaces the adapter entirely.
+
+use serde::Deserialize;
+use zed_extension_api::serde_json::{self, Value};
+use zed_extension_api::{self as zed, ContextServerConfiguration, Result};
+
+use crate::error::VulneraError;
+
+/// Tools served by `vulnera-adapter mcp`.
+pub const TOOLS: &[(&str, &str)] = &[
+    (
+        "scan_workspace",
+        "Scan the project's manifests and lockfiles for vulnerable dependencies",
+    ),
+    (
+        "lookup_advisory",
+        "Fetch an advisory (GHSA, CVE, RUSTSEC, …) or the advisories affecting package@version",
+    ),
+    (
+        "suggest_fix",
+        "Suggest the version bumps that clear the current findings",
+    ),
+    (
+        "check_dependency",
+        "Check a dependency (name, version, ecosystem) for known advisories before adding it to a manifest",
+    ),
+];
+
+/// `context_servers.vulnera.settings`.
+#[derive(Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(default, deny_unknown_fields)]
+pub struct ContextServerOptions {
+    /// Tools to expose; all of [`TOOLS`] when empty.
+    pub tools: Vec<String>,
+    pub api_url: Option<String>,
+    pub api_key: Option<String>,
+    pub offline_db_path: Option<String>,
+}
+
+impl ContextServerOptions {
+    pub fn parse(settings: Option<Value>) -> Result<Self> {
+        let options: Self = match settings {
+            Some(value) => serde_json::from_value(value)
+                .map_err(|e| VulneraError::config("context_servers.vulnera.settings", e))?,
+            None => Self::default(),
+        };
+        if let Some(unknown) = options
+            .tools
+            .iter()
+            .find(|t| !TOOLS.iter().any(|(name, _)| name == t))
+        {
+            return Err(VulneraError::config(
+                "context_servers.vulnera.settings.tools",
+                format!("unknown tool '{}'", unknown),
+            )
+            .into());
+        }
+        Ok(options)
+    }
+}
+
+/// The `vulnera-adapter mcp` invocation for `binary`, serving the SBOMs in
+/// `sbom_dir` as resources.
+pub fn command(
+    binary: String,
+    options: &ContextServerOptions,
+    sbom_dir: Option<String>,
+) -> zed::Command {
+    let mut args = vec!["mcp".to_string()];
+    if let Some(dir) = sbom_dir {
+        args.push("--sbom-dir".to_string());
+        args.push(dir);
+    }
+    if !options.tools.is_empty() {
+        args.push("--tools".to_string());
+        args.push(options.tools.join(","));
+    }
+
+    let mut env = Vec::new();
+    match &options.offline_db_path {
+        Some(db) => env.push(("VULNERA_OFFLINE_DB".to_string(), db.clone())),
+        None => {
+            if let Some(url) = &options.api_url {
+                env.push(("VULNERA_API_URL".to_string(), url.clone()));
+            }
+            if let Some(key) = &options.api_key {
+                env.push(("VULNERA_API_KEY".to_string(), key.clone()));
+            }
+        }
+    }
+
+    zed::Command {
+        command: binary,
+        args,
+        env,
+    }
+}
+
+/// Setup help and settings schema shown in the agent's context server panel.
+pub fn configuration() -> ContextServerConfiguration {
+    let tools: Vec<&str> = TOOLS.iter().map(|(name, _)| *name).collect();
+    let tool_list: String = TOOLS
+        .iter()
+        .map(|(name, description)| format!("- `{}` — {}\n", name, description))
+        .collect();
+
+    ContextServerConfiguration {
+        installation_instructions: format!(
+            "The Vulnera adapter is downloaded automatically and serves these tools:\n\n{}\n\
+             It also serves each open project's SBOM as a `vulnera://sbom/…` resource.\n\
+             Set `api_key` for authenticated lookups, or `offline_db_path` to work offline.",
+            tool_list
+        ),
+        settings_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "tools": {
+                    "type": "array",
+                    "items": { "enum": tools },
+                    "description": "Tools to expose (default: all)"
+                },
+                "api_url": { "type": "string" },
+                "api_key": { "type": "string" },
+                "offline_db_path": { "type": "string" }
+            },
+            "additionalProperties": false
+        })
+        .to_string(),
+        default_settings: "{\n  \"tools\": []\n}".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ContextServerOptions, command};
+    use zed_extension_api::serde_json::json;
+
+    #[test]
+    fn builds_mcp_command_from_settings() {
+        let options = ContextServerOptions::parse(Some(json!({
+            "tools": ["scan_workspace", "suggest_fix"],
+            "api_key": "secret",
+            "offline_db_path": "/data/osv.db"
+        })))
+        .unwrap();
+        let command = command(
+            "/bin/vulnera-adapter".to_string(),
+            &options,
+            Some("/ext/sbom".to_string()),
+        );
+
+        assert_eq!(
+            command.args,
+            [
+                "mcp",
+                "--sbom-dir",
+                "/ext/sbom",
+                "--tools",
+                "scan_workspace,suggest_fix"
+            ]
+        );
+        // Offline mode withholds the API key, as for the language server.
+        assert_eq!(
+            command.env,
+            [("VULNERA_OFFLINE_DB".to_string(), "/data/osv.db".to_string())]
+        );
+
+        assert!(ContextServerOptions::parse(Some(json!({ "tools": ["rm_rf"] }))).is_err());
+    }
+}
@@ -0,0 +1,83 @@
+//! Restart-storm detection for freshly installed adapters.
+//!
+//! Zed calls `language_server_command` again each time the server exits, so
+//! a release that crashes on startup shows up as launches of the same
+//! version, by the same language server for the same worktree, in quick
+//! succession. Keying by server keeps the secondary servers, which run the
+//! same binary, from adding up to a storm on an ordinary start.
+//! [`LaunchHistory`] remembers recent launch times; when
+//! [`CRASH_LOOP_LAUNCHES`] fall within [`CRASH_LOOP_WINDOW_SECS`] the caller
+//! treats the version as broken and goes back to the one it replaced (see
+//! [`crate::adapters::mark_bad_version`]). Only launches within
+//! [`FRESH_INSTALL_SECS`] of a version's first launch are counted, so an
+//! established release is never flagged for restarts the user asked for.
+
+use std::collections::HashMap;
+
+/// Launches within [`CRASH_LOOP_WINDOW_SECS`] that count as a restart storm.
+pub const CRASH_LOOP_LAUNCHES: usize = 4;
+
+/// How far back launches are counted.
+pub const CRASH_LOOP_WINDOW_SECS: u64 = 90;
+
+/// How long after its first launch a version counts as freshly installed.
+pub const FRESH_INSTALL_SECS: u64 = 10 * 60;
+
+/// Recent launch times per language server, version and worktree.
+#[derive(Debug, Default)]
+pub struct LaunchHistory {
+    launches: HashMap<(String, String, String), Vec<u64>>,
+}
+
+impl LaunchHistory {
+    /// Record a launch of `version` by language server `server` for
+    /// `worktree` at `now`, and report whether it completes a restart storm.
+    /// The history of that launch is cleared once a storm is reported.
+    pub fn record(&mut self, server: &str, version: &str, worktree: &str, now: u64) -> bool {
+        let key = (
+            server.to_string(),
+            version.to_string(),
+            worktree.to_string(),
+        );
+        let times = self.launches.entry(key.clone()).or_default();
+        times.retain(|at| now.saturating_sub(*at) < CRASH_LOOP_WINDOW_SECS);
+        times.push(now);
+        let storm = times.len() >= CRASH_LOOP_LAUNCHES;
+        if storm {
+            self.launches.remove(&key);
+        }
+        storm
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CRASH_LOOP_LAUNCHES, CRASH_LOOP_WINDOW_SECS, LaunchHistory};
+
+    #[test]
+    fn detects_quick_restarts_only() {
+        let mut history = LaunchHistory::default();
+        let storm: Vec<bool> = (0..CRASH_LOOP_LAUNCHES as u64)
+            .map(|i| history.record("vulnera", "0.4.0", "/work/a", 1000 + i * 5))
+            .collect();
+        assert_eq!(storm.iter().filter(|s| **s).count(), 1);
+        assert!(storm.last().copied().unwrap_or(false));
+
+        // Restarts spread out over time, or across worktrees or servers, are
+        // normal.
+        let mut history = LaunchHistory::default();
+        for i in 0..CRASH_LOOP_LAUNCHES as u64 * 2 {
+            let at = 1000 + i * CRASH_LOOP_WINDOW_SECS / 2;
+            assert!(!history.record("vulnera", "0.4.0", "/work/a", at));
+        }
+        let mut history = LaunchHistory::default();
+        for i in 0..CRASH_LOOP_LAUNCHES {
+            let worktree = format!("/work/{}", i);
+            assert!(!history.record("vulnera", "0.4.0", &worktree, 1000));
+        }
+        let mut history = LaunchHistory::default();
+        for server in ["vulnera", "vulnera-secrets", "vulnera-iac", "vulnera"] {
+            assert!(!history.record(server, "0.4.0", "/work/a", 1000));
+        }
+    }
+}
@@ -0,0 +1,256 @@
+//! `/vulnera-doctor`: a self-diagnostic report for bug reports.
+//!
+//! Collects what the extension resolved — platform, adapter version and path,
+//! version-cache freshness, API reachability, overrides in effect, the last
+//! install error — into one Markdown report. Secrets are masked.
+//!
+//! `/vulnera-version` is the short form: per managed binary, the installed and
+//! latest versions, the version-cache age and any pin in effect.
+
+use zed_extension_api::http_client::{HttpMethod, HttpRequest, RedirectPolicy};
+use zed_extension_api::{self as zed, Worktree};
+
+use crate::adapters::{
+    self, AdapterSpec, CORE_ADAPTER, PlatformInfo, read_cached_latest_version,
+    read_last_install_error, resolve_platform,
+};
+use crate::host::Host;
+use crate::http::{HttpFetch, ZedHttp};
+use crate::metrics;
+use crate::overrides::OVERRIDES_FILE;
+use crate::state;
+use crate::{LaunchContext, env_value, logging, now_secs, resolve_project_config};
+
+/// API endpoint the adapter uses when `VULNERA_API_URL` is unset.
+const DEFAULT_API_URL: &str = "https://api.vulnera.studio";
+
+/// `spec`'s resolved version and install health.
+fn adapter_health(ctx: &LaunchContext, spec: &AdapterSpec, platform: &PlatformInfo) -> String {
+    let spec = &ctx.on_channel(spec);
+    let version = ctx.adapter_version(&Host::REAL, spec);
+    match adapters::health(&Host::REAL, spec, platform, &version) {
+        Ok(path) => format!("{} at {} (installed)", version, path),
+        Err(reason) => format!("{} ({})", version, reason),
+    }
+}
+
+/// The cached latest version of `spec`, with its freshness.
+fn version_cache(spec: &AdapterSpec) -> String {
+    match read_cached_latest_version(&Host::REAL, spec) {
+        Some((version, fetched_at)) => {
+            let age = now_secs().saturating_sub(fetched_at);
+            let state = if age < spec.channel.cache_ttl_secs() {
+                "fresh"
+            } else {
+                "stale"
+            };
+            format!("{} ({}, {} h old)", version, state, age / 3600)
+        }
+        None => "empty".to_string(),
+    }
+}
+
+/// Installed, latest and selected versions of every managed binary.
+pub fn version_report(worktree: Option<&Worktree>) -> String {
+    let ctx = worktree.and_then(|w| LaunchContext::for_worktree(w).ok());
+    let mut out = String::from("## Vulnera versions\n");
+    for spec in adapters::ALL {
+        let spec = &match &ctx {
+            Some(ctx) => ctx.on_channel(spec),
+            None => **spec,
+        };
+        out.push_str(&format!("\n### {}\n\n", spec.name));
+        let mut line =
+            |key: &str, value: String| out.push_str(&format!("- **{}**: {}\n", key, value));
+        line("Channel", spec.channel.to_string());
+        line(
+            "Installed",
+            adapters::newest_installed(&Host::REAL, spec, None)
+                .unwrap_or_else(|| "none".to_string()),
+        );
+        line("Latest (cached)", version_cache(spec));
+        if let Some(available) = adapters::read_update_available(&Host::REAL, spec) {
+            line(
+                "Update available",
+                format!("{} (run /vulnera-update)", available),
+            );
+        }
+        match &ctx {
+            Some(ctx) => {
+                line(
+                    "Pin",
+                    ctx.version_pin(spec)
+                        .map(|(version, source)| format!("{} from {}", version, source))
+                        .unwrap_or_else(|| "none".to_string()),
+                );
+                line(
+                    "Selected for this project",
+                    ctx.adapter_version(&Host::REAL, spec),
+                );
+            }
+            None => line("Pin", "open a project to check pins".to_string()),
+        }
+    }
+    logging::redact(&out)
+}
+
+/// Build the diagnostic report, for `worktree` if a project is open.
+pub fn report(worktree: Option<&Worktree>) -> String {
+    let mut out = String::from("## Vulnera doctor\n\n");
+    let mut line = |key: &str, value: String| out.push_str(&format!("- **{}**: {}\n", key, value));
+
+    line("Extension", adapters::EXTENSION_VERSION.to_string());
+
+    let (os, arch) = zed::current_platform();
+    let platform = resolve_platform(os, arch);
+    line(
+        "Platform",
+        match &platform {
+            Ok(p) => format!("{:?} / {:?} → {}", os, arch, p.asset_name(&CORE_ADAPTER)),
+            Err(e) => e.to_string(),
+        },
+    );
+
+    line("Version cache", version_cache(&CORE_ADAPTER));
+    for (label, value) in metrics::read(&Host::REAL).summary() {
+        line(label, value);
+    }
+    let state = state::read(&Host::REAL);
+    line(
+        "Last update check",
+        match (state.last_update_check, state.network_unavailable_since) {
+            (None, _) => "never".to_string(),
+            (Some(at), None) => format!("{} h ago", now_secs().saturating_sub(at) / 3600),
+            (Some(at), Some(since)) => format!(
+                "{} h ago, GitHub unreachable since {} h ago",
+                now_secs().saturating_sub(at) / 3600,
+                now_secs().saturating_sub(since) / 3600
+            ),
+        },
+    );
+    line(
+        "Last known good",
+        state
+            .last_known_good
+            .get(&CORE_ADAPTER.install_dir())
+            .cloned()
+            .unwrap_or_else(|| "none yet".to_string()),
+    );
+
+    if let Some(refused) = adapters::known_incompatible(&Host::REAL, &CORE_ADAPTER) {
+        line("Newest release", refused.to_string());
+    }
+
+    if state
+        .floor_warned_at
+        .contains_key(&CORE_ADAPTER.install_dir())
+    {
+        line(
+            "Minimum version in use",
+            adapters::floor_warning(&CORE_ADAPTER),
+        );
+    }
+
+    line(
+        "Last install error",
+        match read_last_install_error(&Host::REAL, &CORE_ADAPTER) {
+            Some((at, version, error)) => format!(
+                "{} ({} h ago, adapter {})",
+                error,
+                now_secs().saturating_sub(at) / 3600,
+                version
+            ),
+            None => "none".to_string(),
+        },
+    );
+
+    let Some(worktree) = worktree else {
+        out.push_str("\nOpen a project for adapter, settings and API checks.\n");
+        return logging::redact(&out);
+    };
+
+    let ctx = match LaunchContext::for_worktree(worktree) {
+        Ok(ctx) => ctx,
+        Err(e) => {
+            out.push_str(&format!("- **Worktree**: {}\n", e));
+            return logging::redact(&out);
+        }
+    };
+    let mut line = |key: &str, value: String| out.push_str(&format!("- **{}**: {}\n", key, value));
+
+    line("Worktree", ctx.worktree_root.clone());
+    line(
+        "Adapter",
+        match (ctx.binary_override(), &platform) {
+            (Some((path, source)), _) => format!("{} (user override via {})", path, source),
+            (None, Ok(platform)) => adapter_health(&ctx, &CORE_ADAPTER, platform),
+            (None, Err(_)) => "unavailable on this platform".to_string(),
+        },
+    );
+    if let Ok(platform) = &platform {
+        for spec in adapters::ALL.iter().filter(|spec| !spec.is_core()) {
+            line(
+                &format!("Adapter ({})", spec.server_id),
+                adapter_health(&ctx, spec, platform),
+            );
+        }
+    }
+
+    let mut env_keys: Vec<&str> = ctx
+        .shell_env
+        .iter()
+        .filter(|(k, v)| k.starts_with("VULNERA_") && !v.trim().is_empty())
+        .map(|(k, _)| k.as_str())
+        .collect();
+    env_keys.sort();
+    line(
+        "Env overrides",
+        if env_keys.is_empty() {
+            "none".to_string()
+        } else {
+            env_keys.join(", ")
+        },
+    );
+    line(
+        "Worktree overrides",
+        if worktree.read_text_file(OVERRIDES_FILE).is_ok() {
+            format!("{:?}", ctx.overrides)
+        } else {
+            "none".to_string()
+        },
+    );
+    line(
+        "Project config",
+        resolve_project_config(&ctx.shell_env, worktree).unwrap_or_else(|| "none".to_string()),
+    );
+
+    line(
+        "API",
+        match &ctx.offline_db {
+            Some(db) => format!("offline mode ({}); not checked", db),
+            None => {
+                let url = env_value(&ctx.shell_env, "VULNERA_API_URL").unwrap_or(DEFAULT_API_URL);
+                format!("{} — {}", url, check_reachable(&ZedHttp, url))
+            }
+        },
+    );
+
+    logging::redact(&out)
+}
+
+fn check_reachable(http: &dyn HttpFetch, url: &str) -> String {
+    let request = HttpRequest {
+        url: url.to_string(),
+        method: HttpMethod::Get,
+        headers: vec![(
+            "User-Agent".to_string(),
+            "vulnera-zed-extension".to_string(),
+        )],
+        body: None,
+        redirect_policy: RedirectPolicy::FollowLimit(3),
+    };
+    match http.fetch(&request) {
+        Ok(_) => "reachable".to_string(),
+        Err(e) => format!("unreachable or error: {}", e),
+    }
+}
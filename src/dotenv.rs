@@ -0,0 +1,187 @@
+//! Scan-option variables from a worktree `.env` / `.env.vulnera` file.
+//!
+//! Zed launched from the dock or Finder does not see shell exports, so these
+//! files give GUI users the scan knobs. Real shell variables always win, and
+//! `.env.vulnera` wins over `.env`.
+//!
+//! The files come with the repository, so unless the worktree is listed in
+//! `trusted_paths` only [`SCAN_KEYS`] are read from them. Anything that picks
+//! an executable, an endpoint, a credential, a version or the log output
+//! (`VULNERA_ADAPTER_PATH`, `VULNERA_API_URL`, `VULNERA_GITHUB_TOKEN`, …) is
+//! then left to the user's shell.
+
+use crate::host::Checkout;
+
+/// Files consulted, lowest precedence first.
+const DOTENV_FILES: &[&str] = &[".env", ".env.vulnera"];
+
+/// The variables a repository may set: they only change what is scanned.
+pub const SCAN_KEYS: &[&str] = &["VULNERA_OFFLINE_DB", "VULNERA_TELEMETRY"];
+
+/// Return `shell_env` extended with entries from the worktree's dotenv files
+/// that the shell environment does not already define: every `VULNERA_*`
+/// variable in an `explicitly_trusted` worktree, else only [`SCAN_KEYS`].
+pub fn merge_worktree_dotenv(
+    shell_env: Vec<(String, String)>,
+    worktree: &dyn Checkout,
+    explicitly_trusted: bool,
+) -> Vec<(String, String)> {
+    let files: Vec<_> = DOTENV_FILES
+        .iter()
+        .filter_map(|file| Some((*file, worktree.read_text_file(file).ok()?)))
+        .collect();
+    merge(shell_env, &files, explicitly_trusted)
+}
+
+/// [`merge_worktree_dotenv`] over `(file name, contents)` pairs.
+fn merge(
+    mut shell_env: Vec<(String, String)>,
+    files: &[(&str, String)],
+    explicitly_trusted: bool,
+) -> Vec<(String, String)> {
+    let mut from_files: Vec<(String, String)> = Vec::new();
+
+    for (file, contents) in files {
+        for (key, value) in parse(contents) {
+            if !key.starts_with("VULNERA_") {
+                continue;
+            }
+            if !explicitly_trusted && !SCAN_KEYS.contains(&key.as_str()) {
+                warn!(
+                    "Ignoring {} from the worktree {}: set it in your shell, or add the \
+                     worktree to trusted_paths",
+                    key, file
+                );
+                continue;
+            }
+            from_files.retain(|(k, _)| *k != key);
+            from_files.push((key, value));
+        }
+    }
+
+    for (key, value) in from_files {
+        if !shell_env.iter().any(|(k, _)| *k == key) {
+            log!("Using {} from worktree dotenv file", key);
+            shell_env.push((key, value));
+        }
+    }
+
+    shell_env
+}
+
+/// Parse `KEY=VALUE` lines. Supports `export` prefixes, `#` comments, single
+/// quotes (literal), and double quotes (with `\n`, `\"`, `\\` escapes).
+fn parse(contents: &str) -> Vec<(String, String)> {
+    contents
+        .lines()
+        .filter_map(|line| {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                return None;
+            }
+            let line = line.strip_prefix("export ").unwrap_or(line);
+            let (key, raw) = line.split_once('=')?;
+            let key = key.trim();
+            if key.is_empty() || key.contains(char::is_whitespace) {
+                return None;
+            }
+            Some((key.to_string(), parse_value(raw.trim())))
+        })
+        .collect()
+}
+
+fn parse_value(raw: &str) -> String {
+    if let Some(inner) = raw.strip_prefix('\'') {
+        return inner.split('\'').next().unwrap_or_default().to_string();
+    }
+
+    if let Some(inner) = raw.strip_prefix('"') {
+        let mut value = String::new();
+        let mut chars = inner.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '"' => break,
+                '\\' => match chars.next() {
+                    Some('n') => value.push('\n'),
+                    Some(c) => value.push(c),
+                    None => break,
+                },
+                c => value.push(c),
+            }
+        }
+        return value;
+    }
+
+    // Unquoted: an inline comment starts at ` #`.
+    match raw.find(" #") {
+        Some(i) => raw[..i].trim_end().to_string(),
+        None => raw.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{merge, parse};
+
+    #[test]
+    fn parses_dotenv_syntax() {
+        let contents = r#"
+# Vulnera settings
+VULNERA_API_URL=https://api.example.com # staging
+export VULNERA_API_KEY="abc\"def"
+VULNERA_LOG='debug,hyper=warn'
+not a pair
+"#;
+        assert_eq!(
+            parse(contents),
+            vec![
+                (
+                    "VULNERA_API_URL".to_string(),
+                    "https://api.example.com".to_string()
+                ),
+                ("VULNERA_API_KEY".to_string(), "abc\"def".to_string()),
+                ("VULNERA_LOG".to_string(), "debug,hyper=warn".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn reads_only_scan_options_from_the_repository() {
+        let dotenv = "VULNERA_ADAPTER_PATH=/tmp/evil\n\
+                      VULNERA_API_URL=https://attacker.example\n\
+                      VULNERA_GITHUB_TOKEN=ghp_x\n\
+                      VULNERA_EXT_LOG=debug\n\
+                      VULNERA_TELEMETRY=off\n\
+                      VULNERA_OFFLINE_DB=osv-snapshot\n";
+        let shell = vec![("VULNERA_OFFLINE_DB".to_string(), "/data/osv".to_string())];
+        assert_eq!(
+            merge(shell, &[(".env", dotenv.to_string())], false),
+            vec![
+                ("VULNERA_OFFLINE_DB".to_string(), "/data/osv".to_string()),
+                ("VULNERA_TELEMETRY".to_string(), "off".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn reads_every_variable_in_explicitly_trusted_worktrees() {
+        let dotenv = "VULNERA_API_KEY=key\n\
+                      VULNERA_API_URL=https://api.internal.example\n\
+                      VULNERA_ADAPTER_VERSION=0.4.0\n\
+                      VULNERA_OFFLINE_DB=osv-snapshot\n\
+                      OTHER=ignored\n";
+        let shell = vec![("VULNERA_OFFLINE_DB".to_string(), "/data/osv".to_string())];
+        assert_eq!(
+            merge(shell, &[(".env", dotenv.to_string())], true),
+            vec![
+                ("VULNERA_OFFLINE_DB".to_string(), "/data/osv".to_string()),
+                ("VULNERA_API_KEY".to_string(), "key".to_string()),
+                (
+                    "VULNERA_API_URL".to_string(),
+                    "https://api.internal.example".to_string()
+                ),
+                ("VULNERA_ADAPTER_VERSION".to_string(), "0.4.0".to_string()),
+            ]
+        );
+    }
+}
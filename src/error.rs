@@ -0,0 +1,375 @@
+//! Typed failures with stable error codes.
+//!
+//! Zed's extension API reports errors as strings, so a [`VulneraError`]
+//! converts into one at the API boundary (`?` does it via `From`). Until
+//! then the variant can be matched on. Every variant has a short code
+//! (`VUL-101`, …) that stays fixed across releases and appears in the
+//! message, so users can search for it in the README's error code table.
+//!
+//! Messages state the cause first and end with the next step
+//! ([`VulneraError::next_step`]): set `VULNERA_GITHUB_TOKEN`, pin a version,
+//! point `VULNERA_ADAPTER_PATH` at a local build, and so on. Raw transport
+//! errors are classified by [`VulneraError::download`] rather than shown as is.
+
+use std::fmt;
+
+use crate::adapters::EXTENSION_VERSION;
+
+/// A failure of the extension itself (as opposed to a finding).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VulneraError {
+    /// No prebuilt adapter exists for this OS / architecture.
+    UnsupportedPlatform { os: String, arch: String },
+    /// A release asset could not be downloaded.
+    DownloadFailed { url: String, reason: String },
+    /// GitHub refused a request because the rate limit was exceeded.
+    RateLimited { url: String },
+    /// The asset URL answered 404.
+    AssetNotFound { url: String },
+    /// The host could not be reached at all.
+    Offline { url: String, reason: String },
+    /// The release has no asset for this platform.
+    AssetMissing {
+        binary: String,
+        version: String,
+        expected: String,
+        found: Vec<String>,
+    },
+    /// The adapter is needed but not installed yet, and this path does not
+    /// install it.
+    NotInstalled { binary: String, reason: String },
+    /// An OSV advisory query failed or answered something unexpected.
+    AdvisoryLookupFailed { url: String, reason: String },
+    /// The release needs a newer extension than this one.
+    ExtensionTooOld {
+        binary: String,
+        version: String,
+        required: String,
+    },
+    /// A downloaded file is not a native executable.
+    VerificationFailed { path: String },
+    /// The extension work dir could not be changed.
+    Filesystem { action: String, reason: String },
+    /// A user-supplied adapter path could not be run.
+    BadOverride {
+        path: String,
+        source: &'static str,
+        reason: String,
+    },
+    /// A setting, override file or environment variable is malformed.
+    InvalidConfig { source: String, reason: String },
+    /// Zed asked for a language server this extension does not provide.
+    UnknownServer(String),
+    /// The worktree is not trusted and its `untrusted_mode` does not allow a
+    /// scan.
+    Untrusted { worktree: String, reason: String },
+    /// A slash command got an argument it cannot use.
+    BadArgument { command: String, reason: String },
+    /// A slash command needs an open project.
+    NoProject { command: String },
+    /// The adapter ran but failed.
+    AdapterFailed { reason: String },
+    /// A state the extension should never reach.
+    Internal { reason: String },
+}
+
+/// The HTTP status in a fetch error such as `status code 403` or
+/// `HTTP 404 Not Found`; digits elsewhere (a URL, a version) do not count.
+fn http_status(reason: &str) -> Option<u16> {
+    let lower = reason.to_ascii_lowercase();
+    ["status code ", "status: ", "status ", "http/1.1 ", "http "]
+        .iter()
+        .find_map(|prefix| {
+            let (_, rest) = lower.split_once(prefix)?;
+            let digits: String = rest.chars().take_while(char::is_ascii_digit).collect();
+            digits
+                .parse()
+                .ok()
+                .filter(|status| (100..600).contains(status))
+        })
+}
+
+impl VulneraError {
+    /// The stable code shown with the message.
+    pub fn code(&self) -> &'static str {
+        match self {
+            VulneraError::UnsupportedPlatform { .. } => "VUL-001",
+            VulneraError::DownloadFailed { .. } => "VUL-101",
+            VulneraError::RateLimited { .. } => "VUL-102",
+            VulneraError::AssetMissing { .. } => "VUL-103",
+            VulneraError::VerificationFailed { .. } => "VUL-104",
+            VulneraError::AssetNotFound { .. } => "VUL-105",
+            VulneraError::Offline { .. } => "VUL-106",
+            VulneraError::ExtensionTooOld { .. } => "VUL-107",
+            VulneraError::NotInstalled { .. } => "VUL-108",
+            VulneraError::AdvisoryLookupFailed { .. } => "VUL-109",
+            VulneraError::Filesystem { .. } => "VUL-201",
+            VulneraError::BadOverride { .. } => "VUL-202",
+            VulneraError::InvalidConfig { .. } => "VUL-301",
+            VulneraError::UnknownServer(_) => "VUL-302",
+            VulneraError::Untrusted { .. } => "VUL-303",
+            VulneraError::BadArgument { .. } => "VUL-304",
+            VulneraError::NoProject { .. } => "VUL-305",
+            VulneraError::AdapterFailed { .. } => "VUL-401",
+            VulneraError::Internal { .. } => "VUL-901",
+        }
+    }
+
+    /// A failed fetch of `url`, classified by the HTTP status in the error
+    /// (GitHub's rate limit responses, a missing asset) or, without one, by
+    /// the transport failure: no network at all, or anything else.
+    pub fn download(url: &str, reason: impl fmt::Display) -> Self {
+        let reason = reason.to_string();
+        let url = url.to_string();
+        match http_status(&reason) {
+            Some(403 | 429) => return VulneraError::RateLimited { url },
+            Some(404) => return VulneraError::AssetNotFound { url },
+            Some(_) => return VulneraError::DownloadFailed { url, reason },
+            None => {}
+        }
+        let lower = reason.to_ascii_lowercase();
+        let offline = [
+            "dns",
+            "failed to resolve",
+            "connection refused",
+            "failed to connect",
+            "network is unreachable",
+            "timed out",
+            "timeout",
+        ];
+        if offline.iter().any(|n| lower.contains(n)) {
+            VulneraError::Offline { url, reason }
+        } else {
+            VulneraError::DownloadFailed { url, reason }
+        }
+    }
+
+    /// A malformed `source` (a setting path, file or variable name).
+    pub fn config(source: impl Into<String>, reason: impl fmt::Display) -> Self {
+        VulneraError::InvalidConfig {
+            source: source.into(),
+            reason: reason.to_string(),
+        }
+    }
+
+    /// A failed `action` on the work dir.
+    pub fn fs(action: impl Into<String>, reason: impl fmt::Display) -> Self {
+        VulneraError::Filesystem {
+            action: action.into(),
+            reason: reason.to_string(),
+        }
+    }
+
+    /// What the user can do about it.
+    pub fn next_step(&self) -> String {
+        match self {
+            VulneraError::UnsupportedPlatform { .. } => {
+                "Build vulnera-adapter from source and set VULNERA_ADAPTER_PATH to it.".to_string()
+            }
+            VulneraError::DownloadFailed { .. } => {
+                "Retry with /vulnera-update; if it keeps failing, pin an installed version \
+                 with VULNERA_ADAPTER_VERSION."
+                    .to_string()
+            }
+            VulneraError::RateLimited { .. } => {
+                "Set VULNERA_GITHUB_TOKEN to a GitHub token (no scopes needed) in your shell, \
+                 or pin an installed version with VULNERA_ADAPTER_VERSION."
+                    .to_string()
+            }
+            VulneraError::AssetMissing { .. } | VulneraError::AssetNotFound { .. } => {
+                "Pin a version that ships this platform with VULNERA_ADAPTER_VERSION \
+                 (or adapter_version in .zed/vulnera.toml), or set VULNERA_ADAPTER_PATH \
+                 to a local build."
+                    .to_string()
+            }
+            VulneraError::Offline { .. } => {
+                "Check the network or proxy; to work offline set VULNERA_ADAPTER_PATH \
+                 to a local binary and VULNERA_OFFLINE_DB to a local database."
+                    .to_string()
+            }
+            VulneraError::ExtensionTooOld { .. } => {
+                "Update the Vulnera extension from zed: extensions first, or pin an earlier \
+                 adapter with VULNERA_ADAPTER_VERSION."
+                    .to_string()
+            }
+            VulneraError::NotInstalled { .. } => {
+                "Open a manifest so Zed starts the language server and installs it, or run \
+                 /vulnera-update."
+                    .to_string()
+            }
+            VulneraError::AdvisoryLookupFailed { .. } => {
+                "Check the network or proxy and run the command again; the adapter's own \
+                 diagnostics do not depend on OSV."
+                    .to_string()
+            }
+            VulneraError::VerificationFailed { .. } => {
+                "Run /vulnera-update to download it again; if the release itself is broken, \
+                 pin the previous version with VULNERA_ADAPTER_VERSION."
+                    .to_string()
+            }
+            VulneraError::Filesystem { .. } => {
+                "Check that the Zed extension directory is writable and not mounted noexec, \
+                 or set VULNERA_ADAPTER_PATH to a binary installed elsewhere."
+                    .to_string()
+            }
+            VulneraError::BadOverride { source, .. } => format!(
+                "Point {} at an executable vulnera-adapter, or remove it to use the managed binary.",
+                source
+            ),
+            VulneraError::InvalidConfig { source, .. } => {
+                format!("Fix {} and restart the language server.", source)
+            }
+            VulneraError::UnknownServer(_) => {
+                "Update the Vulnera extension; this server needs a newer version.".to_string()
+            }
+            VulneraError::Untrusted { .. } => {
+                "Add the project to trusted_paths in lsp.vulnera.settings if you trust it."
+                    .to_string()
+            }
+            VulneraError::BadArgument { command, .. } => {
+                format!("Run /{} with one of the suggested arguments.", command)
+            }
+            VulneraError::NoProject { .. } => "Open a project and run it again.".to_string(),
+            VulneraError::AdapterFailed { .. } => {
+                "See the adapter output above; /vulnera-doctor shows the adapter in use."
+                    .to_string()
+            }
+            VulneraError::Internal { .. } => {
+                "This is a bug in the extension; please report it with the Zed log.".to_string()
+            }
+        }
+    }
+}
+
+impl fmt::Display for VulneraError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Vulnera [{}]: ", self.code())?;
+        match self {
+            VulneraError::UnsupportedPlatform { os, arch } => {
+                write!(f, "unsupported platform ({} / {})", os, arch)
+            }
+            VulneraError::DownloadFailed { url, reason } => {
+                write!(f, "download failed for {}: {}", url, reason)
+            }
+            VulneraError::RateLimited { url } => {
+                write!(f, "GitHub rate limit exceeded fetching {}", url)
+            }
+            VulneraError::AssetNotFound { url } => write!(f, "{} does not exist (404)", url),
+            VulneraError::Offline { url, reason } => {
+                write!(f, "could not reach {}: {}", url, reason)
+            }
+            VulneraError::AssetMissing {
+                binary,
+                version,
+                expected,
+                found,
+            } => write!(
+                f,
+                "{} {} has no release asset for this platform (expected {}; found {})",
+                binary,
+                version,
+                expected,
+                if found.is_empty() {
+                    "none".to_string()
+                } else {
+                    found.join(", ")
+                }
+            ),
+            VulneraError::ExtensionTooOld {
+                binary,
+                version,
+                required,
+            } => write!(
+                f,
+                "{} {} requires Vulnera extension {} or newer (this is {})",
+                binary, version, required, EXTENSION_VERSION
+            ),
+            VulneraError::NotInstalled { binary, reason } => {
+                write!(f, "{} is not installed: {}", binary, reason)
+            }
+            VulneraError::AdvisoryLookupFailed { url, reason } => {
+                write!(f, "advisory lookup at {} failed: {}", url, reason)
+            }
+            VulneraError::VerificationFailed { path } => write!(
+                f,
+                "{} is not a native executable; the release asset may be missing or corrupt",
+                path
+            ),
+            VulneraError::Filesystem { action, reason } => {
+                write!(f, "failed to {}: {}", action, reason)
+            }
+            VulneraError::BadOverride {
+                path,
+                source,
+                reason,
+            } => write!(f, "cannot run {} (from {}): {}", path, source, reason),
+            VulneraError::InvalidConfig { source, reason } => {
+                write!(f, "invalid {}: {}", source, reason)
+            }
+            VulneraError::UnknownServer(id) => write!(f, "unknown language server id '{}'", id),
+            VulneraError::Untrusted { worktree, reason } => {
+                write!(f, "{} is not trusted; {}", worktree, reason)
+            }
+            VulneraError::BadArgument { command, reason } => write!(f, "/{}: {}", command, reason),
+            VulneraError::NoProject { command } => write!(f, "/{} needs an open project", command),
+            VulneraError::AdapterFailed { reason } => write!(f, "{}", reason),
+            VulneraError::Internal { reason } => write!(f, "{}", reason),
+        }?;
+        write!(f, ". {}", self.next_step())
+    }
+}
+
+impl std::error::Error for VulneraError {}
+
+impl From<VulneraError> for String {
+    fn from(error: VulneraError) -> String {
+        error.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::VulneraError;
+
+    #[test]
+    fn messages_carry_their_code() {
+        let error = VulneraError::config("VULNERA_ADAPTER_ARGS", "unterminated quote");
+        assert_eq!(
+            String::from(error),
+            "Vulnera [VUL-301]: invalid VULNERA_ADAPTER_ARGS: unterminated quote. \
+             Fix VULNERA_ADAPTER_ARGS and restart the language server."
+        );
+        assert_eq!(
+            VulneraError::download("https://dl/a", "HTTP 403 Forbidden"),
+            VulneraError::RateLimited {
+                url: "https://dl/a".to_string()
+            }
+        );
+        assert_eq!(
+            VulneraError::download("https://dl/a", "connection reset").code(),
+            "VUL-101"
+        );
+    }
+
+    #[test]
+    fn classifies_transport_errors_with_a_next_step() {
+        let url = "https://github.com/vulnera-rs/adapter/releases/download/x";
+        let limited = VulneraError::download(url, "status 403: API rate limit exceeded");
+        assert!(limited.to_string().contains("Set VULNERA_GITHUB_TOKEN"));
+        let missing = VulneraError::download(url, "HTTP 404 Not Found");
+        assert_eq!(missing.code(), "VUL-105");
+        assert!(missing.to_string().contains("VULNERA_ADAPTER_VERSION"));
+        let offline = VulneraError::download(url, "error sending request: dns error");
+        assert_eq!(offline.code(), "VUL-106");
+        let server_error = VulneraError::download(
+            "https://dl/adapter-v0.4.03/asset",
+            "failed to fetch 'https://dl/adapter-v0.4.03/asset': status code 502",
+        );
+        assert_eq!(server_error.code(), "VUL-101");
+        let chmod = VulneraError::fs("chmod +x server/0.2.0/vulnera-adapter", "EPERM");
+        assert!(chmod.to_string().ends_with(
+            "mounted noexec, \
+             or set VULNERA_ADAPTER_PATH to a binary installed elsewhere."
+        ));
+    }
+}
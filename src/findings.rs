@@ -0,0 +1,265 @@
+//! Findings for a worktree, from a one-off `vulnera-adapter scan` run.
+//!
+//! The adapter prints one JSON array of findings with `--format json`; the
+//! assistant slash commands summarise them. With `--format sarif --output` it
+//! writes a SARIF 2.1 report for code-scanning dashboards instead.
+
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+
+use serde::Deserialize;
+use zed_extension_api::serde_json::{self, Value};
+use zed_extension_api::{Result, Worktree};
+
+use crate::error::VulneraError;
+use crate::settings::{Severity, resolve_in_worktree};
+
+/// Default SARIF report path, relative to the worktree root.
+pub const DEFAULT_SARIF_OUTPUT: &str = "vulnera.sarif";
+
+/// One advisory affecting one dependency.
+#[derive(Debug, Clone, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Finding {
+    /// Manifest or lockfile path, relative to the worktree root.
+    pub manifest: String,
+    pub package: String,
+    pub version: String,
+    /// Advisory ID (`GHSA-…`, `CVE-…`, …).
+    pub advisory: String,
+    pub severity: Severity,
+    /// Lowest version that fixes the advisory, if one exists.
+    #[serde(default)]
+    pub fixed_version: Option<String>,
+}
+
+/// A dependency with all of its findings folded together.
+#[derive(Debug, PartialEq, Eq)]
+pub struct VulnerableDependency {
+    pub package: String,
+    pub version: String,
+    pub worst_severity: Severity,
+    pub advisories: Vec<String>,
+    /// The lowest version fixing every advisory, or `None` if any advisory
+    /// has no fix yet.
+    pub fixed_version: Option<String>,
+}
+
+/// Scan `worktree` with the installed adapter.
+pub fn scan(worktree: &Worktree) -> Result<Vec<Finding>> {
+    let root = worktree.root_path();
+    let stdout = crate::run_adapter_cli(worktree, &["scan", "--format", "json", &root])?;
+    parse(&stdout)
+}
+
+/// Scan only `manifest` (worktree-relative) with the installed adapter.
+pub fn scan_file(worktree: &Worktree, manifest: &str) -> Result<Vec<Finding>> {
+    let path = resolve_in_worktree(&worktree.root_path(), manifest);
+    let stdout = crate::run_adapter_cli(worktree, &["scan", "--format", "json", &path])?;
+    parse(&stdout)
+}
+
+/// Write a SARIF report of `worktree`'s findings to `output` (worktree-relative
+/// or absolute). Returns the number of results when the report is readable
+/// through the worktree.
+pub fn export_sarif(worktree: &Worktree, output: &str) -> Result<Option<usize>> {
+    let root = worktree.root_path();
+    let path = resolve_in_worktree(&root, output);
+    crate::run_adapter_cli(
+        worktree,
+        &["scan", "--format", "sarif", "--output", &path, &root],
+    )?;
+    Ok(worktree
+        .read_text_file(output.trim_start_matches("./"))
+        .ok()
+        .and_then(|report| count_sarif_results(&report)))
+}
+
+fn count_sarif_results(report: &str) -> Option<usize> {
+    let sarif: Value = serde_json::from_str(report).ok()?;
+    Some(
+        sarif["runs"]
+            .as_array()?
+            .iter()
+            .filter_map(|run| run["results"].as_array())
+            .map(Vec::len)
+            .sum(),
+    )
+}
+
+fn parse(stdout: &str) -> Result<Vec<Finding>> {
+    serde_json::from_str(stdout).map_err(|e| {
+        VulneraError::AdapterFailed {
+            reason: format!("unexpected adapter scan output: {}", e),
+        }
+        .into()
+    })
+}
+
+/// Group findings by manifest, then by dependency (most severe first).
+pub fn by_manifest(findings: &[Finding]) -> BTreeMap<&str, Vec<VulnerableDependency>> {
+    let mut grouped: BTreeMap<&str, BTreeMap<(&str, &str), Vec<&Finding>>> = BTreeMap::new();
+    for finding in findings {
+        grouped
+            .entry(&finding.manifest)
+            .or_default()
+            .entry((&finding.package, &finding.version))
+            .or_default()
+            .push(finding);
+    }
+
+    grouped
+        .into_iter()
+        .map(|(manifest, deps)| {
+            let mut deps: Vec<VulnerableDependency> = deps
+                .into_iter()
+                .map(|((package, version), findings)| fold(package, version, &findings))
+                .collect();
+            deps.sort_by(|a, b| {
+                a.worst_severity
+                    .cmp(&b.worst_severity)
+                    .then_with(|| a.package.cmp(&b.package))
+            });
+            (manifest, deps)
+        })
+        .collect()
+}
+
+fn fold(package: &str, version: &str, findings: &[&Finding]) -> VulnerableDependency {
+    let mut advisories: Vec<String> = findings.iter().map(|f| f.advisory.clone()).collect();
+    advisories.sort();
+    advisories.dedup();
+
+    let fixed_version = findings
+        .iter()
+        .map(|f| f.fixed_version.as_deref())
+        .collect::<Option<Vec<&str>>>()
+        .and_then(|fixes| fixes.into_iter().max_by(|a, b| compare_versions(a, b)))
+        .map(str::to_string);
+
+    VulnerableDependency {
+        package: package.to_string(),
+        version: version.to_string(),
+        worst_severity: findings
+            .iter()
+            .map(|f| f.severity)
+            .min()
+            .unwrap_or(Severity::Low),
+        advisories,
+        fixed_version,
+    }
+}
+
+/// Compare versions with SemVer precedence: dotted segments numerically
+/// (`1.10` > `1.9`, string order for non-numeric ones), then a release above
+/// its prereleases (`0.5.0` > `0.5.0-rc.1`), whose identifiers compare the
+/// same way. Build metadata (`+…`) is ignored.
+pub fn compare_versions(a: &str, b: &str) -> Ordering {
+    let parts = |v: &str| {
+        let v = v.trim_start_matches('v');
+        let v = v.split_once('+').map_or(v, |(version, _)| version);
+        match v.split_once('-') {
+            Some((release, pre)) => (release.to_string(), Some(pre.to_string())),
+            None => (v.to_string(), None),
+        }
+    };
+    let ((a_release, a_pre), (b_release, b_pre)) = (parts(a), parts(b));
+    compare_identifiers(&a_release, &b_release).then_with(|| match (a_pre, b_pre) {
+        (None, None) => Ordering::Equal,
+        (None, Some(_)) => Ordering::Greater,
+        (Some(_), None) => Ordering::Less,
+        (Some(a), Some(b)) => compare_identifiers(&a, &b),
+    })
+}
+
+/// Dot-separated identifiers in order; numeric ones rank below the others,
+/// and a shorter list below a longer one it prefixes.
+fn compare_identifiers(a: &str, b: &str) -> Ordering {
+    let (a, b): (Vec<_>, Vec<_>) = (a.split('.').collect(), b.split('.').collect());
+    for (x, y) in a.iter().zip(&b) {
+        let ordering = match (x.parse::<u64>(), y.parse::<u64>()) {
+            (Ok(x), Ok(y)) => x.cmp(&y),
+            (Ok(_), Err(_)) => Ordering::Less,
+            (Err(_), Ok(_)) => Ordering::Greater,
+            _ => x.cmp(y),
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+    a.len().cmp(&b.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{VulnerableDependency, by_manifest, compare_versions, count_sarif_results, parse};
+    use crate::settings::Severity;
+
+    #[test]
+    fn orders_versions_by_semver_precedence() {
+        let ordered = [
+            "0.5.0-alpha",
+            "0.5.0-alpha.1",
+            "0.5.0-beta.2",
+            "0.5.0-beta.11",
+            "0.5.0-rc1",
+            "0.5.0",
+            "0.9.1",
+            "v0.10.0",
+        ];
+        for pair in ordered.windows(2) {
+            assert!(compare_versions(pair[0], pair[1]).is_lt(), "{:?}", pair);
+            assert!(compare_versions(pair[1], pair[0]).is_gt(), "{:?}", pair);
+        }
+        assert!(compare_versions("1.2.3+build.7", "1.2.3").is_eq());
+    }
+
+    #[test]
+    fn groups_findings_per_dependency() {
+        let findings = parse(
+            r#"[
+                {"manifest": "package.json", "package": "lodash", "version": "4.17.10",
+                 "advisory": "GHSA-jf85-cpcp-j695", "severity": "critical", "fixedVersion": "4.17.12"},
+                {"manifest": "package.json", "package": "lodash", "version": "4.17.10",
+                 "advisory": "GHSA-p6mc-m468-83gw", "severity": "high", "fixedVersion": "4.17.19"},
+                {"manifest": "package.json", "package": "minimist", "version": "0.0.8",
+                 "advisory": "GHSA-vh95-rmgr-6w4m", "severity": "medium"}
+            ]"#,
+        )
+        .unwrap();
+
+        let grouped = by_manifest(&findings);
+        assert_eq!(
+            grouped["package.json"],
+            vec![
+                VulnerableDependency {
+                    package: "lodash".to_string(),
+                    version: "4.17.10".to_string(),
+                    worst_severity: Severity::Critical,
+                    advisories: vec![
+                        "GHSA-jf85-cpcp-j695".to_string(),
+                        "GHSA-p6mc-m468-83gw".to_string()
+                    ],
+                    fixed_version: Some("4.17.19".to_string()),
+                },
+                VulnerableDependency {
+                    package: "minimist".to_string(),
+                    version: "0.0.8".to_string(),
+                    worst_severity: Severity::Medium,
+                    advisories: vec!["GHSA-vh95-rmgr-6w4m".to_string()],
+                    fixed_version: None,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn counts_sarif_results_across_runs() {
+        let report = r#"{"version": "2.1.0", "runs": [
+            {"tool": {"driver": {"name": "vulnera"}}, "results": [{"ruleId": "GHSA-jf85-cpcp-j695"}, {"ruleId": "GHSA-p6mc-m468-83gw"}]},
+            {"tool": {"driver": {"name": "vulnera"}}, "results": []}
+        ]}"#;
+        assert_eq!(count_sarif_results(report), Some(2));
+        assert_eq!(count_sarif_results("not sarif"), None);
+    }
+}
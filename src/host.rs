@@ -0,0 +1,413 @@
+//! Host services the install lifecycle depends on, behind traits.
+//!
+//! [`Host`] bundles the network ([`HttpFetch`]), the extension work dir
+//! ([`Fs`]), the time ([`Clock`]), the host platform ([`Platform`]) and the
+//! release metadata seen this session ([`ReleaseCache`]).
+//! Production code uses [`Host::REAL`]; tests build one from [`MemFs`],
+//! [`FixedClock`], [`FixedPlatform`] and [`Canned`](crate::http::Canned), so
+//! version caching and installs run deterministically without touching disk
+//! or network.
+//!
+//! The worktree a server launches for is a [`Checkout`]: Zed's `Worktree`
+//! in production, a [`MemWorktree`] in tests.
+
+use std::fs;
+use std::io::{self, Read};
+
+use zed_extension_api::{self as zed, Architecture, DownloadedFileType, Os, Result};
+
+use crate::http::{HttpFetch, ZedHttp};
+use crate::releases::{self, ReleaseCache};
+
+/// Files under the extension work dir. Paths are relative to it.
+pub trait Fs {
+    fn read_to_string(&self, path: &str) -> io::Result<String>;
+    /// Up to the first `len` bytes of `path`.
+    fn read_prefix(&self, path: &str, len: usize) -> io::Result<Vec<u8>>;
+    fn write(&self, path: &str, contents: &str) -> io::Result<()>;
+    fn create_dir_all(&self, path: &str) -> io::Result<()>;
+    fn remove_file(&self, path: &str) -> io::Result<()>;
+    /// Remove the file or directory tree at `path`.
+    fn remove_all(&self, path: &str) -> io::Result<()>;
+    fn exists(&self, path: &str) -> bool;
+    /// Size of the file at `path` in bytes.
+    fn size(&self, path: &str) -> io::Result<u64>;
+    /// Names of the entries directly under `path`; empty if it is missing.
+    fn read_dir(&self, path: &str) -> Vec<String>;
+    /// The absolute path of `path`, for handing to another process.
+    fn absolute(&self, path: &str) -> io::Result<String>;
+    /// Download `url` to `path` (Zed's `download_file`).
+    fn download_file(&self, url: &str, path: &str) -> Result<()>;
+    /// Mark `path` executable (Zed's `make_file_executable`).
+    fn make_executable(&self, path: &str) -> Result<()>;
+}
+
+/// The current time.
+pub trait Clock {
+    /// Seconds since the Unix epoch.
+    fn now_secs(&self) -> u64;
+    /// Milliseconds since the Unix epoch, for timing steps.
+    fn now_millis(&self) -> u64;
+}
+
+/// The machine Zed runs on.
+pub trait Platform {
+    /// Zed's `current_platform`.
+    fn current(&self) -> (Os, Architecture);
+}
+
+/// The worktree a server is launched for: its files, the user's shell
+/// environment in it and the programs on that `PATH`.
+pub trait Checkout {
+    fn id(&self) -> u64;
+    fn root_path(&self) -> String;
+    /// The file at `path`, relative to the worktree root.
+    fn read_text_file(&self, path: &str) -> Result<String>;
+    fn shell_env(&self) -> Vec<(String, String)>;
+    fn which(&self, binary_name: &str) -> Option<String>;
+}
+
+impl Checkout for zed::Worktree {
+    fn id(&self) -> u64 {
+        zed::Worktree::id(self)
+    }
+
+    fn root_path(&self) -> String {
+        zed::Worktree::root_path(self)
+    }
+
+    fn read_text_file(&self, path: &str) -> Result<String> {
+        zed::Worktree::read_text_file(self, path)
+    }
+
+    fn shell_env(&self) -> Vec<(String, String)> {
+        zed::Worktree::shell_env(self)
+    }
+
+    fn which(&self, binary_name: &str) -> Option<String> {
+        zed::Worktree::which(self, binary_name)
+    }
+}
+
+/// The services an install runs against.
+#[derive(Clone, Copy)]
+pub struct Host<'a> {
+    pub http: &'a dyn HttpFetch,
+    pub fs: &'a dyn Fs,
+    pub clock: &'a dyn Clock,
+    pub platform: &'a dyn Platform,
+    pub releases: &'a ReleaseCache,
+    /// `VULNERA_GITHUB_TOKEN` from the user's shell for the worktree being
+    /// served, sent with GitHub API requests to lift the anonymous rate limit.
+    pub github_token: Option<&'a str>,
+}
+
+impl Host<'static> {
+    /// Zed's HTTP client, the real work dir, the system clock, Zed's
+    /// platform and the session's release cache; anonymous GitHub requests.
+    pub const REAL: Host<'static> = Host {
+        http: &ZedHttp,
+        fs: &RealFs,
+        clock: &SystemClock,
+        platform: &ZedPlatform,
+        releases: &releases::SESSION,
+        github_token: None,
+    };
+}
+
+impl Host<'_> {
+    pub fn is_windows(&self) -> bool {
+        matches!(self.platform.current().0, Os::Windows)
+    }
+}
+
+/// `std::fs` and Zed's download helpers.
+pub struct RealFs;
+
+impl Fs for RealFs {
+    fn read_to_string(&self, path: &str) -> io::Result<String> {
+        fs::read_to_string(path)
+    }
+
+    fn read_prefix(&self, path: &str, len: usize) -> io::Result<Vec<u8>> {
+        let mut prefix = Vec::with_capacity(len);
+        fs::File::open(path)?
+            .take(len as u64)
+            .read_to_end(&mut prefix)?;
+        Ok(prefix)
+    }
+
+    fn write(&self, path: &str, contents: &str) -> io::Result<()> {
+        fs::write(path, contents)
+    }
+
+    fn create_dir_all(&self, path: &str) -> io::Result<()> {
+        fs::create_dir_all(path)
+    }
+
+    fn remove_file(&self, path: &str) -> io::Result<()> {
+        fs::remove_file(path)
+    }
+
+    fn remove_all(&self, path: &str) -> io::Result<()> {
+        if fs::metadata(path)?.is_dir() {
+            fs::remove_dir_all(path)
+        } else {
+            fs::remove_file(path)
+        }
+    }
+
+    fn exists(&self, path: &str) -> bool {
+        fs::metadata(path).is_ok()
+    }
+
+    fn size(&self, path: &str) -> io::Result<u64> {
+        Ok(fs::metadata(path)?.len())
+    }
+
+    fn read_dir(&self, path: &str) -> Vec<String> {
+        fs::read_dir(path)
+            .into_iter()
+            .flatten()
+            .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+            .collect()
+    }
+
+    fn absolute(&self, path: &str) -> io::Result<String> {
+        Ok(std::env::current_dir()?
+            .join(path)
+            .to_string_lossy()
+            .into_owned())
+    }
+
+    fn download_file(&self, url: &str, path: &str) -> Result<()> {
+        zed::download_file(url, path, DownloadedFileType::Uncompressed)
+    }
+
+    fn make_executable(&self, path: &str) -> Result<()> {
+        zed::make_file_executable(path)
+    }
+}
+
+/// The system clock.
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now_secs(&self) -> u64 {
+        crate::now_secs()
+    }
+
+    fn now_millis(&self) -> u64 {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0)
+    }
+}
+
+/// Zed's `current_platform`.
+pub struct ZedPlatform;
+
+impl Platform for ZedPlatform {
+    fn current(&self) -> (Os, Architecture) {
+        zed::current_platform()
+    }
+}
+
+/// Where a [`MemFs`] work dir claims to be.
+#[cfg(test)]
+pub const MEM_WORK_DIR: &str = "/ext";
+
+/// An in-memory work dir. Directories exist implicitly; downloads are served
+/// from `downloads` by URL, recording the URLs requested.
+#[cfg(test)]
+#[derive(Default)]
+pub struct MemFs {
+    pub files: std::cell::RefCell<std::collections::BTreeMap<String, Vec<u8>>>,
+    pub downloads: Vec<(String, Vec<u8>)>,
+    pub downloaded: std::cell::RefCell<Vec<String>>,
+}
+
+#[cfg(test)]
+impl MemFs {
+    pub fn with_file(self, path: &str, contents: &[u8]) -> Self {
+        self.files
+            .borrow_mut()
+            .insert(path.to_string(), contents.to_vec());
+        self
+    }
+
+    pub fn file(&self, path: &str) -> Option<String> {
+        self.read_to_string(path).ok()
+    }
+}
+
+#[cfg(test)]
+impl Fs for MemFs {
+    fn read_to_string(&self, path: &str) -> io::Result<String> {
+        let bytes = self.read_prefix(path, usize::MAX)?;
+        String::from_utf8(bytes).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    fn read_prefix(&self, path: &str, len: usize) -> io::Result<Vec<u8>> {
+        self.files
+            .borrow()
+            .get(path)
+            .map(|bytes| bytes.iter().take(len).copied().collect())
+            .ok_or_else(|| io::Error::from(io::ErrorKind::NotFound))
+    }
+
+    fn write(&self, path: &str, contents: &str) -> io::Result<()> {
+        self.files
+            .borrow_mut()
+            .insert(path.to_string(), contents.as_bytes().to_vec());
+        Ok(())
+    }
+
+    fn create_dir_all(&self, _path: &str) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn remove_file(&self, path: &str) -> io::Result<()> {
+        self.files
+            .borrow_mut()
+            .remove(path)
+            .map(drop)
+            .ok_or_else(|| io::Error::from(io::ErrorKind::NotFound))
+    }
+
+    fn remove_all(&self, path: &str) -> io::Result<()> {
+        if !self.exists(path) {
+            return Err(io::Error::from(io::ErrorKind::NotFound));
+        }
+        let dir = format!("{}/", path);
+        self.files
+            .borrow_mut()
+            .retain(|file, _| file != path && !file.starts_with(&dir));
+        Ok(())
+    }
+
+    fn exists(&self, path: &str) -> bool {
+        let dir = format!("{}/", path);
+        self.files
+            .borrow()
+            .keys()
+            .any(|file| file == path || file.starts_with(&dir))
+    }
+
+    fn size(&self, path: &str) -> io::Result<u64> {
+        Ok(self.read_prefix(path, usize::MAX)?.len() as u64)
+    }
+
+    fn read_dir(&self, path: &str) -> Vec<String> {
+        let dir = format!("{}/", path);
+        let mut names: Vec<String> = self
+            .files
+            .borrow()
+            .keys()
+            .filter_map(|file| file.strip_prefix(&dir))
+            .map(|rest| rest.split('/').next().unwrap_or(rest).to_string())
+            .collect();
+        names.dedup();
+        names
+    }
+
+    fn absolute(&self, path: &str) -> io::Result<String> {
+        if path.starts_with('/') {
+            return Ok(path.to_string());
+        }
+        Ok(format!("{}/{}", MEM_WORK_DIR, path))
+    }
+
+    fn download_file(&self, url: &str, path: &str) -> Result<()> {
+        self.downloaded.borrow_mut().push(url.to_string());
+        let (_, bytes) = self
+            .downloads
+            .iter()
+            .find(|(u, _)| u == url)
+            .ok_or_else(|| format!("404 Not Found: {}", url))?;
+        self.files
+            .borrow_mut()
+            .insert(path.to_string(), bytes.clone());
+        Ok(())
+    }
+
+    fn make_executable(&self, _path: &str) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// A clock stopped at a given time.
+#[cfg(test)]
+pub struct FixedClock(pub u64);
+
+#[cfg(test)]
+impl Clock for FixedClock {
+    fn now_secs(&self) -> u64 {
+        self.0
+    }
+
+    fn now_millis(&self) -> u64 {
+        self.0 * 1000
+    }
+}
+
+/// A fixed OS and architecture.
+#[cfg(test)]
+pub struct FixedPlatform(pub Os, pub Architecture);
+
+#[cfg(test)]
+impl Platform for FixedPlatform {
+    fn current(&self) -> (Os, Architecture) {
+        (self.0, self.1)
+    }
+}
+
+/// An in-memory worktree at `root`: its files, the user's shell variables
+/// and the programs found on `PATH`.
+#[cfg(test)]
+#[derive(Default)]
+pub struct MemWorktree {
+    pub id: u64,
+    pub root: String,
+    pub files: std::collections::BTreeMap<String, String>,
+    pub env: Vec<(String, String)>,
+    pub programs: Vec<(String, String)>,
+}
+
+#[cfg(test)]
+impl MemWorktree {
+    pub fn with_file(mut self, path: &str, contents: &str) -> Self {
+        self.files.insert(path.to_string(), contents.to_string());
+        self
+    }
+}
+
+#[cfg(test)]
+impl Checkout for MemWorktree {
+    fn id(&self) -> u64 {
+        self.id
+    }
+
+    fn root_path(&self) -> String {
+        self.root.clone()
+    }
+
+    fn read_text_file(&self, path: &str) -> Result<String> {
+        self.files
+            .get(path)
+            .cloned()
+            .ok_or_else(|| format!("{} not found", path))
+    }
+
+    fn shell_env(&self) -> Vec<(String, String)> {
+        self.env.clone()
+    }
+
+    fn which(&self, binary_name: &str) -> Option<String> {
+        self.programs
+            .iter()
+            .find(|(name, _)| name == binary_name)
+            .map(|(_, path)| path.clone())
+    }
+}
@@ -0,0 +1,114 @@
+//! Network access behind a trait, so request logic can be unit tested.
+//!
+//! Every HTTP request and GitHub release lookup goes through [`HttpFetch`].
+//! Production code passes [`ZedHttp`], which forwards to Zed's host
+//! functions; tests pass a [`Canned`] set of responses keyed by URL.
+
+use zed_extension_api::http_client::{HttpRequest, HttpResponse};
+use zed_extension_api::{self as zed, GithubRelease, GithubReleaseOptions, Result};
+
+/// The network operations the extension performs.
+pub trait HttpFetch {
+    /// Perform `request`; non-2xx responses are errors.
+    fn fetch(&self, request: &HttpRequest) -> Result<HttpResponse>;
+
+    /// The latest release of `repo` matching `options`.
+    fn latest_github_release(
+        &self,
+        repo: &str,
+        options: GithubReleaseOptions,
+    ) -> Result<GithubRelease>;
+
+    /// The release of `repo` tagged `tag`.
+    fn github_release_by_tag_name(&self, repo: &str, tag: &str) -> Result<GithubRelease>;
+}
+
+/// Zed's HTTP client and GitHub helpers.
+pub struct ZedHttp;
+
+impl HttpFetch for ZedHttp {
+    fn fetch(&self, request: &HttpRequest) -> Result<HttpResponse> {
+        zed::http_client::fetch(request)
+    }
+
+    fn latest_github_release(
+        &self,
+        repo: &str,
+        options: GithubReleaseOptions,
+    ) -> Result<GithubRelease> {
+        zed::latest_github_release(repo, options)
+    }
+
+    fn github_release_by_tag_name(&self, repo: &str, tag: &str) -> Result<GithubRelease> {
+        zed::github_release_by_tag_name(repo, tag)
+    }
+}
+
+/// Fixed responses by URL, recording the URLs requested. GitHub helper
+/// lookups fail unless a release is canned for the repository or tag.
+#[cfg(test)]
+#[derive(Default)]
+pub struct Canned {
+    pub responses: Vec<(String, Result<String>)>,
+    pub releases: Vec<(String, GithubRelease)>,
+    pub requested: std::cell::RefCell<Vec<String>>,
+}
+
+#[cfg(test)]
+impl Canned {
+    /// Respond to requests for `url` with `body`; later entries for the same
+    /// URL answer later requests, and the last one repeats.
+    pub fn with(mut self, url: &str, body: Result<&str>) -> Self {
+        self.responses
+            .push((url.to_string(), body.map(str::to_string)));
+        self
+    }
+
+    /// The release canned for `key`; the lookup is recorded as a request.
+    fn release(&self, key: &str) -> Result<GithubRelease> {
+        self.requested.borrow_mut().push(key.to_string());
+        self.releases
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, release)| release.clone())
+            .ok_or_else(|| format!("no release canned for {}", key))
+    }
+}
+
+#[cfg(test)]
+impl HttpFetch for Canned {
+    fn fetch(&self, request: &HttpRequest) -> Result<HttpResponse> {
+        let served = self
+            .requested
+            .borrow()
+            .iter()
+            .filter(|url| **url == request.url)
+            .count();
+        self.requested.borrow_mut().push(request.url.clone());
+        let canned: Vec<_> = self
+            .responses
+            .iter()
+            .filter(|(url, _)| *url == request.url)
+            .collect();
+        let body = canned
+            .get(served.min(canned.len().saturating_sub(1)))
+            .map(|(_, body)| body.clone())
+            .unwrap_or_else(|| Err(format!("404 Not Found: {}", request.url)))?;
+        Ok(HttpResponse {
+            headers: Vec::new(),
+            body: body.into_bytes(),
+        })
+    }
+
+    fn latest_github_release(
+        &self,
+        repo: &str,
+        _options: GithubReleaseOptions,
+    ) -> Result<GithubRelease> {
+        self.release(repo)
+    }
+
+    fn github_release_by_tag_name(&self, repo: &str, tag: &str) -> Result<GithubRelease> {
+        self.release(&format!("{}@{}", repo, tag))
+    }
+}
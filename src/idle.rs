@@ -0,0 +1,164 @@
+//! A language server that does nothing, for servers that should not run in a
+//! worktree.
+//!
+//! Zed shows an `Err` from `language_server_command` as a failed start in
+//! every buffer of every language the server is registered for, so an opt-in
+//! server that is switched off (or has nothing to scan) returns this command
+//! instead. It answers `initialize` with no capabilities and `shutdown` with
+//! `null`, ignores everything else and exits on `exit` or end of input — a
+//! few lines of `sh`, or PowerShell on Windows, so nothing is downloaded.
+
+use zed_extension_api as zed;
+
+/// Reads one framed message per loop: headers up to the blank line, then
+/// `Content-Length` bytes. `awk` walks the body's JSON to print its top-level
+/// `method` and `id` (verbatim, so string ids round-trip), whatever the key
+/// order and whatever nested objects carry the same names.
+const SH_SCRIPT: &str = r#"while :; do
+  len=
+  while IFS= read -r line; do
+    line=$(printf '%s' "$line" | tr -d '\r')
+    [ -z "$line" ] && break
+    case $line in Content-Length:*) len=$(printf '%s' "${line#*:}" | tr -dc 0-9) ;; esac
+  done
+  [ -n "$len" ] || exit 0
+  body=$(dd bs=1 count="$len" 2>/dev/null)
+  fields=$(printf '%s' "$body" | awk '{ s = s $0 "\n" } END {
+    n = length(s); d = 0; key = ""; value = 0
+    for (i = 1; i <= n; i++) {
+      c = substr(s, i, 1)
+      if (c == "\"") {
+        for (j = i + 1; j <= n && (e = substr(s, j, 1)) != "\""; j++) if (e == "\\") j++
+        if (d == 1 && value) { if (key == "id") id = substr(s, i, j - i + 1); if (key == "method") method = substr(s, i + 1, j - i - 1); value = 0 }
+        else if (d == 1) key = substr(s, i + 1, j - i - 1)
+        i = j
+      } else if (c == "{" || c == "[") { d++; if (d == 2) value = 0 }
+      else if (c == "}" || c == "]") d--
+      else if (d == 1 && c == ":") value = 1
+      else if (d == 1 && c == ",") { key = ""; value = 0 }
+      else if (d == 1 && value && c ~ /[-0-9]/) {
+        for (j = i; j <= n && substr(s, j, 1) ~ /[-0-9.eE+]/; j++);
+        if (key == "id") id = substr(s, i, j - i)
+        value = 0; i = j - 1
+      }
+    }
+    print method; print id
+  }')
+  method=$(printf '%s\n' "$fields" | sed -n 1p)
+  id=$(printf '%s\n' "$fields" | sed -n 2p)
+  case $method in
+    initialize) result='{"capabilities":{}}' ;;
+    shutdown) result=null ;;
+    exit) exit 0 ;;
+    *) continue ;;
+  esac
+  [ -n "$id" ] || continue
+  reply="{\"jsonrpc\":\"2.0\",\"id\":$id,\"result\":$result}"
+  printf 'Content-Length: %s\r\n\r\n%s' "${#reply}" "$reply"
+done"#;
+
+/// The same loop, with the body parsed by `ConvertFrom-Json`.
+const POWERSHELL_SCRIPT: &str = r#"$in = [Console]::OpenStandardInput(); $out = [Console]::OpenStandardOutput()
+function Read-Header {
+  $bytes = New-Object System.Collections.Generic.List[byte]
+  while (($c = $in.ReadByte()) -ne 10) { if ($c -lt 0) { exit }; if ($c -ne 13) { $bytes.Add($c) } }
+  [Text.Encoding]::ASCII.GetString($bytes.ToArray())
+}
+while ($true) {
+  $len = 0
+  while (($line = Read-Header) -ne '') { if ($line -match '^Content-Length:\s*(\d+)') { $len = [int]$Matches[1] } }
+  $buf = New-Object byte[] $len; $n = 0
+  while ($n -lt $len) { $r = $in.Read($buf, $n, $len - $n); if ($r -le 0) { exit }; $n += $r }
+  try { $msg = [Text.Encoding]::UTF8.GetString($buf) | ConvertFrom-Json } catch { continue }
+  if ($msg.method -eq 'exit') { exit }
+  if ($msg.method -eq 'initialize') { $result = '{"capabilities":{}}' }
+  elseif ($msg.method -eq 'shutdown') { $result = 'null' }
+  else { continue }
+  if ($null -eq $msg.id) { continue }
+  $id = ConvertTo-Json -Compress $msg.id
+  $reply = [Text.Encoding]::UTF8.GetBytes('{"jsonrpc":"2.0","id":' + $id + ',"result":' + $result + '}')
+  $header = [Text.Encoding]::ASCII.GetBytes("Content-Length: $($reply.Length)`r`n`r`n")
+  $out.Write($header, 0, $header.Length); $out.Write($reply, 0, $reply.Length); $out.Flush()
+}"#;
+
+/// The do-nothing server, logging `reason` once per launch.
+pub fn command(reason: &str, windows: bool) -> zed::Command {
+    log!("{}; running an idle server instead", reason);
+    let (command, args) = if windows {
+        (
+            "powershell.exe",
+            vec![
+                "-NoLogo".to_string(),
+                "-NoProfile".to_string(),
+                "-NonInteractive".to_string(),
+                "-Command".to_string(),
+                POWERSHELL_SCRIPT.to_string(),
+            ],
+        )
+    } else {
+        ("sh", vec!["-c".to_string(), SH_SCRIPT.to_string()])
+    };
+    zed::Command {
+        command: command.to_string(),
+        args,
+        env: Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::command;
+
+    fn frame(body: &str) -> String {
+        format!("Content-Length: {}\r\n\r\n{}", body.len(), body)
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn answers_the_lsp_handshake_and_exits() {
+        use std::io::Write;
+        use std::process::{Command, Stdio};
+
+        let idle = command("test", false);
+        let mut child = Command::new(&idle.command)
+            .args(&idle.args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap();
+        let input = [
+            // Nested keys named like the top-level ones come first.
+            frame(
+                r#"{"jsonrpc":"2.0","method":"initialize","params":{"processId":1,"capabilities":{"workspace":{"id":7}},"rootUri":"file:///a\"b"},"id":0}"#,
+            ),
+            frame(r#"{"jsonrpc":"2.0","method":"initialized","params":{"method":"exit"}}"#),
+            frame(r#"{"id":"s-1","jsonrpc":"2.0","method":"shutdown"}"#),
+            frame(r#"{"jsonrpc":"2.0","method":"exit"}"#),
+        ]
+        .concat();
+        child
+            .stdin
+            .take()
+            .unwrap()
+            .write_all(input.as_bytes())
+            .unwrap();
+        let output = child.wait_with_output().unwrap();
+
+        assert!(output.status.success());
+        assert_eq!(
+            String::from_utf8(output.stdout).unwrap(),
+            [
+                frame(r#"{"jsonrpc":"2.0","id":0,"result":{"capabilities":{}}}"#),
+                frame(r#"{"jsonrpc":"2.0","id":"s-1","result":null}"#),
+            ]
+            .concat()
+        );
+    }
+
+    #[test]
+    fn uses_powershell_on_windows() {
+        let idle = command("test", true);
+        assert_eq!(idle.command, "powershell.exe");
+        assert!(idle.args.last().unwrap().contains("Content-Length"));
+    }
+}
@@ -0,0 +1,60 @@
+//! Ignore patterns from the worktree's `.gitignore` and `.vulneraignore`.
+//!
+//! Patterns are forwarded verbatim (gitignore syntax, including `!` negations)
+//! as `initializationOptions.vulnera.ignorePatterns`, so the adapter skips
+//! build output and vendored trees the same way git does. Only root-level
+//! files are read; nested `.gitignore` files are left to the adapter.
+
+use zed_extension_api::Worktree;
+
+/// Collect patterns from `.gitignore` (when `use_gitignore` is set) followed by
+/// `.vulneraignore`, which therefore can re-include paths with `!pattern`.
+pub fn ignore_patterns(worktree: &Worktree, use_gitignore: bool) -> Vec<String> {
+    let files: &[&str] = if use_gitignore {
+        &[".gitignore", ".vulneraignore"]
+    } else {
+        &[".vulneraignore"]
+    };
+
+    files
+        .iter()
+        .filter_map(|file| worktree.read_text_file(file).ok())
+        .flat_map(|contents| parse(&contents))
+        .collect()
+}
+
+/// Extract the patterns from a gitignore-style file, dropping blank lines and
+/// comments. Trailing whitespace is insignificant unless escaped.
+fn parse(contents: &str) -> Vec<String> {
+    contents
+        .lines()
+        .filter_map(|line| {
+            let trimmed = line.trim_end();
+            let pattern = if trimmed.ends_with('\\') && line.len() > trimmed.len() {
+                // `foo\ ` keeps one escaped trailing space.
+                &line[..trimmed.len() + 1]
+            } else {
+                trimmed
+            };
+            if pattern.is_empty() || pattern.starts_with('#') {
+                None
+            } else {
+                Some(pattern.to_string())
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse;
+
+    #[test]
+    fn parses_gitignore_patterns() {
+        let contents = "# build output\n/target\nnode_modules/  \n\n\\#notes\n!vendor/keep\n";
+        assert_eq!(
+            parse(contents),
+            vec!["/target", "node_modules/", "\\#notes", "!vendor/keep"]
+        );
+    }
+}
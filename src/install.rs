@@ -0,0 +1,325 @@
+//! Install/update orchestration for a managed adapter, as a state machine.
+//!
+//! ```text
+//! ResolvingVersion ──▶ Downloading ──▶ Verifying ──▶ Installing ──▶ Ready
+//!        │                  ▲  │            │             │
+//!        │ already          └──┴── retry ◀──┘             ▼
+//!        └─ installed ──▶ Ready                       Failed(reason)
+//! ```
+//!
+//! [`InstallState::next`] is a pure transition function; the side effect of
+//! each state is performed by an [`InstallSteps`] implementation (see
+//! [`crate::adapters::ensure_binary`] for the real one). [`run`] drives the
+//! two, rolling back a failed download or verification before retrying or
+//! giving up, and reports every state so the caller can surface it as the
+//! language server's installation status.
+
+use zed_extension_api::{self as zed, Result};
+
+use crate::error::VulneraError;
+
+/// Download attempts per install; a failed verification counts as one.
+pub const MAX_DOWNLOAD_ATTEMPTS: u32 = 2;
+
+/// Where an install currently stands.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InstallState {
+    /// Choosing the version and checking whether it is already installed.
+    ResolvingVersion,
+    /// Fetching the release asset (`attempt` starts at 1).
+    Downloading { version: String, attempt: u32 },
+    /// Checking that the download is a native executable.
+    Verifying { version: String, attempt: u32 },
+    /// Marking the download executable and installed.
+    Installing { version: String },
+    /// The binary at `path` can be launched.
+    Ready { version: String, path: String },
+    /// The install gave up.
+    Failed(String),
+}
+
+/// The result of performing one state's step.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    /// The version to use, and its path if it is already installed.
+    Resolved {
+        version: String,
+        installed: Option<String>,
+    },
+    /// The step succeeded.
+    Done,
+    /// The binary was installed at this path.
+    Installed(String),
+    /// The step failed.
+    Error(String),
+}
+
+/// The side effects behind each state.
+pub trait InstallSteps {
+    /// The version to install.
+    fn resolve_version(&mut self) -> String;
+    /// Path of `version` if it is installed and healthy.
+    fn installed(&mut self, version: &str) -> Result<String>;
+    /// Download `version`'s release asset.
+    fn download(&mut self, version: &str) -> Result<()>;
+    /// Check the downloaded asset.
+    fn verify(&mut self, version: &str) -> Result<()>;
+    /// Make the verified download launchable and return its path.
+    fn install(&mut self, version: &str) -> Result<String>;
+    /// Remove whatever a failed step left behind for `version`.
+    fn rollback(&mut self, version: &str);
+}
+
+impl InstallState {
+    /// Whether no further transitions follow.
+    pub fn is_terminal(&self) -> bool {
+        matches!(self, InstallState::Ready { .. } | InstallState::Failed(_))
+    }
+
+    /// The version this state works on, once resolved.
+    pub fn version(&self) -> Option<&str> {
+        match self {
+            InstallState::Downloading { version, .. }
+            | InstallState::Verifying { version, .. }
+            | InstallState::Installing { version }
+            | InstallState::Ready { version, .. } => Some(version),
+            InstallState::ResolvingVersion | InstallState::Failed(_) => None,
+        }
+    }
+
+    /// The installation status Zed shows for this state.
+    pub fn status(&self) -> zed::LanguageServerInstallationStatus {
+        match self {
+            InstallState::ResolvingVersion => {
+                zed::LanguageServerInstallationStatus::CheckingForUpdate
+            }
+            InstallState::Downloading { .. }
+            | InstallState::Verifying { .. }
+            | InstallState::Installing { .. } => zed::LanguageServerInstallationStatus::Downloading,
+            InstallState::Ready { .. } => zed::LanguageServerInstallationStatus::None,
+            InstallState::Failed(reason) => {
+                zed::LanguageServerInstallationStatus::Failed(reason.clone())
+            }
+        }
+    }
+
+    /// Perform this state's step.
+    pub fn perform(&self, steps: &mut impl InstallSteps) -> Outcome {
+        let result = match self {
+            InstallState::ResolvingVersion => {
+                let version = steps.resolve_version();
+                let installed = steps.installed(&version).ok();
+                return Outcome::Resolved { version, installed };
+            }
+            InstallState::Downloading { version, .. } => steps.download(version),
+            InstallState::Verifying { version, .. } => steps.verify(version),
+            InstallState::Installing { version } => {
+                return match steps.install(version) {
+                    Ok(path) => Outcome::Installed(path),
+                    Err(e) => Outcome::Error(e),
+                };
+            }
+            InstallState::Ready { .. } | InstallState::Failed(_) => return Outcome::Done,
+        };
+        match result {
+            Ok(()) => Outcome::Done,
+            Err(e) => Outcome::Error(e),
+        }
+    }
+
+    /// The state following this one after `outcome`.
+    pub fn next(self, outcome: Outcome) -> InstallState {
+        match (self, outcome) {
+            (state, _) if state.is_terminal() => state,
+            (
+                InstallState::ResolvingVersion,
+                Outcome::Resolved {
+                    version,
+                    installed: Some(path),
+                },
+            ) => InstallState::Ready { version, path },
+            (
+                InstallState::ResolvingVersion,
+                Outcome::Resolved {
+                    version,
+                    installed: None,
+                },
+            ) => InstallState::Downloading {
+                version,
+                attempt: 1,
+            },
+            (InstallState::Downloading { version, attempt }, Outcome::Done) => {
+                InstallState::Verifying { version, attempt }
+            }
+            (InstallState::Verifying { version, .. }, Outcome::Done) => {
+                InstallState::Installing { version }
+            }
+            (
+                InstallState::Downloading { version, attempt }
+                | InstallState::Verifying { version, attempt },
+                Outcome::Error(_),
+            ) if attempt < MAX_DOWNLOAD_ATTEMPTS => InstallState::Downloading {
+                version,
+                attempt: attempt + 1,
+            },
+            (InstallState::Installing { version }, Outcome::Installed(path)) => {
+                InstallState::Ready { version, path }
+            }
+            (_, Outcome::Error(e)) => InstallState::Failed(e),
+            (state, outcome) => InstallState::Failed(
+                VulneraError::Internal {
+                    reason: format!("unexpected install step {:?} while {:?}", outcome, state),
+                }
+                .into(),
+            ),
+        }
+    }
+}
+
+/// Drive an install from [`InstallState::ResolvingVersion`] to a terminal
+/// state, calling `report` with every state entered.
+pub fn run(steps: &mut impl InstallSteps, mut report: impl FnMut(&InstallState)) -> InstallState {
+    let mut state = InstallState::ResolvingVersion;
+    report(&state);
+    while !state.is_terminal() {
+        let outcome = state.perform(steps);
+        if let (Outcome::Error(e), Some(version)) = (&outcome, state.version()) {
+            debug!("Install step {:?} failed: {}", state, e);
+            steps.rollback(version);
+        }
+        state = state.next(outcome);
+        report(&state);
+    }
+    state
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{InstallState, InstallSteps, MAX_DOWNLOAD_ATTEMPTS, Outcome, run};
+    use crate::error::VulneraError;
+    use zed_extension_api::Result;
+
+    /// Scripted steps: each download result is taken in order.
+    #[derive(Default)]
+    struct Scripted {
+        installed: bool,
+        downloads: Vec<Result<()>>,
+        corrupt: bool,
+        rollbacks: u32,
+    }
+
+    impl InstallSteps for Scripted {
+        fn resolve_version(&mut self) -> String {
+            "0.2.0".to_string()
+        }
+        fn installed(&mut self, version: &str) -> Result<String> {
+            if self.installed {
+                Ok(format!("server/{}/vulnera-adapter", version))
+            } else {
+                Err("not installed".to_string())
+            }
+        }
+        fn download(&mut self, _version: &str) -> Result<()> {
+            self.downloads.remove(0)
+        }
+        fn verify(&mut self, _version: &str) -> Result<()> {
+            if self.corrupt {
+                Err("not a native executable".to_string())
+            } else {
+                Ok(())
+            }
+        }
+        fn install(&mut self, version: &str) -> Result<String> {
+            Ok(format!("server/{}/vulnera-adapter", version))
+        }
+        fn rollback(&mut self, _version: &str) {
+            self.rollbacks += 1;
+        }
+    }
+
+    fn states(steps: &mut Scripted) -> Vec<InstallState> {
+        let mut seen = Vec::new();
+        run(steps, |state| seen.push(state.clone()));
+        seen
+    }
+
+    #[test]
+    fn installed_version_is_ready_without_download() {
+        let mut steps = Scripted {
+            installed: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            states(&mut steps),
+            vec![
+                InstallState::ResolvingVersion,
+                InstallState::Ready {
+                    version: "0.2.0".to_string(),
+                    path: "server/0.2.0/vulnera-adapter".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn retries_a_failed_download_after_rollback() {
+        let mut steps = Scripted {
+            downloads: vec![Err("timed out".to_string()), Ok(())],
+            ..Default::default()
+        };
+        let seen = states(&mut steps);
+        assert_eq!(
+            seen[1..4],
+            [
+                InstallState::Downloading {
+                    version: "0.2.0".to_string(),
+                    attempt: 1
+                },
+                InstallState::Downloading {
+                    version: "0.2.0".to_string(),
+                    attempt: 2
+                },
+                InstallState::Verifying {
+                    version: "0.2.0".to_string(),
+                    attempt: 2
+                },
+            ]
+        );
+        assert!(matches!(seen.last(), Some(InstallState::Ready { .. })));
+        assert_eq!(steps.rollbacks, 1);
+    }
+
+    #[test]
+    fn gives_up_on_a_corrupt_asset() {
+        let mut steps = Scripted {
+            downloads: vec![Ok(()); MAX_DOWNLOAD_ATTEMPTS as usize],
+            corrupt: true,
+            ..Default::default()
+        };
+        let seen = states(&mut steps);
+        assert_eq!(
+            seen.last(),
+            Some(&InstallState::Failed("not a native executable".to_string()))
+        );
+        assert_eq!(steps.rollbacks, MAX_DOWNLOAD_ATTEMPTS);
+    }
+
+    #[test]
+    fn terminal_states_do_not_move() {
+        let failed = InstallState::Failed("offline".to_string());
+        assert_eq!(failed.clone().next(Outcome::Done), failed);
+        assert_eq!(
+            InstallState::Installing {
+                version: "0.2.0".to_string()
+            }
+            .next(Outcome::Done),
+            InstallState::Failed(
+                VulneraError::Internal {
+                    reason: "unexpected install step Done while Installing { version: \"0.2.0\" }"
+                        .to_string()
+                }
+                .into()
+            )
+        );
+    }
+}
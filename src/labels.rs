@@ -0,0 +1,218 @@
+//! Code labels for the adapter's completions and workspace symbols.
+//!
+//! Version completions in manifests carry an advisory note in `detail`:
+//! `fixes GHSA-…[, …] (critical)` for versions clearing the dependency's
+//! findings, `affected by GHSA-…[, …] (high)` for still-vulnerable ones. They
+//! are rendered as `1.4.2 ✔ fixes GHSA-…, critical` with the severity
+//! highlighted; other completions keep Zed's default label.
+//!
+//! Workspace symbols are advisories named `<ID> (<severity>) <package>@<version>`
+//! and affected packages (kind `Package`) named `<package>@<version>`; they get
+//! a severity badge and the package context in the symbol pickers.
+
+use zed_extension_api::lsp::{Completion, Symbol, SymbolKind};
+use zed_extension_api::{CodeLabel, CodeLabelSpan};
+
+use crate::settings::Severity;
+
+/// Advisory note attached to a version completion.
+#[derive(Debug, PartialEq, Eq)]
+struct VersionNote<'a> {
+    fixes: bool,
+    advisories: Vec<&'a str>,
+    severity: Option<Severity>,
+}
+
+/// Decorated label for a version completion, if it carries an advisory note.
+pub fn completion_label(completion: &Completion) -> Option<CodeLabel> {
+    let note = parse_note(completion.detail.as_deref()?)?;
+    let version = completion.label.as_str();
+
+    let (mark, verb) = if note.fixes {
+        ("✔", "fixes")
+    } else {
+        ("✘", "affected by")
+    };
+    let mut advisories = note.advisories[0].to_string();
+    if note.advisories.len() > 1 {
+        advisories.push_str(&format!(" +{}", note.advisories.len() - 1));
+    }
+
+    let mut spans = vec![
+        CodeLabelSpan::literal(version, Some("string".to_string())),
+        CodeLabelSpan::literal(
+            format!(" {} {} {}", mark, verb, advisories),
+            Some("comment".to_string()),
+        ),
+    ];
+    if let Some(severity) = note.severity {
+        spans.push(CodeLabelSpan::literal(", ", None));
+        spans.push(CodeLabelSpan::literal(
+            severity.as_str(),
+            Some(severity_highlight(severity).to_string()),
+        ));
+    }
+
+    Some(CodeLabel {
+        code: String::new(),
+        spans,
+        filter_range: (0..version.len()).into(),
+    })
+}
+
+/// Decorated label for an advisory or affected-package workspace symbol.
+pub fn symbol_label(symbol: &Symbol) -> Option<CodeLabel> {
+    if let SymbolKind::Package = symbol.kind {
+        let (package, version) = split_package(&symbol.name)?;
+        return Some(CodeLabel {
+            code: String::new(),
+            spans: vec![
+                CodeLabelSpan::literal(package, Some("type".to_string())),
+                CodeLabelSpan::literal(format!(" {}", version), Some("string".to_string())),
+            ],
+            filter_range: (0..package.len()).into(),
+        });
+    }
+
+    let advisory = parse_advisory_symbol(&symbol.name)?;
+    let badge = format!("[{}]", advisory.severity.as_str().to_ascii_uppercase());
+    let mut spans = vec![
+        CodeLabelSpan::literal(
+            badge.clone(),
+            Some(severity_highlight(advisory.severity).to_string()),
+        ),
+        CodeLabelSpan::literal(" ", None),
+        CodeLabelSpan::literal(advisory.id, Some("title".to_string())),
+    ];
+    if let Some((package, version)) = advisory.package {
+        spans.push(CodeLabelSpan::literal(
+            format!(" {} {}", package, version),
+            Some("comment".to_string()),
+        ));
+    }
+
+    let id_start = badge.len() + 1;
+    Some(CodeLabel {
+        code: String::new(),
+        spans,
+        filter_range: (id_start..id_start + advisory.id.len()).into(),
+    })
+}
+
+/// An advisory workspace symbol.
+#[derive(Debug, PartialEq, Eq)]
+struct AdvisorySymbol<'a> {
+    id: &'a str,
+    severity: Severity,
+    package: Option<(&'a str, &'a str)>,
+}
+
+/// Parse `<ID> (<severity>)[ <package>@<version>]`.
+fn parse_advisory_symbol(name: &str) -> Option<AdvisorySymbol<'_>> {
+    let (id, rest) = name.split_once(" (")?;
+    let (severity, rest) = rest.split_once(')')?;
+    let rest = rest.trim();
+    Some(AdvisorySymbol {
+        id,
+        severity: Severity::parse(severity)?,
+        package: if rest.is_empty() {
+            None
+        } else {
+            Some(split_package(rest)?)
+        },
+    })
+}
+
+/// Split `name@version`, keeping scoped npm names (`@scope/pkg`) intact.
+fn split_package(spec: &str) -> Option<(&str, &str)> {
+    spec.rsplit_once('@')
+        .filter(|(name, version)| !name.is_empty() && !version.is_empty())
+}
+
+/// Theme highlight used for a severity badge.
+fn severity_highlight(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Critical | Severity::High => "emphasis.strong",
+        Severity::Medium => "emphasis",
+        Severity::Low => "comment",
+    }
+}
+
+/// Parse `fixes|affected by <ID>[, <ID>…][ (<severity>)]`.
+fn parse_note(detail: &str) -> Option<VersionNote<'_>> {
+    let (fixes, rest) = if let Some(rest) = detail.strip_prefix("fixes ") {
+        (true, rest)
+    } else {
+        (false, detail.strip_prefix("affected by ")?)
+    };
+
+    let (ids, severity) = match rest.trim_end().strip_suffix(')') {
+        Some(head) => {
+            let (ids, severity) = head.rsplit_once(" (")?;
+            (ids, Some(Severity::parse(severity)?))
+        }
+        None => (rest, None),
+    };
+    let advisories: Vec<&str> = ids
+        .split(',')
+        .map(str::trim)
+        .filter(|id| !id.is_empty())
+        .collect();
+    if advisories.is_empty() {
+        return None;
+    }
+
+    Some(VersionNote {
+        fixes,
+        advisories,
+        severity,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AdvisorySymbol, VersionNote, parse_advisory_symbol, parse_note};
+    use crate::settings::Severity;
+
+    #[test]
+    fn parses_version_notes() {
+        assert_eq!(
+            parse_note("fixes GHSA-jf85-cpcp-j695, GHSA-p6mc-m468-83gw (critical)"),
+            Some(VersionNote {
+                fixes: true,
+                advisories: vec!["GHSA-jf85-cpcp-j695", "GHSA-p6mc-m468-83gw"],
+                severity: Some(Severity::Critical),
+            })
+        );
+        assert_eq!(
+            parse_note("affected by RUSTSEC-2021-0078"),
+            Some(VersionNote {
+                fixes: false,
+                advisories: vec!["RUSTSEC-2021-0078"],
+                severity: None,
+            })
+        );
+        assert_eq!(parse_note("latest"), None);
+    }
+
+    #[test]
+    fn parses_advisory_symbols() {
+        assert_eq!(
+            parse_advisory_symbol("GHSA-jf85-cpcp-j695 (critical) @scope/lodash@4.17.10"),
+            Some(AdvisorySymbol {
+                id: "GHSA-jf85-cpcp-j695",
+                severity: Severity::Critical,
+                package: Some(("@scope/lodash", "4.17.10")),
+            })
+        );
+        assert_eq!(
+            parse_advisory_symbol("RUSTSEC-2021-0078 (High)"),
+            Some(AdvisorySymbol {
+                id: "RUSTSEC-2021-0078",
+                severity: Severity::High,
+                package: None,
+            })
+        );
+        assert_eq!(parse_advisory_symbol("serde"), None);
+    }
+}
@@ -5,140 +5,261 @@
 //!
 //! ## Binary lifecycle
 //! 1. On `language_server_command`, resolve the current OS/arch to a target triple.
-//! 2. Check if `server/vulnera-adapter[.exe]` exists and its version matches the
-//!    latest release fetched from GitHub (cached for 24 h in `server/cached-version.txt`).
-//! 3. If stale or missing, download from GitHub Releases and make executable.
+//! 2. Check if `server/<version>/vulnera-adapter[.exe]` exists for the version
+//!    to run — by default the latest release fetched from GitHub (cached for
+//!    24 h in `server/cached-version.txt`). Each version has its own directory,
+//!    so worktrees pinned to different versions do not reinstall over each other.
+//! 3. If stale or missing, download from GitHub Releases, verify and make
+//!    executable, retrying a failed download once. Each step is a state of the
+//!    [`install`] state machine and is shown as the server's install status.
 //! 4. Return a `Command` that spawns the binary with no extra arguments
 //!    (the binary reads/writes stdio by default).
 //!
+//! Every Zed extension call is synchronous, so whatever reaches the network
+//! holds up the caller and every other call into the extension. Exactly
+//! these paths do (tested in the `simulation` module):
+//!
+//! - `language_server_command`, when the version to run is not installed:
+//!   the first install (one release lookup, its `compat.json` and the
+//!   download), a pin to a version not installed yet (the download), or an
+//!   update found by the last check (its download only). The install status
+//!   is shown meanwhile. Otherwise the version comes from local state alone
+//!   — a pin, else the cached latest version, else the newest installed one
+//!   — and nothing is requested.
+//! - `language_server_workspace_configuration`, which runs after the server
+//!   started: at most once per version-cache lifetime (or sooner after
+//!   `/vulnera-update`), the latest-release lookup, and for a newer release
+//!   its `compat.json` and release notes. It holds up the server's first
+//!   `didChangeConfiguration` meanwhile. It never downloads.
+//! - `/vulnera-update` and the other slash commands the user runs.
+//!
+//! An install made elsewhere while a server runs (by `/vulnera-update` or
+//! another project) reaches it as `adapterUpdate` (see `update_restart`).
+//!
 //! ## Version resolution (priority order)
 //! 1. `VULNERA_ADAPTER_VERSION` env var — explicit pin for CI / development.
-//! 2. `server/cached-version.txt` if its timestamp is within 24 h.
-//! 3. Live query to the GitHub Releases API; result is written to the cache.
-//! 4. Stale cache value (network outage tolerance).
-//! 5. `MINIMUM_ADAPTER_VERSION` as absolute floor.
+//! 2. `adapter_version` in the worktree's `.zed/vulnera.toml`.
+//! 3. `server/cached-version.txt` if its timestamp is within 24 h.
+//! 4. Live query to the GitHub Releases API; result is written to the cache.
+//! 5. Stale cache value (network outage tolerance).
+//! 6. `MINIMUM_ADAPTER_VERSION` as absolute floor, warned about at most
+//!    hourly in the log and shown by `/vulnera-doctor` until a lookup succeeds.
+//!
+//! Steps 3–5 follow the `channel` setting (stable, beta or nightly); each
+//! channel has its own install directory and version cache (see the
+//! [`channel`] module).
+//!
+//! The platform and the resolved version are memoized for the session, so a
+//! warm restart of a server skips the disk and network work above. A memoized
+//! version is keyed by its pin and update settings, expires with the version
+//! cache, and is dropped by `/vulnera-update` (see
+//! [`adapters::invalidate_session`]). A warm start of the same worktree with
+//! the same launch configuration then costs one stat of the installed binary;
+//! the time taken to build each command is logged.
 //!
 //! ## Other environment variable overrides
 //! - `VULNERA_ADAPTER_PATH`  — absolute path to a pre-built binary (skips download entirely).
+//!   Takes precedence over the `lsp.vulnera.binary.path` setting.
 //! - `VULNERA_API_URL`       — API base URL forwarded to the server as an env var.
 //! - `VULNERA_API_KEY`       — API key forwarded to the server as an env var.
 //! - `VULNERA_LOG`           — tracing log filter forwarded to the server (default: `info`).
-
+//! - `VULNERA_LOG_FORMAT`    — `pretty` or `json` log output (overrides the `log_format` setting).
+//! - `VULNERA_ADAPTER_ARGS`  — shell-style extra arguments appended after the
+//!   `adapter_args` setting (e.g. `--trace-lsp`).
+//! - `VULNERA_OFFLINE_DB`    — local vulnerability database; enables offline mode
+//!   (takes precedence over the `offline_db_path` setting).
+//! - `VULNERA_TELEMETRY`     — `off` disables adapter telemetry (as does the
+//!   `telemetry: false` setting or `DO_NOT_TRACK=1`).
+//! - `VULNERA_ADAPTER_CONNECT` — loopback `host:port` or `unix:/path` of an adapter
+//!   that is already running; skips install and bridges stdio to it (see [`transport`]).
+//!   Read from the shell only, never from repository files.
+//! - `VULNERA_EXT_LOG`      — extension log filter such as `warn,adapters=debug`
+//!   when the `extension_log` setting is unset, read once per session;
+//!   while set, extension log lines are also written to `server/extension.log`
+//!   (see the [`logging`] module).
+//! - `VULNERA_GITHUB_TOKEN`  — GitHub token for release lookups, lifting the
+//!   anonymous API rate limit; sent only with the lookups made for the
+//!   worktree whose shell sets it.
+//! - `VULNERA_CONFIG`        — project policy file passed to the server; defaults to a
+//!   `.vulnera.toml` or `vulnera.toml` at the worktree root when present.
+//!
+//! `VULNERA_*` variables missing from the shell environment are also read
+//! from a worktree `.env` / `.env.vulnera` file: all of them in worktrees
+//! listed in `trusted_paths`, elsewhere only `VULNERA_OFFLINE_DB` and
+//! `VULNERA_TELEMETRY` (see the [`dotenv`] module).
+//!
+//! ## Transport
+//! The adapter speaks LSP over stdio unless `transport` selects a TCP or
+//! unix-socket listener bridged to stdio, or `daemon` shares one adapter
+//! process across worktrees (see the [`transport`] module).
+//!
+//! ## Slash commands
+//! Assistant commands (`/vulnera-advisory`, `/vulnera-deps`, …) live in the [`slash_commands`]
+//! module.
+//!
+//! ## Secondary servers
+//! Opt-in server IDs `vulnera-secrets` and `vulnera-iac` run the adapter in
+//! secrets or IaC mode; `vulnera-container` runs a separate binary,
+//! `vulnera-container-adapter`, on Dockerfile and compose buffers. Each is
+//! configured separately (see the [`mode_servers`] module). Every hook looks
+//! the ID up in one table, so adding a server means a `ModeServer` entry and
+//! its `extension.toml` declaration.
+//!
+//! Every managed binary is described by an `AdapterSpec` and follows the
+//! lifecycle above in its own directory (`container/<version>/…` for the
+//! container adapter), with its own version cache and version pin
+//! (`VULNERA_CONTAINER_ADAPTER_VERSION`); see the [`adapters`] module.
+//! Downloads that are not a native executable are rejected before being
+//! marked installed.
+//!
+//! ## Tasks
+//! `/vulnera-task` renders the Zed task templates under `tasks/` against a
+//! stable launcher for the managed adapter (see the [`tasks`] module).
+//!
+//! ## Context server
+//! The `vulnera` MCP context server exposes scan and advisory tools to Zed's
+//! agent (see the [`context_server`] module), plus each worktree's SBOM, which
+//! the core server writes after its scans (see the [`sbom`] module).
+//!
+//! ## Advisory docs
+//! The `vulnera-advisories` docs provider indexes the OSV advisories of the
+//! worktree's locked dependencies (see the [`advisory_docs`] module).
+//!
+//! ## Settings
+//! Extension settings live under `lsp.vulnera.settings` and are parsed by the
+//! [`settings`] module; scan options are forwarded as initialization options.
+//! A worktree `.zed/vulnera.toml` can override the adapter version, binary
+//! path and offline mode for one repository (see the [`overrides`] module).
+
+#[macro_use]
+mod logging;
+
+mod adapters;
+mod advisory_docs;
+mod channel;
+mod context_server;
+mod crash_loop;
+mod doctor;
+mod dotenv;
+mod error;
+mod findings;
+mod host;
+mod http;
+mod idle;
+mod ignore;
+mod install;
+mod labels;
+mod lockfiles;
+mod manifests;
+mod metrics;
+mod mode_servers;
+mod osv;
+mod overrides;
+mod registries;
+mod releases;
+mod sbom;
+mod settings;
+#[cfg(test)]
+mod simulation;
+mod slash_commands;
+mod state;
+mod tasks;
+mod transport;
+mod uninstall;
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
 use std::fs;
-use std::path::PathBuf;
-use std::time::{SystemTime, UNIX_EPOCH};
-
-use zed_extension_api::http_client::{HttpMethod, HttpRequest, RedirectPolicy};
-use zed_extension_api::{self as zed, Architecture, DownloadedFileType, Os, Result};
-
-// ── Constants ─────────────────────────────────────────────────────────────────
-
-/// Absolute minimum version used when the GitHub API is unreachable and no
-/// version has ever been cached locally.
-const MINIMUM_ADAPTER_VERSION: &str = "0.1.1";
-
-/// How many seconds a cached version stays fresh before we re-query GitHub.
-const VERSION_CACHE_TTL_SECS: u64 = 24 * 60 * 60; // 24 hours
-
-/// GitHub repository that publishes `adapter-v*` releases.
-const GITHUB_REPO: &str = "vulnera-rs/adapter";
+use std::hash::{Hash, Hasher};
+use std::io::ErrorKind;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use zed_extension_api::serde_json::{Value, json};
+use zed_extension_api::{self as zed, Os, Result};
+
+use adapters::{
+    AdapterSpec, CORE_ADAPTER, PlatformInfo, resolve_adapter_version, resolve_platform,
+};
+use channel::Channel;
+use error::VulneraError;
+use host::{Checkout, Host};
+use overrides::WorktreeOverrides;
+use settings::{UntrustedMode, UpdateMode, UpdateRestart, VulneraSettings};
 
 /// Language server ID declared in `extension.toml`.
 const SERVER_ID: &str = "vulnera";
 
-// ── Extension state ───────────────────────────────────────────────────────────
-
-struct VulneraExtension {
-    /// Cached path to the installed binary, set after the first successful install.
-    cached_binary: Option<String>,
-}
-
-// ── Platform resolution ───────────────────────────────────────────────────────
-
-/// Maps a (Os, Architecture) pair to the release asset metadata.
-struct PlatformInfo {
-    /// Rust target triple, e.g. `x86_64-unknown-linux-gnu`.
-    target_triple: &'static str,
-    /// Full filename of the release asset on the GitHub release page.
-    asset_name: &'static str,
-    /// Whether the platform requires a `.exe` suffix.
-    is_windows: bool,
+/// A language server ID this extension answers for. Every `Extension` hook
+/// dispatches on this; a new server is a [`mode_servers::ModeServer`] entry.
+#[derive(Clone, Copy)]
+enum Server {
+    /// [`SERVER_ID`]: the core adapter, configured under `lsp.vulnera`.
+    Core,
+    /// A secondary server, configured under `lsp.<id>`.
+    Mode(&'static mode_servers::ModeServer),
 }
 
-fn resolve_platform(os: Os, arch: Architecture) -> Result<PlatformInfo> {
-    match (os, arch) {
-        (Os::Linux, Architecture::X8664) => Ok(PlatformInfo {
-            target_triple: "x86_64-unknown-linux-gnu",
-            asset_name: "vulnera-adapter-x86_64-unknown-linux-gnu",
-            is_windows: false,
-        }),
-        (Os::Linux, Architecture::Aarch64) => Ok(PlatformInfo {
-            target_triple: "aarch64-unknown-linux-gnu",
-            asset_name: "vulnera-adapter-aarch64-unknown-linux-gnu",
-            is_windows: false,
-        }),
-        (Os::Mac, Architecture::X8664) => Ok(PlatformInfo {
-            target_triple: "x86_64-apple-darwin",
-            asset_name: "vulnera-adapter-x86_64-apple-darwin",
-            is_windows: false,
-        }),
-        (Os::Mac, Architecture::Aarch64) => Ok(PlatformInfo {
-            target_triple: "aarch64-apple-darwin",
-            asset_name: "vulnera-adapter-aarch64-apple-darwin",
-            is_windows: false,
-        }),
-        (Os::Windows, Architecture::X8664) => Ok(PlatformInfo {
-            target_triple: "x86_64-pc-windows-msvc",
-            asset_name: "vulnera-adapter-x86_64-pc-windows-msvc.exe",
-            is_windows: true,
-        }),
-        _ => Err(format!(
-            "Vulnera: unsupported platform ({:?} / {:?}). \
-             Build vulnera-adapter from source and set VULNERA_ADAPTER_PATH.",
-            os, arch
-        )),
+impl Server {
+    /// The server registered as `id`, if any.
+    fn find(id: &str) -> Option<Self> {
+        match id {
+            SERVER_ID => Some(Server::Core),
+            id => mode_servers::find(id).map(Server::Mode),
+        }
     }
 }
 
-// ── Path helpers ──────────────────────────────────────────────────────────────
-
-fn binary_path(platform: &PlatformInfo) -> String {
-    if platform.is_windows {
-        "server/vulnera-adapter.exe".to_string()
-    } else {
-        "server/vulnera-adapter".to_string()
-    }
-}
+// ── Extension state ───────────────────────────────────────────────────────────
 
-fn installed_version_path() -> &'static str {
-    "server/installed-version.txt"
+struct VulneraExtension {
+    /// Installed binary paths keyed by binary name and version, shared by all
+    /// worktrees.
+    installed_binaries: HashMap<(&'static str, String), String>,
+    /// [`launch_fingerprint`] of the last command built, per worktree id.
+    launch_fingerprints: HashMap<u64, u64>,
+    /// The host platform, resolved on first launch.
+    platform: Option<PlatformInfo>,
+    /// Versions resolved this session, keyed by binary name and
+    /// [`LaunchContext::version_key`].
+    resolved_versions: HashMap<(&'static str, String), ResolvedVersion>,
+    /// Binary the task launcher last pointed at.
+    launcher_target: Option<String>,
+    /// Recent launches, to notice a release crashing on startup.
+    launch_history: crash_loop::LaunchHistory,
+    /// Managed version last launched, keyed by binary name and worktree root.
+    running_versions: HashMap<(&'static str, String), String>,
+    /// Core adapter binary last launched per worktree id, which the context
+    /// server of the worktree's project runs too.
+    core_binaries: HashMap<u64, String>,
+    /// Updates whose release notes were looked up, keyed by binary name and
+    /// version, so a failed lookup is not repeated on every configuration.
+    release_notes: HashSet<(&'static str, String)>,
 }
 
-fn cached_latest_version_path() -> &'static str {
-    "server/cached-version.txt"
+/// A version memoized by [`VulneraExtension::session_version`].
+struct ResolvedVersion {
+    version: String,
+    /// When the version was resolved, in seconds since the epoch.
+    at: u64,
+    /// [`adapters::generation`] at resolution time.
+    generation: u64,
+    /// Lifetime of the version cache of the binary's channel.
+    ttl: u64,
 }
 
-fn cached_version_timestamp_path() -> &'static str {
-    "server/cached-version-timestamp.txt"
+impl ResolvedVersion {
+    /// Whether the memoized version may still be used at `now`.
+    fn is_fresh(&self, now: u64) -> bool {
+        self.generation == adapters::generation() && now.saturating_sub(self.at) < self.ttl
+    }
 }
 
-// ── Installed-version marker ──────────────────────────────────────────────────
+// ── Platform helpers ──────────────────────────────────────────────────────────
 
-fn read_installed_version() -> Option<String> {
-    fs::read_to_string(installed_version_path())
-        .ok()
-        .map(|s| s.trim().to_string())
-        .filter(|s| !s.is_empty())
+fn is_windows() -> bool {
+    matches!(zed::current_platform().0, Os::Windows)
 }
 
-fn write_installed_version(version: &str) {
-    if let Err(e) = fs::write(installed_version_path(), version) {
-        eprintln!("[Vulnera] Failed to write installed-version marker: {}", e);
-    }
-}
-
-// ── Latest-version cache (with TTL) ──────────────────────────────────────────
-
 fn now_secs() -> u64 {
     SystemTime::now()
         .duration_since(UNIX_EPOCH)
@@ -146,259 +267,902 @@ fn now_secs() -> u64 {
         .unwrap_or(0)
 }
 
-fn read_cached_latest_version() -> Option<(String, u64)> {
-    let version = fs::read_to_string(cached_latest_version_path())
-        .ok()
-        .map(|s| s.trim().to_string())
-        .filter(|s| !s.is_empty())?;
+// ── Offline database ──────────────────────────────────────────────────────────
+
+/// Resolve the offline vulnerability-database path from `VULNERA_OFFLINE_DB`
+/// or the `offline_db_path` setting, relative to the worktree root.
+///
+/// The extension runs sandboxed and may not be able to see host paths outside
+/// its work directory, so a failed check is only logged; the adapter reports
+/// the definitive error if the database is really missing.
+fn resolve_offline_db(
+    shell_env: &[(String, String)],
+    settings: &VulneraSettings,
+    overrides: &WorktreeOverrides,
+    worktree_root: &str,
+) -> Option<String> {
+    let env_db = env_value(shell_env, "VULNERA_OFFLINE_DB");
+    let raw = match overrides.offline {
+        Some(false) if env_db.is_none() => return None,
+        _ => env_db.or(settings.offline_db_path()),
+    };
+    let Some(raw) = raw else {
+        if overrides.offline == Some(true) {
+            log!(
+                "{} requests offline mode but no offline database is configured",
+                overrides::OVERRIDES_FILE
+            );
+        }
+        return None;
+    };
+    let path = settings::resolve_in_worktree(worktree_root, raw);
 
-    let timestamp: u64 = fs::read_to_string(cached_version_timestamp_path())
-        .ok()
-        .and_then(|s| s.trim().parse().ok())
-        .unwrap_or(0);
+    match fs::metadata(&path) {
+        Ok(_) => log!("Offline mode using database at {}", path),
+        Err(e) if e.kind() == ErrorKind::NotFound => {
+            log!("Offline database not found at {}", path)
+        }
+        Err(e) => warn!("Could not verify offline database at {}: {}", path, e),
+    }
 
-    Some((version, timestamp))
+    Some(path)
 }
 
-fn write_cached_latest_version(version: &str) {
-    if let Err(e) = fs::write(cached_latest_version_path(), version) {
-        eprintln!("[Vulnera] Failed to write cached-version: {}", e);
-    }
-    if let Err(e) = fs::write(cached_version_timestamp_path(), now_secs().to_string()) {
-        eprintln!("[Vulnera] Failed to write cached-version timestamp: {}", e);
+/// Project policy files looked up at the worktree root, in order.
+const PROJECT_CONFIG_FILES: &[&str] = &[".vulnera.toml", "vulnera.toml"];
+
+/// Resolve the project policy file shared with the CLI and CI: the
+/// `VULNERA_CONFIG` override, else the first [`PROJECT_CONFIG_FILES`] entry
+/// present in the worktree.
+fn resolve_project_config(
+    shell_env: &[(String, String)],
+    worktree: &dyn Checkout,
+) -> Option<String> {
+    let root = worktree.root_path();
+    if let Some(path) = env_value(shell_env, "VULNERA_CONFIG") {
+        return Some(settings::resolve_in_worktree(&root, path));
     }
+
+    let file = PROJECT_CONFIG_FILES
+        .iter()
+        .find(|file| worktree.read_text_file(file).is_ok())?;
+    let path = settings::resolve_in_worktree(&root, file);
+    log!("Using project config {}", path);
+    Some(path)
 }
 
-// ── GitHub version fetch ──────────────────────────────────────────────────────
+/// Look up `key` in the worktree shell environment, ignoring blank values.
+fn env_value<'a>(shell_env: &'a [(String, String)], key: &str) -> Option<&'a str> {
+    shell_env
+        .iter()
+        .find(|(k, _)| k == key)
+        .map(|(_, v)| v.trim())
+        .filter(|v| !v.is_empty())
+}
 
-/// Query the GitHub Releases API and return the version string (without the
-/// `adapter-v` prefix) of the latest stable `adapter-v*` release, or `None`
-/// if the request fails or no matching release is found.
-fn fetch_latest_adapter_version_from_github() -> Option<String> {
-    let url = format!("https://api.github.com/repos/{}/releases", GITHUB_REPO);
+// ── Launch context ────────────────────────────────────────────────────────────
+
+/// Everything resolved from settings, the environment and worktree files that
+/// shapes how the adapter runs for one worktree — as a language server or as a
+/// one-off CLI invocation from a slash command.
+struct LaunchContext {
+    settings: VulneraSettings,
+    overrides: WorktreeOverrides,
+    shell_env: Vec<(String, String)>,
+    worktree_root: String,
+    offline_db: Option<String>,
+    /// Whether the worktree may pick its own adapter binary and receives
+    /// registry credentials; see [`VulneraSettings::trusts`].
+    trusted: bool,
+}
 
-    let request = HttpRequest {
-        url,
-        method: HttpMethod::Get,
-        headers: vec![
-            (
-                "User-Agent".to_string(),
-                "vulnera-zed-extension".to_string(),
-            ),
+impl LaunchContext {
+    /// Resolve the launch context for `worktree`, refusing untrusted worktrees
+    /// that may not run the adapter.
+    fn for_worktree(worktree: &zed::Worktree) -> Result<Self> {
+        Self::resolve(VulneraSettings::for_worktree(worktree)?, worktree)
+    }
+
+    /// [`Self::for_worktree`] with the settings already read.
+    fn resolve(settings: VulneraSettings, worktree: &dyn Checkout) -> Result<Self> {
+        logging::set_debug(settings.debug);
+        let worktree_root = worktree.root_path();
+        let trusted = settings.trusts(&worktree_root);
+
+        // An untrusted checkout must not pick its own binary or endpoints.
+        let (shell_env, mut overrides) = if trusted {
             (
-                "Accept".to_string(),
-                "application/vnd.github+json".to_string(),
-            ),
-        ],
-        body: None,
-        redirect_policy: RedirectPolicy::FollowAll,
-    };
+                dotenv::merge_worktree_dotenv(
+                    worktree.shell_env(),
+                    worktree,
+                    settings.explicitly_trusts(&worktree_root),
+                ),
+                WorktreeOverrides::for_worktree(worktree)?,
+            )
+        } else {
+            (worktree.shell_env(), WorktreeOverrides::default())
+        };
+        // Trust by default covers settings, not running a checked-in program.
+        if overrides.binary_path.is_some() && !settings.explicitly_trusts(&worktree_root) {
+            warn!(
+                "Ignoring binary_path in {}: add {} to trusted_paths to run an adapter \
+                 supplied by the repository",
+                overrides::OVERRIDES_FILE,
+                worktree_root
+            );
+            overrides.binary_path = None;
+        }
+        for spec in adapters::ALL {
+            if let Some(pin) = env_value(&shell_env, spec.version_env)
+                && !adapters::is_semver(pin)
+            {
+                return Err(VulneraError::config(
+                    spec.version_env,
+                    format!("`{}` is not a version such as 0.4.0", pin),
+                )
+                .into());
+            }
+        }
+        logging::register_secrets(&shell_env);
+        // Session-wide, so from the user's shell, never a worktree's dotenv file.
+        let user_env = worktree.shell_env();
+        logging::configure(
+            settings
+                .extension_log()
+                .or_else(|| env_value(&user_env, "VULNERA_EXT_LOG")),
+        );
 
-    // `fetch` returns Err on transport failures and non-2xx HTTP errors.
-    let response = match zed_extension_api::http_client::fetch(&request) {
-        Ok(r) => r,
-        Err(e) => {
-            eprintln!("[Vulnera] GitHub API request failed: {}", e);
-            return None;
+        let offline_db = resolve_offline_db(&shell_env, &settings, &overrides, &worktree_root);
+        if !trusted {
+            match (settings.untrusted_mode, &offline_db) {
+                (UntrustedMode::Offline, Some(_)) => {
+                    log!("{} is not trusted; scanning offline only", worktree_root)
+                }
+                (UntrustedMode::Offline, None) => {
+                    return Err(VulneraError::Untrusted {
+                        worktree: worktree_root,
+                        reason: "no offline_db_path is configured to scan it offline".to_string(),
+                    }
+                    .into());
+                }
+                (UntrustedMode::Skip, _) => {
+                    return Err(VulneraError::Untrusted {
+                        worktree: worktree_root,
+                        reason: "untrusted_mode is skip".to_string(),
+                    }
+                    .into());
+                }
+            }
         }
-    };
 
-    let body = match String::from_utf8(response.body) {
-        Ok(b) => b,
-        Err(e) => {
-            eprintln!("[Vulnera] Failed to decode GitHub API response: {}", e);
-            return None;
+        Ok(LaunchContext {
+            settings,
+            overrides,
+            shell_env,
+            worktree_root,
+            offline_db,
+            trusted,
+        })
+    }
+
+    /// `base` sending this worktree's `VULNERA_GITHUB_TOKEN` to GitHub. Only
+    /// a worktree in `trusted_paths` may set it in its `.env` (see
+    /// [`dotenv::SCAN_KEYS`]); elsewhere the token is the user's own.
+    fn host<'a>(&'a self, base: &Host<'a>) -> Host<'a> {
+        Host {
+            github_token: env_value(&self.shell_env, "VULNERA_GITHUB_TOKEN"),
+            ..*base
         }
-    };
+    }
 
-    // Guard against HTML error pages (non-JSON responses).
-    if !body.trim_start().starts_with('[') {
-        eprintln!("[Vulnera] GitHub API returned unexpected body (not a JSON array)");
-        return None;
+    /// A user-supplied adapter binary and where it was set:
+    /// `VULNERA_ADAPTER_PATH`, then the `.zed/vulnera.toml` `binary_path`,
+    /// then `lsp.vulnera.binary.path`. Untrusted worktrees always run the
+    /// managed adapter.
+    fn binary_override(&self) -> Option<(String, &'static str)> {
+        if !self.trusted {
+            return None;
+        }
+        if let Some(p) = env_value(&self.shell_env, "VULNERA_ADAPTER_PATH") {
+            log!("Using VULNERA_ADAPTER_PATH override: {}", p);
+            return Some((p.to_string(), "VULNERA_ADAPTER_PATH"));
+        }
+        if let Some(p) = self
+            .overrides
+            .binary_path
+            .as_deref()
+            .map(str::trim)
+            .filter(|p| !p.is_empty())
+        {
+            let p = settings::resolve_in_worktree(&self.worktree_root, p);
+            log!(
+                "Using {} binary_path override: {}",
+                overrides::OVERRIDES_FILE,
+                p
+            );
+            return Some((p, "binary_path in .zed/vulnera.toml"));
+        }
+        if let Some(p) = self.settings.binary_path() {
+            let p = settings::resolve_in_worktree(&self.worktree_root, p);
+            log!("Using lsp.vulnera.binary.path override: {}", p);
+            return Some((p, "lsp.vulnera.binary.path"));
+        }
+        None
     }
 
-    // Minimal JSON extraction — find the first non-draft, non-prerelease
-    // entry whose `tag_name` starts with `"adapter-v"`.
-    //
-    // We avoid pulling in `serde_json` to keep the WASM binary small;
-    // the GitHub releases list format is stable enough for this approach.
-    parse_latest_stable_version(&body)
-}
+    /// The version of `binary` this worktree should run. `.zed/vulnera.toml`
+    /// pins the core adapter only; with `auto_update: false` or outside
+    /// `update_mode: "auto"`, unpinned binaries stay on the installed version.
+    fn adapter_version(&self, host: &Host, binary: &AdapterSpec) -> String {
+        let pinned = if binary.is_core() {
+            self.overrides.adapter_version.as_deref()
+        } else {
+            None
+        };
+        let is_pinned = self.version_pin(binary).is_some();
+        if !is_pinned
+            && self.settings.auto_update == Some(false)
+            && let Some(installed) = adapters::newest_installed(host, binary, None)
+        {
+            debug!("auto_update is off; keeping {} {}", binary.name, installed);
+            return installed;
+        }
 
-/// Extract the first stable `adapter-v{VERSION}` tag from a raw JSON string
-/// that looks like the GitHub `/releases` endpoint response.
-///
-/// Returns the version number without the `adapter-v` prefix on success.
-fn parse_latest_stable_version(json: &str) -> Option<String> {
-    // Each release object contains "tag_name":"adapter-vX.Y.Z".
-    // We scan for that pattern while skipping entries marked as draft or prerelease.
-    //
-    // The JSON array is ordered newest-first, so the first matching entry is
-    // the version we want.
-    let mut remaining = json;
-
-    while let Some(tag_start) = remaining.find("\"tag_name\":") {
-        let after_key = &remaining[tag_start + "\"tag_name\":".len()..];
-
-        // Find the opening quote of the tag value.
-        let value_start = after_key.find('"')? + 1;
-        let value_slice = &after_key[value_start..];
-        let value_end = value_slice.find('"')?;
-        let tag_name = &value_slice[..value_end];
-
-        if tag_name.starts_with("adapter-v") {
-            // Now look ahead within the same object for "prerelease":true or
-            // "draft":true. Objects are separated by `},{` in the array.
-            // We look at the substring up to the next top-level `},{`.
-            let object_end = remaining[tag_start..]
-                .find("},{")
-                .unwrap_or(remaining.len() - tag_start);
-            let object_slice = &remaining[tag_start..tag_start + object_end];
-
-            let is_prerelease = object_slice.contains("\"prerelease\":true");
-            let is_draft = object_slice.contains("\"draft\":true");
-
-            if !is_prerelease && !is_draft {
-                let version = tag_name.trim_start_matches("adapter-v").to_string();
-                if !version.is_empty() {
-                    return Some(version);
-                }
-            }
+        let resolved = resolve_adapter_version(host, binary, &self.shell_env, pinned);
+        match self.settings.update_mode {
+            _ if is_pinned => resolved,
+            UpdateMode::Auto => resolved,
+            mode => adapters::hold_update(host, binary, resolved, mode == UpdateMode::Notify),
         }
+    }
 
-        // Advance past this tag_name occurrence.
-        remaining = &remaining[tag_start + "\"tag_name\":".len()..];
+    /// The version of `binary` to launch without touching the network: a pin,
+    /// else [`adapters::local_version`]. `None` when nothing usable is
+    /// installed yet, so only a first install waits for GitHub.
+    fn local_version(&self, host: &Host, binary: &AdapterSpec) -> Option<String> {
+        if let Some((version, _)) = self.version_pin(binary) {
+            return Some(version);
+        }
+        adapters::local_version(host, binary)
     }
 
-    None
-}
+    /// Everything in this context that selects the version of `binary`, so a
+    /// memoized version is not reused after a pin or update setting changes.
+    fn version_key(&self, binary: &AdapterSpec) -> String {
+        format!(
+            "{}|{:?}|{:?}|{:?}",
+            binary.channel,
+            self.version_pin(binary),
+            self.settings.update_mode,
+            self.settings.auto_update
+        )
+    }
 
-// ── Version resolution ────────────────────────────────────────────────────────
+    /// Whether releases newer than the installed one are installed as they
+    /// appear (`update_mode: "auto"`, `auto_update` not off).
+    fn installs_updates(&self) -> bool {
+        self.settings.update_mode == UpdateMode::Auto && self.settings.auto_update != Some(false)
+    }
 
-/// Resolve the adapter version to use, applying the priority chain documented
-/// at the top of this module.
-fn resolve_adapter_version(shell_env: &[(String, String)]) -> String {
-    // 1. Env var pin.
-    if let Some((_, v)) = shell_env
-        .iter()
-        .find(|(k, _)| k == "VULNERA_ADAPTER_VERSION")
-    {
-        let v = v.trim();
-        if !v.is_empty() {
-            eprintln!("[Vulnera] Adapter version from env override: {}", v);
-            return v.to_string();
-        }
+    /// The release channel: a user setting, never a worktree override.
+    fn channel(&self) -> Channel {
+        self.settings.channel
     }
 
-    let now = now_secs();
+    /// `binary` on this worktree's release channel.
+    fn on_channel(&self, binary: &AdapterSpec) -> AdapterSpec {
+        binary.on_channel(self.channel())
+    }
 
-    // 2. Fresh cache hit.
-    if let Some((cached, fetched_at)) = read_cached_latest_version()
-        && now.saturating_sub(fetched_at) < VERSION_CACHE_TTL_SECS
-    {
-        eprintln!(
-            "[Vulnera] Adapter version from cache (age {}s): {}",
-            now.saturating_sub(fetched_at),
-            cached
-        );
-        return cached;
+    /// The version `binary` is pinned to and where the pin comes from.
+    fn version_pin(&self, binary: &AdapterSpec) -> Option<(String, String)> {
+        if let Some(v) = env_value(&self.shell_env, binary.version_env) {
+            return Some((v.to_string(), binary.version_env.to_string()));
+        }
+        let pinned = self
+            .overrides
+            .adapter_version
+            .as_deref()
+            .map(str::trim)
+            .filter(|v| binary.is_core() && !v.is_empty())?;
+        Some((pinned.to_string(), overrides::OVERRIDES_FILE.to_string()))
     }
 
-    // 3. Live fetch.
-    eprintln!("[Vulnera] Fetching latest adapter version from GitHub…");
-    if let Some(fetched) = fetch_latest_adapter_version_from_github() {
-        eprintln!("[Vulnera] Latest adapter version from GitHub: {}", fetched);
-        write_cached_latest_version(&fetched);
-        return fetched;
+    /// The adapter binary for CLI use, from local state alone: nothing is
+    /// installed and no release is looked up.
+    fn installed_binary(&self) -> Result<String> {
+        if let Some((p, _)) = self.binary_override() {
+            return Ok(p);
+        }
+        let (os, arch) = zed::current_platform();
+        let platform = resolve_platform(os, arch)?;
+        let binary = self.on_channel(&CORE_ADAPTER);
+        let host = self.host(&Host::REAL);
+        let version =
+            self.local_version(&host, &binary)
+                .ok_or_else(|| VulneraError::NotInstalled {
+                    binary: binary.name.to_string(),
+                    reason: "no version has been downloaded yet".to_string(),
+                })?;
+        adapters::health(&host, &binary, &platform, &version)
     }
 
-    // 4. Stale cache fallback.
-    if let Some((cached, _)) = read_cached_latest_version() {
-        eprintln!(
-            "[Vulnera] GitHub fetch failed; using stale cached version: {}",
-            cached
-        );
-        return cached;
+    /// A command running `binary` with the adapter environment and no
+    /// arguments.
+    fn command(&self, binary: String, worktree: &dyn Checkout) -> zed::Command {
+        let mut command = self.command_without_credentials(binary, worktree);
+        if let Some(key) = self
+            .shell_env
+            .iter()
+            .find(|(k, v)| k == "VULNERA_API_KEY" && !v.trim().is_empty())
+            && self.offline_db.is_none()
+        {
+            command.env.push(key.clone());
+        }
+        if self.trusted {
+            let registry_env = registries::registry_env(
+                &self.settings.registries,
+                worktree,
+                &self.shell_env,
+                self.settings.explicitly_trusts(&self.worktree_root),
+            );
+            logging::register_secrets(&registry_env);
+            command.env.extend(registry_env);
+        }
+        command
     }
 
-    // 5. Absolute floor.
-    eprintln!(
-        "[Vulnera] GitHub fetch failed and no cache; falling back to minimum: {}",
-        MINIMUM_ADAPTER_VERSION
-    );
-    MINIMUM_ADAPTER_VERSION.to_string()
+    /// [`Self::command`] minus the Vulnera API key and registry credentials,
+    /// for binaries the user points a secondary server at.
+    fn command_without_credentials(&self, binary: String, worktree: &dyn Checkout) -> zed::Command {
+        let mut command = build_command(
+            binary,
+            &self.shell_env,
+            &self.settings,
+            &self.worktree_root,
+            self.offline_db.as_deref(),
+        );
+        if let Some(config) = resolve_project_config(&self.shell_env, worktree) {
+            command.env.retain(|(k, _)| k != "VULNERA_CONFIG");
+            command.env.push(("VULNERA_CONFIG".into(), config));
+        }
+        command.env.retain(|(k, _)| k != "VULNERA_API_KEY");
+        command
+    }
 }
 
-// ── Download ──────────────────────────────────────────────────────────────────
-
-fn download_url(platform: &PlatformInfo, version: &str) -> String {
-    format!(
-        "https://github.com/{}/releases/download/adapter-v{}/{}",
-        GITHUB_REPO, version, platform.asset_name
-    )
+/// Run the managed adapter by file name, with its install directory first on
+/// `PATH`, so the extension's `process:exec` capability can name the adapter
+/// instead of allowing any command.
+fn exec_by_name(command: &mut zed::Command, host: &Host, windows: bool) -> Result<()> {
+    let Some((dir, name)) = command.command.rsplit_once(['/', '\\']) else {
+        return Ok(());
+    };
+    let dir = host
+        .fs
+        .absolute(dir)
+        .map_err(|e| VulneraError::fs("resolve the adapter directory", e))?;
+    let path = match command.env.iter().position(|(k, _)| k == "PATH") {
+        Some(i) => {
+            let (_, inherited) = command.env.remove(i);
+            format!("{}{}{}", dir, if windows { ';' } else { ':' }, inherited)
+        }
+        None => dir,
+    };
+    command.env.push(("PATH".to_string(), path));
+    command.command = name.to_string();
+    Ok(())
 }
 
-fn download_binary(platform: &PlatformInfo, version: &str) -> Result<()> {
-    if let Err(e) = fs::create_dir_all("server") {
-        return Err(format!(
-            "Vulnera: failed to create server/ directory: {}",
-            e
-        ));
+/// Run the installed adapter as a one-off CLI for `worktree` and return its
+/// stdout. Used by slash commands; never installs the adapter.
+fn run_adapter_cli(worktree: &zed::Worktree, args: &[&str]) -> Result<String> {
+    let ctx = LaunchContext::for_worktree(worktree)?;
+    let binary = ctx.installed_binary()?;
+    let mut command = ctx.command(binary, worktree);
+    if ctx.binary_override().is_none() {
+        exec_by_name(&mut command, &Host::REAL, is_windows())?;
     }
+    command.args = args.iter().map(|a| a.to_string()).collect();
+
+    debug!("Running {} {:?}", command.command, command.args);
+    let output = command.output().map_err(|e| match ctx.binary_override() {
+        Some((path, source)) => VulneraError::BadOverride {
+            path,
+            source,
+            reason: e.to_string(),
+        },
+        None => VulneraError::AdapterFailed {
+            reason: format!("failed to run the adapter: {}", e),
+        },
+    })?;
+    if output.status != Some(0) {
+        return Err(VulneraError::AdapterFailed {
+            reason: format!(
+                "`vulnera-adapter {}` failed: {}",
+                args.first().unwrap_or(&""),
+                String::from_utf8_lossy(&output.stderr).trim()
+            ),
+        }
+        .into());
+    }
+    String::from_utf8(output.stdout).map_err(|e| {
+        VulneraError::AdapterFailed {
+            reason: format!("adapter output is not UTF-8: {}", e),
+        }
+        .into()
+    })
+}
 
-    let url = download_url(platform, version);
-    let dest = binary_path(platform);
+// ── Extension implementation ──────────────────────────────────────────────────
 
-    eprintln!(
-        "[Vulnera] Downloading vulnera-adapter {} ({}) from {}",
-        version, platform.target_triple, url
-    );
+impl VulneraExtension {
+    /// Resolve the adapter binary to launch, installing it if necessary.
+    fn adapter_binary(
+        &mut self,
+        host: &Host,
+        ctx: &LaunchContext,
+        language_server_id: Option<&zed::LanguageServerId>,
+    ) -> Result<String> {
+        // ── 1. Allow hard override for development / CI ───────────────────────
+        if let Some((p, source)) = ctx.binary_override() {
+            if state::approve_binary(host, &p) {
+                log!(
+                    "Launching user-supplied adapter {} (from {}) for the first time",
+                    p,
+                    source
+                );
+            }
+            return Ok(p);
+        }
+
+        // ── 2–4. Resolve platform and version, ensure installed ───────────────
+        self.managed_binary(host, ctx, &CORE_ADAPTER, language_server_id)
+    }
 
-    zed::download_file(&url, &dest, DownloadedFileType::Uncompressed)
-        .map_err(|e| format!("Vulnera: download failed for {}: {}", url, e))?;
+    /// Resolve `binary` for this platform and worktree, installing it if
+    /// necessary and reporting progress as `language_server_id`'s status.
+    fn managed_binary(
+        &mut self,
+        host: &Host,
+        ctx: &LaunchContext,
+        binary: &AdapterSpec,
+        language_server_id: Option<&zed::LanguageServerId>,
+    ) -> Result<String> {
+        let binary = &ctx.on_channel(binary);
+        let platform = self.platform(host)?;
+        let pin = ctx.version_pin(binary);
+        if let Some((pin, _)) = &pin {
+            adapters::clear_bad_version(host, binary, pin);
+        }
+        let pinned = pin.is_some();
+        let mut version = match ctx.local_version(host, binary) {
+            Some(version) => version,
+            None => {
+                let version = self.session_version(host, ctx, binary);
+                match adapters::check_compatible(host, binary, &version) {
+                    Ok(()) => version,
+                    Err(refused) => {
+                        let fallback = adapters::newest_compatible(host, binary, &version)
+                            .ok_or_else(|| refused.to_string())?;
+                        log!("{}; installing {} instead", refused, fallback);
+                        fallback
+                    }
+                }
+            }
+        };
+        // An update found by the last check is installed before the server
+        // starts; if that fails, the installed version runs instead.
+        if !pinned
+            && ctx.installs_updates()
+            && let Some(update) = adapters::cached_update(host, binary, &version)
+        {
+            let installed = adapters::check_compatible(host, binary, &update)
+                .map_err(String::from)
+                .and_then(|()| {
+                    self.install_adapter(
+                        host,
+                        binary,
+                        &platform,
+                        update.clone(),
+                        language_server_id,
+                    )
+                });
+            match installed {
+                Ok(_) => version = update,
+                Err(e) => warn!(
+                    "Not updating {} to {}: {}; launching {}",
+                    binary.name, update, e, version
+                ),
+            }
+        }
+        if !pinned
+            && adapters::is_bad_version(host, binary, &version)
+            && let Some(fallback) = adapters::fallback_version(host, binary, &version)
+        {
+            debug!(
+                "{} {} is flagged as crashing; launching {}",
+                binary.name, version, fallback
+            );
+            version = fallback;
+        }
+        debug!(
+            "Resolved {} {} for {} (asset {})",
+            binary.name,
+            version,
+            platform.target_triple,
+            platform.asset_name(binary)
+        );
 
-    if !platform.is_windows {
-        zed::make_file_executable(&dest)
-            .map_err(|e| format!("Vulnera: chmod +x failed for {}: {}", dest, e))?;
+        let path =
+            self.install_adapter(host, binary, &platform, version.clone(), language_server_id)?;
+        let now = host.clock.now_secs();
+        state::record_launch(host, &binary.install_dir(), &version, now);
+        let server = language_server_id.map_or(SERVER_ID, |id| id.as_ref());
+        let storm = state::launched_recently(
+            host,
+            &binary.install_dir(),
+            &version,
+            now,
+            crash_loop::FRESH_INSTALL_SECS,
+        ) && self
+            .launch_history
+            .record(server, &version, &ctx.worktree_root, now);
+        if storm
+            && !pinned
+            && let Some(fallback) = adapters::fallback_version(host, binary, &version)
+        {
+            warn!(
+                "{} {} restarted {} times within {} s; flagging it as a bad release and \
+                 going back to {}. Pin a version to override.",
+                binary.name,
+                version,
+                crash_loop::CRASH_LOOP_LAUNCHES,
+                crash_loop::CRASH_LOOP_WINDOW_SECS,
+                fallback
+            );
+            adapters::mark_bad_version(host, binary, &version);
+            let path = self.install_adapter(
+                host,
+                binary,
+                &platform,
+                fallback.clone(),
+                language_server_id,
+            )?;
+            self.running_versions
+                .insert((binary.name, ctx.worktree_root.clone()), fallback);
+            return Ok(path);
+        }
+        self.running_versions
+            .insert((binary.name, ctx.worktree_root.clone()), version);
+        Ok(path)
     }
 
-    write_installed_version(version);
+    /// Check for a newer `binary` for `ctx`'s worktree once its server runs:
+    /// resolve the version to run with GitHub (within the version cache's
+    /// lifetime, at most once per session), and fetch the release notes of
+    /// an update it finds. Zed calls the workspace configuration hook after
+    /// the server started, so this is where the lookups skipped by
+    /// [`Self::managed_binary`] happen. Nothing is downloaded here: the
+    /// version cache now names the release, and the next launch installs it.
+    fn refresh_adapter(&mut self, host: &Host, ctx: &LaunchContext, binary: &AdapterSpec) {
+        let launched = (binary.name, ctx.worktree_root.clone());
+        if !self.running_versions.contains_key(&launched)
+            || (binary.is_core() && ctx.binary_override().is_some())
+        {
+            return;
+        }
+        let running = self.running_versions[&launched].clone();
+        let binary = &ctx.on_channel(binary);
+        self.session_version(host, ctx, binary);
+        // Only a newer release counts: the floor or a stale cache after a
+        // failed lookup is no update.
+        if ctx.version_pin(binary).is_none()
+            && ctx.installs_updates()
+            && let Some(update) = adapters::cached_update(host, binary, &running)
+        {
+            match adapters::check_compatible(host, binary, &update) {
+                Ok(()) => {
+                    if self.release_notes.insert((binary.name, update.clone())) {
+                        adapters::fetch_release_notes(host, binary, &update);
+                    }
+                    log!(
+                        "{} {} is available (running {}); it is installed when the server next starts",
+                        binary.name,
+                        update,
+                        running
+                    )
+                }
+                // Also shown by /vulnera-doctor.
+                Err(refused) => log!("{}; keeping {}", refused, running),
+            }
+        }
+    }
 
-    eprintln!(
-        "[Vulnera] vulnera-adapter {} installed at {}",
-        version, dest
-    );
+    /// The `adapterUpdate` option for the servers running `binary` in
+    /// `ctx`'s worktree, when a newer version was installed after they
+    /// started (by `/vulnera-update` or a launch in another project). Zed
+    /// cannot restart a server on an extension's behalf, so the adapter does
+    /// it: per `update_restart` it exits once its in-flight scans are
+    /// published, or after the user agrees, and Zed launches the new version.
+    ///
+    /// Zed has no way for an extension to push configuration, so the option
+    /// arrives the next time Zed asks for it: after a settings change, or
+    /// when the server itself requests `workspace/configuration`.
+    fn adapter_update(
+        &self,
+        host: &Host,
+        ctx: &LaunchContext,
+        binary: &AdapterSpec,
+    ) -> Option<Value> {
+        let running = self
+            .running_versions
+            .get(&(binary.name, ctx.worktree_root.clone()))?;
+        if (binary.is_core() && ctx.binary_override().is_some())
+            || ctx.version_pin(binary).is_some()
+        {
+            return None;
+        }
+        let version = adapters::pending_swap(host, &ctx.on_channel(binary), running)?;
+        let restart = ctx.settings.update_restart;
+        if restart == UpdateRestart::Never {
+            debug!(
+                "{} {} is installed; {} keeps running {} until restarted",
+                binary.name, version, ctx.worktree_root, running
+            );
+            return None;
+        }
+        Some(json!({
+            "version": version,
+            "restart": restart.as_str(),
+        }))
+    }
 
-    Ok(())
-}
+    /// A secondary server that is enabled: the managed adapter in the
+    /// server's mode, or the binary configured under `lsp.<id>.binary`. One
+    /// without its marker files runs [`idle::command`] so Zed does not report
+    /// it as a failed start.
+    fn mode_server_command(
+        &mut self,
+        host: &Host,
+        ctx: &LaunchContext,
+        server: &mode_servers::ModeServer,
+        mut mode_settings: mode_servers::ModeSettings,
+        language_server_id: Option<&zed::LanguageServerId>,
+        worktree: &dyn Checkout,
+    ) -> Result<zed::Command> {
+        // Checked before anything is resolved or downloaded.
+        if !mode_settings.always_start
+            && !server.marker_files.is_empty()
+            && !server
+                .marker_files
+                .iter()
+                .any(|file| worktree.read_text_file(file).is_ok())
+        {
+            return Ok(idle::command(
+                &format!(
+                    "No {} in {}; not starting {} (set lsp.{}.settings.always_start to override)",
+                    server.marker_files.join(" / "),
+                    ctx.worktree_root,
+                    server.id,
+                    server.id
+                ),
+                host.is_windows(),
+            ));
+        }
+
+        if !ctx.trusted {
+            mode_settings.binary = None;
+        }
+        let mut command = match mode_settings.binary_path() {
+            Some(path) => ctx.command_without_credentials(
+                settings::resolve_in_worktree(&ctx.worktree_root, path),
+                worktree,
+            ),
+            None if server.adapter.is_core() => {
+                let binary = self.adapter_binary(host, ctx, language_server_id)?;
+                ctx.command(binary, worktree)
+            }
+            None => {
+                let binary = self.managed_binary(host, ctx, server.adapter, language_server_id)?;
+                ctx.command(binary, worktree)
+            }
+        };
+        command.args = mode_settings.arguments(server);
+        if ctx.settings.debug {
+            command.args.push("--verbose".to_string());
+        }
 
-// ── Binary resolution ─────────────────────────────────────────────────────────
+        debug_command(&command);
+        Ok(command)
+    }
 
-fn ensure_binary(platform: &PlatformInfo, version: &str) -> Result<String> {
-    let dest = binary_path(platform);
-    let installed = read_installed_version();
-    let binary_exists = PathBuf::from(&dest).exists();
+    /// The host platform, resolved once per session.
+    fn platform(&mut self, host: &Host) -> Result<PlatformInfo> {
+        if let Some(platform) = self.platform {
+            return Ok(platform);
+        }
+        let (os, arch) = host.platform.current();
+        let platform = resolve_platform(os, arch)?;
+        self.platform = Some(platform);
+        Ok(platform)
+    }
 
-    let needs_download = !binary_exists || installed.as_deref() != Some(version);
+    /// [`LaunchContext::adapter_version`], memoized for the session.
+    fn session_version(
+        &mut self,
+        host: &Host,
+        ctx: &LaunchContext,
+        binary: &AdapterSpec,
+    ) -> String {
+        let key = (binary.name, ctx.version_key(binary));
+        let now = host.clock.now_secs();
+        if let Some(resolved) = self.resolved_versions.get(&key)
+            && resolved.is_fresh(now)
+        {
+            return resolved.version.clone();
+        }
 
-    if needs_download {
-        download_binary(platform, version)?;
-    } else {
-        eprintln!(
-            "[Vulnera] vulnera-adapter {} already installed ({})",
-            version, dest
+        let version = ctx.adapter_version(host, binary);
+        self.resolved_versions.insert(
+            key,
+            ResolvedVersion {
+                version: version.clone(),
+                at: now,
+                generation: adapters::generation(),
+                ttl: binary.channel.cache_ttl_secs(),
+            },
         );
+        version
     }
 
-    Ok(dest)
-}
+    /// The core server's command, and whether this was a warm start: the
+    /// worktree already launched this session with the same configuration.
+    ///
+    /// A warm start skips the manifest probe, the dependency snapshot and the
+    /// launcher rewrite, and reuses the memoized platform. It still reads the
+    /// settings, the worktree's `.env`, overrides and registry files, and the
+    /// version cache and `state.json`; it waits on the network only to install
+    /// an update found since the server last started.
+    fn core_server_command(
+        &mut self,
+        host: &Host,
+        ctx: &LaunchContext,
+        language_server_id: Option<&zed::LanguageServerId>,
+        worktree: &dyn Checkout,
+    ) -> Result<(zed::Command, bool)> {
+        let settings = &ctx.settings;
+
+        let fingerprint = launch_fingerprint(&ctx.shell_env, settings, &ctx.overrides);
+        let previous = self.launch_fingerprints.get(&worktree.id()).copied();
+        let warm = previous == Some(fingerprint);
+        if previous.is_some() && !warm {
+            log!("Launch configuration changed; rebuilding the adapter command");
+        }
 
-// ── Extension implementation ──────────────────────────────────────────────────
+        if !warm {
+            // Like the secondary servers, idle rather than report a failed start.
+            if !settings.always_start && manifests::find_manifest(worktree).is_none() {
+                let reason = format!(
+                    "No supported manifest in {}; not starting the adapter \
+                     (set lsp.vulnera.settings.always_start to override)",
+                    ctx.worktree_root
+                );
+                return Ok((idle::command(&reason, host.is_windows()), false));
+            }
+            self.launch_fingerprints.insert(worktree.id(), fingerprint);
+        }
+
+        // From the user's shell only: a repository must not redirect the LSP stream.
+        let user_env = worktree.shell_env();
+        if let Some(connect) = env_value(&user_env, "VULNERA_ADAPTER_CONNECT") {
+            let endpoint = transport::Endpoint::parse(connect)?;
+            let (bridge, args) = transport::bridge_command(&endpoint, worktree)?;
+            log!(
+                "Attaching to the running adapter at {} via {}",
+                endpoint,
+                bridge
+            );
+            let command = zed::Command {
+                command: bridge,
+                args,
+                env: Vec::new(),
+            };
+            debug_command(&command);
+            return Ok((command, warm));
+        }
+
+        let binary = self.adapter_binary(host, ctx, language_server_id)?;
+        self.core_binaries.insert(worktree.id(), binary.clone());
+        if self.launcher_target.as_ref() != Some(&binary) {
+            tasks::update_launcher(host, &binary);
+            self.launcher_target = Some(binary.clone());
+        }
+
+        // ── 5. Build command with forwarded environment ───────────────────────
+        let mut command = ctx.command(binary, worktree);
+        // The adapter writes the SBOM served by the context server itself.
+        if let Some(dir) = sbom::sbom_dir() {
+            command.env.push(("VULNERA_SBOM_DIR".to_string(), dir));
+        }
+        command.args = settings.binary_arguments().to_vec();
+        command.args.extend(adapter_args(&ctx.shell_env, settings)?);
+        if settings.daemon {
+            let endpoint = transport::daemon_endpoint(host, &command)?;
+            debug!(
+                "Connecting {} to the shared adapter at {}",
+                ctx.worktree_root, endpoint
+            );
+            command.args.extend(transport::daemon_args(&endpoint));
+        } else if let Some(endpoint) =
+            transport::listen_endpoint(host, &settings.transport, worktree.id())?
+        {
+            log!("Adapter for {} listens on {}", ctx.worktree_root, endpoint);
+            command.args.extend(transport::listen_args(&endpoint));
+        }
+        if settings.debug {
+            command.args.push("--verbose".to_string());
+        }
+
+        debug_command(&command);
+        Ok((command, warm))
+    }
+
+    /// The installed core adapter, for a context server opened before any of
+    /// its project's language servers started. Context servers are per
+    /// project, so no worktree's settings, pins or channel apply; nothing is
+    /// looked up or downloaded.
+    fn installed_core_adapter(&mut self, host: &Host) -> Result<String> {
+        let platform = self.platform(host)?;
+        let version = adapters::local_version(host, &CORE_ADAPTER).ok_or_else(|| {
+            VulneraError::NotInstalled {
+                binary: CORE_ADAPTER.name.to_string(),
+                reason: "no version has been downloaded yet".to_string(),
+            }
+        })?;
+        adapters::health(host, &CORE_ADAPTER, &platform, &version)
+    }
+
+    /// Path to `binary` at `version`, downloading it on first use. Install
+    /// progress is shown as `language_server_id`'s status, if any.
+    fn install_adapter(
+        &mut self,
+        host: &Host,
+        binary: &AdapterSpec,
+        platform: &PlatformInfo,
+        version: String,
+        language_server_id: Option<&zed::LanguageServerId>,
+    ) -> Result<String> {
+        // Binaries are cached per version, so worktrees with different pins share them.
+        let key = (binary.name, version);
+        if let Some(p) = self.installed_binaries.get(&key)
+            && host.fs.exists(p)
+        {
+            return Ok(p.clone());
+        }
+
+        let new_path = adapters::ensure_binary(host, binary, platform, &key.1, |state| {
+            if let Some(id) = language_server_id {
+                zed::set_language_server_installation_status(id, &state.status());
+            }
+        })?;
+        self.installed_binaries.insert(key, new_path.clone());
+        Ok(new_path)
+    }
+}
 
 impl zed::Extension for VulneraExtension {
     fn new() -> Self {
         VulneraExtension {
-            cached_binary: None,
+            installed_binaries: HashMap::new(),
+            launch_fingerprints: HashMap::new(),
+            platform: None,
+            resolved_versions: HashMap::new(),
+            launcher_target: None,
+            launch_history: crash_loop::LaunchHistory::default(),
+            running_versions: HashMap::new(),
+            core_binaries: HashMap::new(),
+            release_notes: HashSet::new(),
         }
     }
 
@@ -407,72 +1171,454 @@ impl zed::Extension for VulneraExtension {
         language_server_id: &zed::LanguageServerId,
         worktree: &zed::Worktree,
     ) -> Result<zed::Command> {
-        if language_server_id.as_ref() != SERVER_ID {
-            return Err(format!(
-                "Vulnera: unknown language server id '{}'",
-                language_server_id.as_ref()
-            ));
+        let id = language_server_id.as_ref();
+        let server = Server::find(id).ok_or_else(|| VulneraError::UnknownServer(id.to_string()))?;
+        if let Server::Mode(server) = server {
+            let mode_settings = mode_servers::ModeSettings::for_worktree(server, worktree)?;
+            if !mode_settings.enabled {
+                return Ok(idle::command(
+                    &format!(
+                        "{} is disabled (set lsp.{}.settings.enabled to start it)",
+                        server.id, server.id
+                    ),
+                    is_windows(),
+                ));
+            }
+            let ctx = LaunchContext::for_worktree(worktree)?;
+            return self.mode_server_command(
+                &ctx.host(&Host::REAL),
+                &ctx,
+                server,
+                mode_settings,
+                Some(language_server_id),
+                worktree,
+            );
         }
 
-        let shell_env: Vec<(String, String)> = worktree.shell_env();
+        let started = Instant::now();
+        let ctx = LaunchContext::for_worktree(worktree)?;
+        let (command, warm) = self.core_server_command(
+            &ctx.host(&Host::REAL),
+            &ctx,
+            Some(language_server_id),
+            worktree,
+        )?;
+        if !warm {
+            slash_commands::remember_dependencies(worktree);
+        }
+        log!(
+            "Built the adapter command ({} start) in {} ms",
+            if warm { "warm" } else { "cold" },
+            started.elapsed().as_millis()
+        );
+        Ok(command)
+    }
 
-        // ── 1. Allow hard override for development / CI ───────────────────────
-        if let Some((_, override_path)) =
-            shell_env.iter().find(|(k, _)| k == "VULNERA_ADAPTER_PATH")
-        {
-            let p = override_path.trim();
-            if !p.is_empty() {
-                eprintln!("[Vulnera] Using VULNERA_ADAPTER_PATH override: {}", p);
-                return Ok(build_command(p.to_string(), &shell_env));
+    fn language_server_initialization_options(
+        &mut self,
+        language_server_id: &zed::LanguageServerId,
+        worktree: &zed::Worktree,
+    ) -> Result<Option<Value>> {
+        match Server::find(language_server_id.as_ref()) {
+            Some(Server::Core) => {
+                let settings = VulneraSettings::for_worktree(worktree)?;
+                server_options(&settings, worktree).map(Some)
             }
+            Some(Server::Mode(server)) => Ok(Some(
+                mode_servers::ModeSettings::for_worktree(server, worktree)?
+                    .initialization_options(server),
+            )),
+            None => Ok(None),
         }
+    }
 
-        // ── 2. Resolve platform ───────────────────────────────────────────────
-        let (os, arch) = zed::current_platform();
-        let platform = resolve_platform(os, arch)?;
-
-        // ── 3. Resolve target version (dynamic) ──────────────────────────────
-        let version = resolve_adapter_version(&shell_env);
-
-        // ── 4. Ensure binary is installed ─────────────────────────────────────
-        let binary = match &self.cached_binary {
-            Some(p) if PathBuf::from(p).exists() => {
-                // Re-validate version in case the extension was updated in-place.
-                if read_installed_version().as_deref() == Some(version.as_str()) {
-                    p.clone()
-                } else {
-                    let new_path = ensure_binary(&platform, &version)?;
-                    self.cached_binary = Some(new_path.clone());
-                    new_path
+    /// Zed calls this again whenever `lsp.vulnera` changes, so scan options
+    /// reach a running server via `workspace/didChangeConfiguration`. Launch
+    /// settings cannot be applied that way; changes to them are logged. Being
+    /// off the launch path, this is also where the adapter is updated.
+    fn language_server_workspace_configuration(
+        &mut self,
+        language_server_id: &zed::LanguageServerId,
+        worktree: &zed::Worktree,
+    ) -> Result<Option<Value>> {
+        match Server::find(language_server_id.as_ref()) {
+            Some(Server::Core) => {}
+            None => return Ok(None),
+            Some(Server::Mode(server)) => {
+                let mode_settings = mode_servers::ModeSettings::for_worktree(server, worktree)?;
+                let mut options = mode_settings.initialization_options(server);
+                if mode_settings.enabled
+                    && mode_settings.binary_path().is_none()
+                    && let Ok(ctx) = LaunchContext::for_worktree(worktree)
+                {
+                    self.refresh_adapter(&ctx.host(&Host::REAL), &ctx, server.adapter);
+                    if let Some(update) =
+                        self.adapter_update(&ctx.host(&Host::REAL), &ctx, server.adapter)
+                    {
+                        options["vulnera"]["adapterUpdate"] = update;
+                    }
                 }
+                return Ok(Some(options));
             }
-            _ => {
-                let new_path = ensure_binary(&platform, &version)?;
-                self.cached_binary = Some(new_path.clone());
-                new_path
+        }
+
+        let settings = VulneraSettings::for_worktree(worktree)?;
+        let mut update = None;
+        if let Some(launched) = self.launch_fingerprints.get(&worktree.id()) {
+            let shell_env = dotenv::merge_worktree_dotenv(
+                worktree.shell_env(),
+                worktree,
+                settings.explicitly_trusts(&worktree.root_path()),
+            );
+            let overrides = WorktreeOverrides::for_worktree(worktree)?;
+            if launch_fingerprint(&shell_env, &settings, &overrides) != *launched {
+                log!(
+                    "Launch settings changed since the server started; \
+                     run `editor: restart language server` to apply them"
+                );
+            }
+            if let Ok(ctx) = LaunchContext::for_worktree(worktree) {
+                self.refresh_adapter(&ctx.host(&Host::REAL), &ctx, &CORE_ADAPTER);
+                update = self.adapter_update(&ctx.host(&Host::REAL), &ctx, &CORE_ADAPTER);
             }
+        }
+
+        let mut options = server_options(&settings, worktree)?;
+        if let Some(update) = update {
+            options["vulnera"]["adapterUpdate"] = update;
+        }
+        Ok(Some(options))
+    }
+
+    fn label_for_completion(
+        &self,
+        language_server_id: &zed::LanguageServerId,
+        completion: zed::lsp::Completion,
+    ) -> Option<zed::CodeLabel> {
+        // The secondary servers' adapters share the core label format.
+        Server::find(language_server_id.as_ref())?;
+        labels::completion_label(&completion)
+    }
+
+    fn label_for_symbol(
+        &self,
+        language_server_id: &zed::LanguageServerId,
+        symbol: zed::lsp::Symbol,
+    ) -> Option<zed::CodeLabel> {
+        Server::find(language_server_id.as_ref())?;
+        labels::symbol_label(&symbol)
+    }
+
+    fn complete_slash_command_argument(
+        &self,
+        command: zed::SlashCommand,
+        args: Vec<String>,
+    ) -> Result<Vec<zed::SlashCommandArgumentCompletion>> {
+        slash_commands::complete(&command, &args)
+    }
+
+    fn run_slash_command(
+        &self,
+        command: zed::SlashCommand,
+        args: Vec<String>,
+        worktree: Option<&zed::Worktree>,
+    ) -> Result<zed::SlashCommandOutput> {
+        slash_commands::run(&command, &args, worktree)
+    }
+
+    fn context_server_command(
+        &mut self,
+        context_server_id: &zed::ContextServerId,
+        project: &zed::Project,
+    ) -> Result<zed::Command> {
+        let settings =
+            zed::settings::ContextServerSettings::for_project(context_server_id.as_ref(), project)?;
+
+        if let Some(custom) = settings.command
+            && let Some(path) = custom.path
+        {
+            return Ok(zed::Command {
+                command: path,
+                args: custom.arguments.unwrap_or_default(),
+                env: custom.env.unwrap_or_default().into_iter().collect(),
+            });
+        }
+
+        let options = context_server::ContextServerOptions::parse(settings.settings)?;
+        let binary = match project
+            .worktree_ids()
+            .iter()
+            .find_map(|id| self.core_binaries.get(id))
+        {
+            Some(binary) => binary.clone(),
+            None => self.installed_core_adapter(&Host::REAL)?,
         };
+        Ok(context_server::command(binary, &options, sbom::sbom_dir()))
+    }
 
-        // ── 5. Build command with forwarded environment ───────────────────────
-        Ok(build_command(binary, &shell_env))
+    fn suggest_docs_packages(&self, provider: String) -> Result<Vec<String>> {
+        if provider != advisory_docs::PROVIDER {
+            return Ok(Vec::new());
+        }
+        Ok(advisory_docs::suggest_packages())
+    }
+
+    fn index_docs(
+        &self,
+        provider: String,
+        package: String,
+        database: &zed::KeyValueStore,
+    ) -> Result<()> {
+        if provider != advisory_docs::PROVIDER {
+            return Err(VulneraError::Internal {
+                reason: format!("unknown docs provider '{}'", provider),
+            }
+            .into());
+        }
+        advisory_docs::index(&package, database)
+    }
+
+    fn context_server_configuration(
+        &mut self,
+        _context_server_id: &zed::ContextServerId,
+        _project: &zed::Project,
+    ) -> Result<Option<zed::ContextServerConfiguration>> {
+        Ok(Some(context_server::configuration()))
+    }
+}
+
+fn debug_command(command: &zed::Command) {
+    debug!(
+        "Launching {} {:?} with env {}",
+        command.command,
+        command.args,
+        command
+            .env
+            .iter()
+            .map(|(k, v)| format!("{}={}", k, v))
+            .collect::<Vec<_>>()
+            .join(" ")
+    );
+}
+
+/// The settings payload sent both as initialization options and as workspace
+/// configuration: scan options plus the worktree's ignore patterns and
+/// detected ecosystems.
+fn server_options(settings: &VulneraSettings, worktree: &zed::Worktree) -> Result<Value> {
+    let mut options = settings.initialization_options(&worktree.root_path())?;
+
+    if settings.detect_ecosystems.unwrap_or(true) {
+        for (ecosystem, enabled) in manifests::detect_ecosystems(worktree) {
+            if settings.scan.ecosystems.contains_key(&ecosystem) {
+                continue;
+            }
+            if !enabled {
+                log!(
+                    "Disabling {} scanning: no package manager on PATH",
+                    ecosystem
+                );
+            }
+            options["vulnera"]["ecosystems"][ecosystem] = Value::Bool(enabled);
+        }
+    }
+
+    options["vulnera"]["languages"] = manifests::language_options(&settings.languages);
+    options["vulnera"]["lockfiles"] = manifests::lockfile_routes();
+    if let Some(kubernetes) = settings.kubernetes()
+        && let Some(yaml) = options["vulnera"]["languages"]["yaml"]["manifests"].as_object_mut()
+    {
+        for path in kubernetes.paths() {
+            yaml.insert(path.to_string(), Value::from("kubernetes"));
+        }
     }
+
+    // Sent explicitly: adapters before work-done progress support ignore it,
+    // newer ones default it off.
+    options["vulnera"]["workDoneProgress"] = Value::Bool(settings.report_progress.unwrap_or(true));
+
+    let patterns = ignore::ignore_patterns(worktree, settings.use_gitignore.unwrap_or(true));
+    if !patterns.is_empty() {
+        options["vulnera"]["ignorePatterns"] = Value::from(patterns);
+    }
+
+    Ok(options)
 }
 
+/// Hash of everything that shapes the adapter command line and environment,
+/// used to notice settings changes that need a new process.
+fn launch_fingerprint(
+    shell_env: &[(String, String)],
+    settings: &VulneraSettings,
+    overrides: &WorktreeOverrides,
+) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    let mut vulnera_env: Vec<_> = shell_env
+        .iter()
+        .filter(|(k, _)| k.starts_with("VULNERA_"))
+        .collect();
+    vulnera_env.sort();
+    vulnera_env.hash(&mut hasher);
+
+    settings.binary_path().hash(&mut hasher);
+    settings.binary_arguments().hash(&mut hasher);
+    settings.adapter_args.hash(&mut hasher);
+    settings.offline_db_path().hash(&mut hasher);
+    settings.worker_threads.hash(&mut hasher);
+    settings.priority.hash(&mut hasher);
+    settings.max_memory_mb.hash(&mut hasher);
+    settings.debug.hash(&mut hasher);
+    settings.log_file().hash(&mut hasher);
+    settings.log_rotation.hash(&mut hasher);
+    settings.log_format.hash(&mut hasher);
+    settings.language.hash(&mut hasher);
+    settings.forward_env.hash(&mut hasher);
+    settings.inherit_env.hash(&mut hasher);
+    settings.registries.hash(&mut hasher);
+    settings.transport.hash(&mut hasher);
+    settings.daemon.hash(&mut hasher);
+
+    overrides.adapter_version.hash(&mut hasher);
+    overrides.binary_path.hash(&mut hasher);
+    overrides.offline.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Protocol additions this extension understands, announced to the adapter in
+/// `VULNERA_CLIENT_FEATURES`: the `vulnera` workspace configuration keys
+/// `adapterUpdate`, `workDoneProgress`, `ignorePatterns` and `lockfiles`.
+const CLIENT_FEATURES: &[&str] = &[
+    "adapterUpdate",
+    "workDoneProgress",
+    "ignorePatterns",
+    "lockfiles",
+];
+
 /// Build a `zed::Command` for the given binary path, forwarding relevant env
-/// vars from the worktree shell environment.
-fn build_command(binary: String, shell_env: &[(String, String)]) -> zed::Command {
-    const FORWARDED_KEYS: &[&str] = &["VULNERA_API_URL", "VULNERA_API_KEY", "VULNERA_LOG"];
+/// vars from the worktree shell environment (or all of it with `inherit_env`).
+///
+/// `PATH` and the common toolchain-manager variables are always forwarded so
+/// the adapter can run `npm`, `cargo metadata`, `mvn`, … even when Zed was
+/// launched outside a login shell.
+///
+/// In offline mode the API URL and key are withheld so the adapter never
+/// tries to reach the Vulnera API.
+///
+/// `VULNERA_CLIENT` names this extension and its version, and
+/// `VULNERA_CLIENT_FEATURES` lists the [`CLIENT_FEATURES`] it speaks, so the
+/// adapter can fall back for older extensions and its logs say who launched it.
+fn build_command(
+    binary: String,
+    shell_env: &[(String, String)],
+    settings: &VulneraSettings,
+    worktree_root: &str,
+    offline_db: Option<&str>,
+) -> zed::Command {
+    const FORWARDED_KEYS: &[&str] = &[
+        "VULNERA_API_URL",
+        "VULNERA_API_KEY",
+        "VULNERA_LOG",
+        "VULNERA_LOG_FORMAT",
+        "VULNERA_TELEMETRY",
+    ];
+    const ONLINE_ONLY_KEYS: &[&str] = &["VULNERA_API_URL", "VULNERA_API_KEY"];
+    // Matched case-insensitively: Windows spells it `Path`.
+    const TOOLCHAIN_KEYS: &[&str] = &[
+        "PATH",
+        "HOME",
+        "USERPROFILE",
+        "NVM_DIR",
+        "VOLTA_HOME",
+        "FNM_DIR",
+        "RUSTUP_HOME",
+        "CARGO_HOME",
+        "GOPATH",
+        "GOROOT",
+        "JAVA_HOME",
+        "MAVEN_HOME",
+        "GRADLE_USER_HOME",
+        "PYENV_ROOT",
+        "VIRTUAL_ENV",
+        "CONDA_PREFIX",
+    ];
 
     let mut env: Vec<(String, String)> = shell_env
         .iter()
-        .filter(|(k, v)| FORWARDED_KEYS.contains(&k.as_str()) && !v.trim().is_empty())
+        .filter(|(k, v)| {
+            settings.inherit_env
+                || ((FORWARDED_KEYS.contains(&k.as_str())
+                    || TOOLCHAIN_KEYS.iter().any(|t| t.eq_ignore_ascii_case(k))
+                    || is_user_forwarded(k, &settings.forward_env))
+                    && !v.trim().is_empty())
+        })
+        .filter(|(k, _)| offline_db.is_none() || !ONLINE_ONLY_KEYS.contains(&k.as_str()))
         .cloned()
         .collect();
 
+    if let Some(path) = offline_db {
+        env.push(("VULNERA_OFFLINE_DB".to_string(), path.to_string()));
+    }
+
+    if let Some(threads) = settings.worker_threads {
+        env.push(("VULNERA_WORKER_THREADS".to_string(), threads.to_string()));
+    }
+
+    if let Some(priority) = settings.priority {
+        env.push((
+            "VULNERA_PRIORITY".to_string(),
+            priority.as_str().to_string(),
+        ));
+    }
+
+    if let Some(megabytes) = settings.max_memory_mb {
+        env.push(("VULNERA_MAX_MEMORY_MB".to_string(), megabytes.to_string()));
+    }
+
+    if let Some(log_file) = settings.log_file() {
+        env.push((
+            "VULNERA_LOG_FILE".to_string(),
+            settings::resolve_in_worktree(worktree_root, log_file),
+        ));
+        if let Some(rotation) = settings.log_rotation {
+            env.push((
+                "VULNERA_LOG_ROTATION".to_string(),
+                rotation.as_str().to_string(),
+            ));
+        }
+    }
+
+    if let Some(format) = settings.log_format
+        && !env.iter().any(|(k, _)| k == "VULNERA_LOG_FORMAT")
+    {
+        env.push((
+            "VULNERA_LOG_FORMAT".to_string(),
+            format.as_str().to_string(),
+        ));
+    }
+
+    if let Some(locale) = resolve_locale(shell_env, settings) {
+        env.push(("VULNERA_LOCALE".to_string(), locale));
+    }
+
+    if !telemetry_enabled(shell_env, settings) {
+        env.retain(|(k, _)| k != "VULNERA_TELEMETRY");
+        env.push(("VULNERA_TELEMETRY".to_string(), "off".to_string()));
+    }
+
     if !env.iter().any(|(k, _)| k == "VULNERA_LOG") {
-        env.push(("VULNERA_LOG".to_string(), "info".to_string()));
+        let filter = if settings.debug { "debug" } else { "info" };
+        env.push(("VULNERA_LOG".to_string(), filter.to_string()));
     }
 
+    env.retain(|(k, _)| k != "VULNERA_CLIENT" && k != "VULNERA_CLIENT_FEATURES");
+    env.push((
+        "VULNERA_CLIENT".to_string(),
+        format!("zed-extension/{}", adapters::EXTENSION_VERSION),
+    ));
+    env.push((
+        "VULNERA_CLIENT_FEATURES".to_string(),
+        CLIENT_FEATURES.join(","),
+    ));
+
     zed::Command {
         command: binary,
         args: vec![],
@@ -480,50 +1626,457 @@ fn build_command(binary: String, shell_env: &[(String, String)]) -> zed::Command
     }
 }
 
+/// The locale for advisory text: the `language` setting, else the POSIX
+/// locale (`LC_ALL`, `LC_MESSAGES`, `LANG`) converted to a BCP 47 tag
+/// (`pt_BR.UTF-8` → `pt-BR`). `C`/`POSIX` mean "no preference".
+fn resolve_locale(shell_env: &[(String, String)], settings: &VulneraSettings) -> Option<String> {
+    if let Some(language) = settings.language.as_deref().map(str::trim)
+        && !language.is_empty()
+    {
+        return Some(language.to_string());
+    }
+
+    let posix = ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .find_map(|key| env_value(shell_env, key))?;
+    let tag = posix
+        .split(['.', '@'])
+        .next()
+        .unwrap_or_default()
+        .replace('_', "-");
+    (!tag.is_empty() && tag != "C" && tag != "POSIX").then_some(tag)
+}
+
+/// Whether usage data may be recorded, by the adapter or the extension. Off
+/// when the `telemetry` setting is `false`, `VULNERA_TELEMETRY` is `off`/`0`/
+/// `false`, or the cross-tool `DO_NOT_TRACK` convention is set.
+fn telemetry_enabled(shell_env: &[(String, String)], settings: &VulneraSettings) -> bool {
+    let is_off = |v: &str| matches!(v.to_ascii_lowercase().as_str(), "off" | "0" | "false");
+    settings.telemetry != Some(false)
+        && !env_value(shell_env, "VULNERA_TELEMETRY").is_some_and(is_off)
+        && env_value(shell_env, "DO_NOT_TRACK").is_none_or(is_off)
+}
+
+/// Extra adapter arguments, appended after `binary.arguments`: the
+/// `adapter_args` setting followed by the shell-style `VULNERA_ADAPTER_ARGS`
+/// env var.
+fn adapter_args(shell_env: &[(String, String)], settings: &VulneraSettings) -> Result<Vec<String>> {
+    let mut args = settings.adapter_args.clone();
+    if let Some(raw) = env_value(shell_env, "VULNERA_ADAPTER_ARGS") {
+        args.extend(
+            split_shell_words(raw).map_err(|e| VulneraError::config("VULNERA_ADAPTER_ARGS", e))?,
+        );
+    }
+    Ok(args)
+}
+
+/// Split a command line the way a POSIX shell would for plain words:
+/// whitespace separates arguments, single quotes are literal, double quotes
+/// allow `\"` and `\\` escapes, and a backslash outside quotes escapes the next
+/// character.
+fn split_shell_words(input: &str) -> Result<Vec<String>> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let mut in_word = false;
+    let mut chars = input.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {
+                if in_word {
+                    words.push(std::mem::take(&mut current));
+                    in_word = false;
+                }
+            }
+            '\'' => {
+                in_word = true;
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(c) => current.push(c),
+                        None => return Err("unterminated single quote".to_string()),
+                    }
+                }
+            }
+            '"' => {
+                in_word = true;
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(c @ ('"' | '\\')) => current.push(c),
+                            Some(c) => {
+                                current.push('\\');
+                                current.push(c);
+                            }
+                            None => return Err("unterminated double quote".to_string()),
+                        },
+                        Some(c) => current.push(c),
+                        None => return Err("unterminated double quote".to_string()),
+                    }
+                }
+            }
+            '\\' => {
+                in_word = true;
+                if let Some(c) = chars.next() {
+                    current.push(c);
+                }
+            }
+            c => {
+                in_word = true;
+                current.push(c);
+            }
+        }
+    }
+
+    if in_word {
+        words.push(current);
+    }
+    Ok(words)
+}
+
+/// Whether `key` matches an entry of the `forward_env` setting.
+fn is_user_forwarded(key: &str, patterns: &[String]) -> bool {
+    patterns
+        .iter()
+        .any(|pattern| match pattern.strip_suffix('*') {
+            Some(prefix) => key.starts_with(prefix),
+            None => key == pattern,
+        })
+}
+
 zed::register_extension!(VulneraExtension);
 
 #[cfg(test)]
 mod tests {
-    use super::parse_latest_stable_version;
+    use std::num::NonZeroU32;
+
+    use super::{
+        ResolvedVersion, Server, VulneraSettings, WorktreeOverrides, adapters, build_command,
+        exec_by_name, is_user_forwarded, launch_fingerprint, resolve_locale,
+        settings::{LogFormat, LogRotation, ProcessPriority},
+        split_shell_words, telemetry_enabled,
+    };
+
+    fn env(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn offline_mode_withholds_api_credentials() {
+        let shell_env = env(&[
+            ("VULNERA_API_URL", "https://api.vulnera.studio"),
+            ("VULNERA_API_KEY", "secret"),
+        ]);
+        let command = build_command(
+            "adapter".into(),
+            &shell_env,
+            &VulneraSettings::default(),
+            "/work/repo",
+            Some("/data/osv"),
+        );
+
+        assert!(
+            !command
+                .env
+                .iter()
+                .any(|(k, _)| k.starts_with("VULNERA_API_"))
+        );
+        assert!(
+            command
+                .env
+                .contains(&("VULNERA_OFFLINE_DB".to_string(), "/data/osv".to_string()))
+        );
+    }
+
+    #[test]
+    fn forward_env_supports_prefix_patterns() {
+        let patterns = vec!["NODE_EXTRA_CA_CERTS".to_string(), "PIP_*".to_string()];
+        assert!(is_user_forwarded("NODE_EXTRA_CA_CERTS", &patterns));
+        assert!(is_user_forwarded("PIP_INDEX_URL", &patterns));
+        assert!(!is_user_forwarded("NODE_OPTIONS", &patterns));
+    }
+
+    #[test]
+    fn inherit_env_passes_everything_through() {
+        let shell_env = env(&[("SSH_AUTH_SOCK", "/tmp/agent.sock"), ("HOME", "/home/me")]);
+        let settings = VulneraSettings {
+            inherit_env: true,
+            ..Default::default()
+        };
+        let command = build_command("adapter".into(), &shell_env, &settings, "/work/repo", None);
+
+        for pair in &shell_env {
+            assert!(command.env.contains(pair));
+        }
+    }
+
+    #[test]
+    fn splits_shell_words() {
+        assert_eq!(
+            split_shell_words(r#"--trace-lsp --mode "deep scan" --label 'a b' x\ y"#).unwrap(),
+            vec![
+                "--trace-lsp",
+                "--mode",
+                "deep scan",
+                "--label",
+                "a b",
+                "x y"
+            ]
+        );
+        assert_eq!(split_shell_words("  ").unwrap(), Vec::<String>::new());
+        assert!(split_shell_words("--mode \"open").is_err());
+    }
+
+    #[test]
+    fn forwards_path_and_toolchain_env() {
+        let shell_env = env(&[
+            ("Path", "C:\\Program Files\\nodejs"),
+            ("CARGO_HOME", "/home/me/.cargo"),
+            ("EDITOR", "vim"),
+        ]);
+        let command = build_command(
+            "adapter".into(),
+            &shell_env,
+            &VulneraSettings::default(),
+            "/work/repo",
+            None,
+        );
+
+        assert!(command.env.contains(&shell_env[0]));
+        assert!(command.env.contains(&shell_env[1]));
+        assert!(!command.env.iter().any(|(k, _)| k == "EDITOR"));
+    }
+
+    #[test]
+    fn launch_fingerprint_tracks_launch_settings_only() {
+        let shell_env = env(&[("VULNERA_API_KEY", "key-1"), ("EDITOR", "vim")]);
+        let settings = VulneraSettings::default();
+        let overrides = WorktreeOverrides::default();
+        let base = launch_fingerprint(&shell_env, &settings, &overrides);
+
+        let unrelated = env(&[("VULNERA_API_KEY", "key-1"), ("EDITOR", "nano")]);
+        assert_eq!(launch_fingerprint(&unrelated, &settings, &overrides), base);
+
+        let rotated = env(&[("VULNERA_API_KEY", "key-2"), ("EDITOR", "vim")]);
+        assert_ne!(launch_fingerprint(&rotated, &settings, &overrides), base);
+
+        let pinned = WorktreeOverrides {
+            adapter_version: Some("0.2.0".to_string()),
+            ..Default::default()
+        };
+        assert_ne!(launch_fingerprint(&shell_env, &settings, &pinned), base);
+    }
+
+    #[test]
+    fn telemetry_opt_out() {
+        let settings = VulneraSettings::default();
+        assert!(telemetry_enabled(&env(&[]), &settings));
+        assert!(!telemetry_enabled(
+            &env(&[("DO_NOT_TRACK", "1")]),
+            &settings
+        ));
+        assert!(!telemetry_enabled(
+            &env(&[("VULNERA_TELEMETRY", "OFF")]),
+            &settings
+        ));
+
+        let opted_out = VulneraSettings {
+            telemetry: Some(false),
+            ..Default::default()
+        };
+        let command = build_command("adapter".into(), &env(&[]), &opted_out, "/work/repo", None);
+        assert!(
+            command
+                .env
+                .contains(&("VULNERA_TELEMETRY".to_string(), "off".to_string()))
+        );
+    }
+
+    #[test]
+    fn forwards_log_file_with_rotation() {
+        let settings = VulneraSettings {
+            log_file: Some(".vulnera/adapter.log".to_string()),
+            log_rotation: Some(LogRotation::Daily),
+            log_format: Some(LogFormat::Json),
+            ..Default::default()
+        };
+        let command = build_command("adapter".into(), &env(&[]), &settings, "/work/repo", None);
+
+        assert!(command.env.contains(&(
+            "VULNERA_LOG_FILE".to_string(),
+            "/work/repo/.vulnera/adapter.log".to_string()
+        )));
+        assert!(
+            command
+                .env
+                .contains(&("VULNERA_LOG_ROTATION".to_string(), "daily".to_string()))
+        );
+        assert!(
+            command
+                .env
+                .contains(&("VULNERA_LOG_FORMAT".to_string(), "json".to_string()))
+        );
+    }
+
+    #[test]
+    fn debug_raises_default_log_level() {
+        let settings = VulneraSettings {
+            debug: true,
+            ..Default::default()
+        };
+        let command = build_command("adapter".into(), &env(&[]), &settings, "/work/repo", None);
+        assert!(
+            command
+                .env
+                .contains(&("VULNERA_LOG".to_string(), "debug".to_string()))
+        );
+
+        let explicit = env(&[("VULNERA_LOG", "trace")]);
+        let command = build_command("adapter".into(), &explicit, &settings, "/work/repo", None);
+        assert!(
+            !command
+                .env
+                .iter()
+                .any(|(k, v)| k == "VULNERA_LOG" && v == "debug")
+        );
+    }
 
     #[test]
-    fn parses_stable_release() {
-        let json = r#"[
-            {"tag_name":"adapter-v0.2.0","prerelease":false,"draft":false,"body":"notes"},
-            {"tag_name":"adapter-v0.1.1","prerelease":false,"draft":false,"body":"notes"}
-        ]"#;
-        assert_eq!(parse_latest_stable_version(json), Some("0.2.0".to_string()));
+    fn dispatches_every_declared_server() {
+        let manifest = include_str!("../extension.toml");
+        for id in [
+            "vulnera",
+            "vulnera-secrets",
+            "vulnera-iac",
+            "vulnera-container",
+        ] {
+            assert!(manifest.contains(&format!("[language_servers.{}]", id)));
+        }
+        assert!(matches!(Server::find("vulnera"), Some(Server::Core)));
+        assert!(matches!(
+            Server::find("vulnera-iac"),
+            Some(Server::Mode(server)) if server.id == "vulnera-iac"
+        ));
+        assert!(Server::find("vulnera-docs").is_none());
+    }
+
+    #[test]
+    fn runs_the_managed_adapter_by_its_capability_name() {
+        use super::host::{FixedClock, FixedPlatform, Host, MemFs};
+        use super::http::Canned;
+        use super::releases::ReleaseCache;
+        use zed_extension_api::{Architecture, Command, Os};
+
+        let fs = MemFs::default();
+        let host = Host {
+            http: &Canned::default(),
+            fs: &fs,
+            clock: &FixedClock(0),
+            platform: &FixedPlatform(Os::Linux, Architecture::X8664),
+            releases: &ReleaseCache::new(),
+            github_token: None,
+        };
+        let mut command = Command {
+            command: "server/0.4.0/vulnera-adapter".to_string(),
+            args: Vec::new(),
+            env: env(&[("PATH", "/usr/bin")]),
+        };
+        exec_by_name(&mut command, &host, false).unwrap();
+        assert_eq!(command.command, "vulnera-adapter");
+        assert_eq!(command.env, env(&[("PATH", "/ext/server/0.4.0:/usr/bin")]));
+
+        let manifest = include_str!("../extension.toml");
+        assert!(manifest.contains("command = \"vulnera-adapter\""));
+        assert!(manifest.contains("command = \"vulnera-adapter.exe\""));
     }
 
     #[test]
-    fn skips_prerelease() {
-        let json = r#"[
-            {"tag_name":"adapter-v0.2.0-rc1","prerelease":true,"draft":false,"body":"notes"},
-            {"tag_name":"adapter-v0.1.1","prerelease":false,"draft":false,"body":"notes"}
-        ]"#;
-        assert_eq!(parse_latest_stable_version(json), Some("0.1.1".to_string()));
+    fn identifies_the_client() {
+        let shell_env = env(&[("VULNERA_CLIENT", "spoofed"), ("PATH", "/usr/bin")]);
+        let command = build_command(
+            "adapter".into(),
+            &shell_env,
+            &VulneraSettings::default(),
+            "/work/repo",
+            None,
+        );
+        let clients: Vec<_> = command
+            .env
+            .iter()
+            .filter(|(k, _)| k == "VULNERA_CLIENT")
+            .collect();
+        assert_eq!(clients.len(), 1);
+        assert_eq!(
+            clients[0].1,
+            format!("zed-extension/{}", env!("CARGO_PKG_VERSION"))
+        );
+        assert!(command.env.contains(&(
+            "VULNERA_CLIENT_FEATURES".to_string(),
+            "adapterUpdate,workDoneProgress,ignorePatterns,lockfiles".to_string()
+        )));
     }
 
     #[test]
-    fn skips_draft() {
-        let json = r#"[
-            {"tag_name":"adapter-v0.2.0","prerelease":false,"draft":true,"body":"notes"},
-            {"tag_name":"adapter-v0.1.1","prerelease":false,"draft":false,"body":"notes"}
-        ]"#;
-        assert_eq!(parse_latest_stable_version(json), Some("0.1.1".to_string()));
+    fn forwards_resource_hints() {
+        let settings = VulneraSettings {
+            priority: Some(ProcessPriority::Idle),
+            max_memory_mb: NonZeroU32::new(512),
+            ..Default::default()
+        };
+        let command = build_command("adapter".into(), &env(&[]), &settings, "/work/repo", None);
+
+        assert!(
+            command
+                .env
+                .contains(&("VULNERA_PRIORITY".to_string(), "idle".to_string()))
+        );
+        assert!(
+            command
+                .env
+                .contains(&("VULNERA_MAX_MEMORY_MB".to_string(), "512".to_string()))
+        );
     }
 
     #[test]
-    fn ignores_non_adapter_tags() {
-        let json = r#"[
-            {"tag_name":"v1.0.0","prerelease":false,"draft":false,"body":"notes"},
-            {"tag_name":"adapter-v0.1.1","prerelease":false,"draft":false,"body":"notes"}
-        ]"#;
-        assert_eq!(parse_latest_stable_version(json), Some("0.1.1".to_string()));
+    fn resolves_locale_from_setting_or_shell() {
+        let settings = VulneraSettings::default();
+        assert_eq!(
+            resolve_locale(&env(&[("LANG", "pt_BR.UTF-8")]), &settings).as_deref(),
+            Some("pt-BR")
+        );
+        assert_eq!(
+            resolve_locale(&env(&[("LC_ALL", "C.UTF-8"), ("LANG", "de_DE")]), &settings),
+            None
+        );
+
+        let explicit = VulneraSettings {
+            language: Some("ja".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            resolve_locale(&env(&[("LANG", "en_US.UTF-8")]), &explicit).as_deref(),
+            Some("ja")
+        );
     }
 
     #[test]
-    fn returns_none_on_empty_list() {
-        assert_eq!(parse_latest_stable_version("[]"), None);
+    fn memoized_version_expires_and_invalidates() {
+        use super::adapters::VERSION_CACHE_TTL_SECS;
+
+        let resolved = ResolvedVersion {
+            version: "0.2.0".to_string(),
+            at: 1_000,
+            generation: adapters::generation(),
+            ttl: VERSION_CACHE_TTL_SECS,
+        };
+        assert!(resolved.is_fresh(1_000 + VERSION_CACHE_TTL_SECS - 1));
+        assert!(!resolved.is_fresh(1_000 + VERSION_CACHE_TTL_SECS));
+
+        adapters::invalidate_session();
+        assert!(!resolved.is_fresh(1_000));
     }
 }
@@ -0,0 +1,205 @@
+//! Resolved dependencies read from the worktree's root lockfiles.
+//!
+//! Only exact `name@version` pairs are extracted — enough for argument
+//! completion and OSV lookups, not a full dependency graph.
+
+use zed_extension_api::Worktree;
+use zed_extension_api::serde_json::{self, Value};
+
+/// A resolved dependency.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Dependency {
+    /// Ecosystem identifier as used by the `ecosystems` setting (`npm`, `cargo`, …).
+    pub ecosystem: String,
+    pub name: String,
+    pub version: String,
+}
+
+impl Dependency {
+    /// The ecosystem name used by the OSV API.
+    pub fn osv_ecosystem(&self) -> &'static str {
+        match self.ecosystem.as_str() {
+            "cargo" => "crates.io",
+            "pypi" => "PyPI",
+            "go" => "Go",
+            "composer" => "Packagist",
+            "rubygems" => "RubyGems",
+            _ => "npm",
+        }
+    }
+}
+
+type Parser = fn(&str) -> Vec<(String, String)>;
+
+/// Supported lockfiles, their ecosystem and parser.
+const LOCKFILES: &[(&str, &str, Parser)] = &[
+    ("package-lock.json", "npm", parse_package_lock),
+    ("Cargo.lock", "cargo", parse_toml_packages),
+    ("poetry.lock", "pypi", parse_toml_packages),
+    ("composer.lock", "composer", parse_composer_lock),
+    ("Gemfile.lock", "rubygems", parse_gemfile_lock),
+    ("go.sum", "go", parse_go_sum),
+];
+
+/// All dependencies pinned by root-level lockfiles, sorted and de-duplicated.
+pub fn dependencies(worktree: &Worktree) -> Vec<Dependency> {
+    let mut deps: Vec<Dependency> = LOCKFILES
+        .iter()
+        .filter_map(|(file, ecosystem, parse)| {
+            let contents = worktree.read_text_file(file).ok()?;
+            Some(
+                parse(&contents)
+                    .into_iter()
+                    .map(|(name, version)| Dependency {
+                        ecosystem: ecosystem.to_string(),
+                        name,
+                        version,
+                    })
+                    .collect::<Vec<_>>(),
+            )
+        })
+        .flatten()
+        .collect();
+    deps.sort();
+    deps.dedup();
+    deps
+}
+
+/// `package-lock.json` v2/v3 (`packages`) or v1 (`dependencies`).
+fn parse_package_lock(contents: &str) -> Vec<(String, String)> {
+    let Ok(lock) = serde_json::from_str::<Value>(contents) else {
+        return Vec::new();
+    };
+    let version_of = |entry: &Value| entry["version"].as_str().map(str::to_string);
+
+    if let Some(packages) = lock["packages"].as_object() {
+        return packages
+            .iter()
+            .filter(|(path, _)| !path.is_empty())
+            .filter_map(|(path, entry)| {
+                let name = path.rsplit("node_modules/").next()?;
+                Some((name.to_string(), version_of(entry)?))
+            })
+            .collect();
+    }
+    lock["dependencies"]
+        .as_object()
+        .map(|deps| {
+            deps.iter()
+                .filter_map(|(name, entry)| Some((name.clone(), version_of(entry)?)))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// `[[package]]` tables with `name` / `version` keys (`Cargo.lock`, `poetry.lock`).
+fn parse_toml_packages(contents: &str) -> Vec<(String, String)> {
+    let mut deps = Vec::new();
+    let mut name = None;
+    for line in contents.lines().map(str::trim) {
+        if line.starts_with('[') {
+            name = None;
+        } else if let Some(value) = toml_string(line, "name") {
+            name = Some(value);
+        } else if let Some(version) = toml_string(line, "version")
+            && let Some(name) = name.take()
+        {
+            deps.push((name, version));
+        }
+    }
+    deps
+}
+
+fn toml_string(line: &str, key: &str) -> Option<String> {
+    let (k, v) = line.split_once('=')?;
+    (k.trim() == key).then(|| v.trim().trim_matches('"').to_string())
+}
+
+/// `composer.lock`: `packages` and `packages-dev` arrays.
+fn parse_composer_lock(contents: &str) -> Vec<(String, String)> {
+    let Ok(lock) = serde_json::from_str::<Value>(contents) else {
+        return Vec::new();
+    };
+    ["packages", "packages-dev"]
+        .iter()
+        .filter_map(|key| lock[key].as_array())
+        .flatten()
+        .filter_map(|p| {
+            Some((
+                p["name"].as_str()?.to_string(),
+                p["version"].as_str()?.trim_start_matches('v').to_string(),
+            ))
+        })
+        .collect()
+}
+
+/// `Gemfile.lock`: `    name (version)` entries under `specs:`.
+fn parse_gemfile_lock(contents: &str) -> Vec<(String, String)> {
+    contents
+        .lines()
+        .filter(|line| line.starts_with("    ") && !line.starts_with("     "))
+        .filter_map(|line| {
+            let (name, rest) = line.trim().split_once(" (")?;
+            Some((name.to_string(), rest.strip_suffix(')')?.to_string()))
+        })
+        .collect()
+}
+
+/// `go.sum`: `module version[/go.mod] hash` lines.
+fn parse_go_sum(contents: &str) -> Vec<(String, String)> {
+    contents
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.split_whitespace();
+            let module = parts.next()?;
+            let version = parts.next()?.trim_end_matches("/go.mod");
+            Some((
+                module.to_string(),
+                version.trim_start_matches('v').to_string(),
+            ))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_gemfile_lock, parse_package_lock, parse_toml_packages};
+
+    fn pairs(items: &[(&str, &str)]) -> Vec<(String, String)> {
+        items
+            .iter()
+            .map(|(n, v)| (n.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn parses_package_lock_v3() {
+        let lock = r#"{
+            "lockfileVersion": 3,
+            "packages": {
+                "": { "name": "app", "version": "1.0.0" },
+                "node_modules/lodash": { "version": "4.17.21" },
+                "node_modules/a/node_modules/@scope/b": { "version": "2.0.0" }
+            }
+        }"#;
+        assert_eq!(
+            parse_package_lock(lock),
+            pairs(&[("@scope/b", "2.0.0"), ("lodash", "4.17.21")])
+        );
+    }
+
+    #[test]
+    fn parses_cargo_lock() {
+        let lock = "version = 3\n\n[[package]]\nname = \"serde\"\nversion = \"1.0.219\"\nsource = \"registry+https://github.com/rust-lang/crates.io-index\"\n";
+        assert_eq!(parse_toml_packages(lock), pairs(&[("serde", "1.0.219")]));
+    }
+
+    #[test]
+    fn parses_gemfile_lock_specs() {
+        let lock = "GEM\n  remote: https://rubygems.org/\n  specs:\n    rack (2.2.3)\n      base64\n    rails (7.0.4)\n";
+        assert_eq!(
+            parse_gemfile_lock(lock),
+            pairs(&[("rack", "2.2.3"), ("rails", "7.0.4")])
+        );
+    }
+}
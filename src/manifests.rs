@@ -0,0 +1,293 @@
+//! Detection of supported dependency manifests in a worktree.
+//!
+//! The extension can only read files by exact path, so detection looks for
+//! well-known manifest and lockfile names at the worktree root. Projects whose
+//! manifests live only in subdirectories (or use `*.csproj`) need the
+//! `always_start` setting.
+//!
+//! Combined with the package-manager binaries on the worktree `PATH`, the same
+//! table decides which ecosystems the adapter should scan.
+//!
+//! The server is attached to whole languages (every Ruby or Kotlin buffer), so
+//! [`LANGUAGE_MANIFESTS`] tells the adapter which file names it should
+//! actually treat as manifests in each language. Lockfiles are attach points
+//! too: [`LOCKFILE_ROUTES`] pairs each with its manifest so the adapter can
+//! report transitive findings in the lockfile where the upgrade happens.
+
+use std::collections::BTreeMap;
+
+use zed_extension_api::serde_json::{Map, Value, json};
+
+use crate::host::Checkout;
+
+/// Root-level manifest and lockfile names, with the ecosystem they belong to.
+pub const MANIFEST_FILES: &[(&str, &str)] = &[
+    ("package.json", "npm"),
+    ("package-lock.json", "npm"),
+    ("yarn.lock", "npm"),
+    ("pnpm-lock.yaml", "npm"),
+    ("requirements.txt", "pypi"),
+    ("Pipfile", "pypi"),
+    ("Pipfile.lock", "pypi"),
+    ("pyproject.toml", "pypi"),
+    ("poetry.lock", "pypi"),
+    ("Cargo.toml", "cargo"),
+    ("Cargo.lock", "cargo"),
+    ("go.mod", "go"),
+    ("go.sum", "go"),
+    ("pom.xml", "maven"),
+    ("build.gradle", "maven"),
+    ("build.gradle.kts", "maven"),
+    ("composer.json", "composer"),
+    ("composer.lock", "composer"),
+    ("Gemfile", "rubygems"),
+    ("Gemfile.lock", "rubygems"),
+    ("packages.config", "nuget"),
+    ("Directory.Packages.props", "nuget"),
+    ("Dockerfile", "container"),
+    ("Containerfile", "container"),
+    ("main.tf", "terraform"),
+    ("versions.tf", "terraform"),
+    (".terraform.lock.hcl", "terraform"),
+];
+
+/// Per LSP language id (as mapped in `extension.toml`), the
+/// [`MANIFEST_FILES`] buffers of that language the adapter should scan.
+const LANGUAGE_MANIFESTS: &[(&str, &[&str])] = &[
+    (
+        "json",
+        &[
+            "package.json",
+            "package-lock.json",
+            "composer.json",
+            "composer.lock",
+            "Pipfile.lock",
+        ],
+    ),
+    (
+        "toml",
+        &[
+            "Cargo.toml",
+            "Cargo.lock",
+            "pyproject.toml",
+            "poetry.lock",
+            "Pipfile",
+        ],
+    ),
+    (
+        "xml",
+        &["pom.xml", "packages.config", "Directory.Packages.props"],
+    ),
+    ("groovy", &["build.gradle"]),
+    ("kotlin", &["build.gradle.kts"]),
+    ("go.mod", &["go.mod"]),
+    ("go.sum", &["go.sum"]),
+    ("ruby", &["Gemfile"]),
+    ("yaml", &["pnpm-lock.yaml"]),
+    ("yarnlock", &["yarn.lock"]),
+    ("dockerfile", &["Dockerfile", "Containerfile"]),
+];
+
+/// Lockfile name and the manifest whose dependencies it pins.
+const LOCKFILE_ROUTES: &[(&str, &str)] = &[
+    ("package-lock.json", "package.json"),
+    ("pnpm-lock.yaml", "package.json"),
+    ("yarn.lock", "package.json"),
+    ("poetry.lock", "pyproject.toml"),
+    ("Pipfile.lock", "Pipfile"),
+    ("Cargo.lock", "Cargo.toml"),
+    ("composer.lock", "composer.json"),
+    ("go.sum", "go.mod"),
+    ("Gemfile.lock", "Gemfile"),
+];
+
+/// `initializationOptions.vulnera.lockfiles`: lockfile name to its ecosystem
+/// and sibling manifest. Findings for direct dependencies are reported on the
+/// manifest, transitive-only ones on the lockfile.
+pub fn lockfile_routes() -> Value {
+    let ecosystem_of = |file: &str| {
+        MANIFEST_FILES
+            .iter()
+            .find(|(name, _)| *name == file)
+            .map(|(_, ecosystem)| *ecosystem)
+    };
+    Value::Object(
+        LOCKFILE_ROUTES
+            .iter()
+            .map(|(lockfile, manifest)| {
+                (
+                    lockfile.to_string(),
+                    json!({ "ecosystem": ecosystem_of(lockfile), "manifest": manifest }),
+                )
+            })
+            .collect(),
+    )
+}
+
+/// Per LSP language id, manifest file-name globs (files that are found by
+/// extension rather than by a fixed name) and their ecosystem.
+const LANGUAGE_PATTERNS: &[(&str, &str, &str)] = &[
+    ("terraform", "*.tf", "terraform"),
+    ("json", "*.tf.json", "terraform"),
+];
+
+/// `initializationOptions.vulnera.languages`: per language, the manifest file
+/// names (or globs) mapped to their ecosystem, with the user's `languages`
+/// entries merged over these defaults key by key.
+pub fn language_options(user: &BTreeMap<String, Map<String, Value>>) -> Value {
+    let mut languages = Map::new();
+    for (language, files) in LANGUAGE_MANIFESTS {
+        let manifests: Map<String, Value> = MANIFEST_FILES
+            .iter()
+            .filter(|(file, _)| files.contains(file))
+            .map(|(file, ecosystem)| (file.to_string(), json!(ecosystem)))
+            .collect();
+        languages.insert(language.to_string(), json!({ "manifests": manifests }));
+    }
+    for (language, pattern, ecosystem) in LANGUAGE_PATTERNS {
+        languages
+            .entry(language.to_string())
+            .or_insert_with(|| json!({ "manifests": {} }))["manifests"][pattern] = json!(ecosystem);
+    }
+    for (language, options) in user {
+        let entry = languages
+            .entry(language.clone())
+            .or_insert_with(|| Value::Object(Map::new()));
+        if let Value::Object(entry) = entry {
+            entry.extend(options.clone());
+        }
+    }
+    Value::Object(languages)
+}
+
+/// Package-manager binaries whose presence means an ecosystem can be resolved.
+const ECOSYSTEM_TOOLS: &[(&str, &[&str])] = &[
+    ("npm", &["npm", "pnpm", "yarn"]),
+    (
+        "pypi",
+        &["pip", "pip3", "python3", "python", "poetry", "pipenv", "uv"],
+    ),
+    ("cargo", &["cargo"]),
+    ("go", &["go"]),
+    ("maven", &["mvn", "gradle"]),
+    ("composer", &["composer"]),
+    ("rubygems", &["bundle", "gem"]),
+    ("nuget", &["dotnet", "nuget"]),
+];
+
+/// Every supported manifest found at the worktree root.
+pub fn find_manifests(worktree: &dyn Checkout) -> Vec<&'static str> {
+    MANIFEST_FILES
+        .iter()
+        .map(|(file, _)| *file)
+        .filter(|file| worktree.read_text_file(file).is_ok())
+        .collect()
+}
+
+/// The first supported manifest found at the worktree root, if any.
+pub fn find_manifest(worktree: &dyn Checkout) -> Option<&'static str> {
+    MANIFEST_FILES
+        .iter()
+        .map(|(file, _)| *file)
+        .find(|file| worktree.read_text_file(file).is_ok())
+}
+
+/// Enable each ecosystem with a manifest at the worktree root whose tooling is
+/// installed, and disable those whose tooling is missing. Ecosystems without a
+/// root manifest are left to the adapter (they may have nested manifests).
+pub fn detect_ecosystems(worktree: &dyn Checkout) -> BTreeMap<String, bool> {
+    ecosystem_map(&find_manifests(worktree), |tool| {
+        worktree.which(tool).is_some()
+    })
+}
+
+fn ecosystem_map(manifests: &[&str], has_tool: impl Fn(&str) -> bool) -> BTreeMap<String, bool> {
+    let mut ecosystems = BTreeMap::new();
+    for (file, ecosystem) in MANIFEST_FILES {
+        if !manifests.contains(file) || ecosystems.contains_key(*ecosystem) {
+            continue;
+        }
+        // Ecosystems resolved without local tooling (container images) are
+        // left to the adapter.
+        let Some((_, tools)) = ECOSYSTEM_TOOLS.iter().find(|(name, _)| name == ecosystem) else {
+            continue;
+        };
+        ecosystems.insert(ecosystem.to_string(), tools.iter().any(|t| has_tool(t)));
+    }
+    ecosystems
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::{ecosystem_map, language_options, lockfile_routes};
+    use zed_extension_api::serde_json::json;
+
+    #[test]
+    fn enables_ecosystems_with_manifest_and_tooling() {
+        let map = ecosystem_map(
+            &["package.json", "yarn.lock", "go.mod", "Dockerfile"],
+            |tool| tool == "yarn",
+        );
+        assert_eq!(map.get("npm"), Some(&true));
+        assert_eq!(map.get("go"), Some(&false));
+        assert_eq!(map.get("cargo"), None);
+        assert_eq!(map.get("container"), None);
+    }
+
+    #[test]
+    fn routes_lockfiles_to_their_manifest() {
+        let routes = lockfile_routes();
+        assert_eq!(
+            routes["pnpm-lock.yaml"],
+            json!({ "ecosystem": "npm", "manifest": "package.json" })
+        );
+        assert_eq!(
+            routes["poetry.lock"],
+            json!({ "ecosystem": "pypi", "manifest": "pyproject.toml" })
+        );
+        // Every routed lockfile is a known manifest file.
+        assert!(
+            routes
+                .as_object()
+                .unwrap()
+                .values()
+                .all(|r| r["ecosystem"].is_string())
+        );
+    }
+
+    #[test]
+    fn merges_user_language_options_over_defaults() {
+        let user = BTreeMap::from([
+            (
+                "json".to_string(),
+                json!({ "manifests": { "package.json": "npm" } })
+                    .as_object()
+                    .unwrap()
+                    .clone(),
+            ),
+            (
+                "hcl".to_string(),
+                json!({ "ecosystem": "terraform" })
+                    .as_object()
+                    .unwrap()
+                    .clone(),
+            ),
+        ]);
+        let options = language_options(&user);
+        assert_eq!(
+            options["json"],
+            json!({ "manifests": { "package.json": "npm" } })
+        );
+        assert_eq!(
+            options["kotlin"],
+            json!({ "manifests": { "build.gradle.kts": "maven" } })
+        );
+        assert_eq!(options["hcl"], json!({ "ecosystem": "terraform" }));
+        assert_eq!(
+            options["terraform"],
+            json!({ "manifests": { "*.tf": "terraform" } })
+        );
+    }
+}
@@ -0,0 +1,245 @@
+//! Local install and download metrics, for `/vulnera-doctor`.
+//!
+//! Counters for downloads (duration, bytes, retries), verification results
+//! and version-cache hits are kept in `metrics.json` in the extension work
+//! dir. Nothing is sent anywhere; the file only exists so that reports like
+//! "updates are slow for our office" come with numbers. A missing or corrupt
+//! file starts the counters from zero.
+
+use serde::{Deserialize, Serialize};
+use zed_extension_api::serde_json;
+
+use crate::host::Host;
+
+pub const METRICS_FILE: &str = "metrics.json";
+
+/// Counters accumulated across sessions.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Metrics {
+    /// Installs that downloaded something, successful or not.
+    pub installs: u64,
+    /// Installs that ended without a usable binary.
+    pub failed_installs: u64,
+    /// Download attempts beyond the first of each install.
+    pub retries: u64,
+    /// Downloads that completed, and their total duration and size.
+    pub downloads: u64,
+    pub download_ms: u64,
+    pub download_bytes: u64,
+    pub verifications_passed: u64,
+    pub verifications_failed: u64,
+    /// Version resolutions answered by a fresh version cache, and those that
+    /// had to ask GitHub.
+    pub cache_hits: u64,
+    pub cache_misses: u64,
+    pub last_install: Option<InstallRecord>,
+}
+
+/// The most recent install that downloaded something.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct InstallRecord {
+    pub binary: String,
+    pub version: String,
+    /// Unix time the install finished.
+    pub at: u64,
+    pub attempts: u32,
+    pub download_ms: u64,
+    pub bytes: u64,
+    pub ok: bool,
+}
+
+/// What one install did, gathered by [`crate::adapters::ensure_binary`].
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct InstallStats {
+    pub attempts: u32,
+    pub downloads: u64,
+    pub download_ms: u64,
+    pub bytes: u64,
+    pub verifications_passed: u64,
+    pub verifications_failed: u64,
+}
+
+pub fn read(host: &Host) -> Metrics {
+    host.fs
+        .read_to_string(METRICS_FILE)
+        .ok()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+fn update(host: &Host, change: impl FnOnce(&mut Metrics)) {
+    let mut metrics = read(host);
+    change(&mut metrics);
+    let written = serde_json::to_string_pretty(&metrics)
+        .map_err(|e| e.to_string())
+        .and_then(|json| {
+            host.fs
+                .write(METRICS_FILE, &json)
+                .map_err(|e| e.to_string())
+        });
+    if let Err(e) = written {
+        debug!("Failed to record metrics: {}", e);
+    }
+}
+
+/// Count a version resolution that did (`hit`) or did not use the cache.
+pub fn record_cache_lookup(host: &Host, hit: bool) {
+    update(host, |m| {
+        if hit {
+            m.cache_hits += 1;
+        } else {
+            m.cache_misses += 1;
+        }
+    });
+}
+
+/// Count an install of `binary` `version` that downloaded at least once.
+pub fn record_install(host: &Host, binary: &str, version: &str, stats: &InstallStats, ok: bool) {
+    update(host, |m| {
+        m.installs += 1;
+        m.failed_installs += u64::from(!ok);
+        m.retries += u64::from(stats.attempts.saturating_sub(1));
+        m.downloads += stats.downloads;
+        m.download_ms += stats.download_ms;
+        m.download_bytes += stats.bytes;
+        m.verifications_passed += stats.verifications_passed;
+        m.verifications_failed += stats.verifications_failed;
+        m.last_install = Some(InstallRecord {
+            binary: binary.to_string(),
+            version: version.to_string(),
+            at: host.clock.now_secs(),
+            attempts: stats.attempts,
+            download_ms: stats.download_ms,
+            bytes: stats.bytes,
+            ok,
+        });
+    });
+}
+
+impl Metrics {
+    /// Doctor lines: `(label, value)`.
+    pub fn summary(&self) -> Vec<(&'static str, String)> {
+        let mut lines = Vec::new();
+        let lookups = self.cache_hits + self.cache_misses;
+        lines.push((
+            "Version cache hit rate",
+            match (self.cache_hits * 100).checked_div(lookups) {
+                Some(percent) => format!("{}% ({} of {})", percent, self.cache_hits, lookups),
+                None => "no lookups yet".to_string(),
+            },
+        ));
+        if self.installs == 0 {
+            lines.push(("Downloads", "none yet".to_string()));
+            return lines;
+        }
+        lines.push((
+            "Downloads",
+            format!(
+                "{} installs ({} failed, {} retries), {} downloads, avg {} at {}",
+                self.installs,
+                self.failed_installs,
+                self.retries,
+                self.downloads,
+                format_ms(self.download_ms / self.downloads.max(1)),
+                format_rate(self.download_bytes, self.download_ms)
+            ),
+        ));
+        lines.push((
+            "Verification",
+            format!(
+                "{} passed, {} failed",
+                self.verifications_passed, self.verifications_failed
+            ),
+        ));
+        if let Some(last) = &self.last_install {
+            lines.push((
+                "Last download",
+                format!(
+                    "{} {} {}: {} attempt(s), {} in {}",
+                    last.binary,
+                    last.version,
+                    if last.ok { "installed" } else { "failed" },
+                    last.attempts,
+                    format_bytes(last.bytes),
+                    format_ms(last.download_ms)
+                ),
+            ));
+        }
+        lines
+    }
+}
+
+fn format_ms(ms: u64) -> String {
+    if ms < 1000 {
+        format!("{} ms", ms)
+    } else {
+        format!("{:.1} s", ms as f64 / 1000.0)
+    }
+}
+
+fn format_bytes(bytes: u64) -> String {
+    if bytes < 1024 * 1024 {
+        format!("{} KiB", bytes / 1024)
+    } else {
+        format!("{:.1} MiB", bytes as f64 / (1024.0 * 1024.0))
+    }
+}
+
+fn format_rate(bytes: u64, ms: u64) -> String {
+    if ms == 0 {
+        return "an unknown rate".to_string();
+    }
+    format!("{}/s", format_bytes(bytes * 1000 / ms))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{InstallStats, Metrics, read, record_cache_lookup, record_install};
+    use crate::host::{FixedClock, FixedPlatform, Host, MemFs};
+    use crate::http::Canned;
+    use crate::releases::ReleaseCache;
+    use zed_extension_api::{Architecture, Os};
+
+    #[test]
+    fn accumulates_and_summarises() {
+        let (http, now) = (Canned::default(), FixedClock(1_800_000_000));
+        let fs = MemFs::default().with_file("metrics.json", b"{not json");
+        let host = Host {
+            http: &http,
+            fs: &fs,
+            clock: &now,
+            platform: &FixedPlatform(Os::Linux, Architecture::X8664),
+            releases: &ReleaseCache::new(),
+            github_token: None,
+        };
+        assert_eq!(read(&host), Metrics::default());
+
+        record_cache_lookup(&host, true);
+        record_cache_lookup(&host, true);
+        record_cache_lookup(&host, false);
+        let stats = InstallStats {
+            attempts: 2,
+            downloads: 2,
+            download_ms: 4000,
+            bytes: 8 * 1024 * 1024,
+            verifications_passed: 1,
+            verifications_failed: 1,
+        };
+        record_install(&host, "vulnera-adapter", "0.4.0", &stats, true);
+
+        let metrics = read(&host);
+        assert_eq!(metrics.retries, 1);
+        let summary = metrics.summary();
+        assert_eq!(summary[0].1, "66% (2 of 3)");
+        assert_eq!(
+            summary[1].1,
+            "1 installs (0 failed, 1 retries), 2 downloads, avg 2.0 s at 2.0 MiB/s"
+        );
+        assert_eq!(
+            summary[3].1,
+            "vulnera-adapter 0.4.0 installed: 2 attempt(s), 8.0 MiB in 4.0 s"
+        );
+    }
+}
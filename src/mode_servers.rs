@@ -0,0 +1,244 @@
+//! Secondary language servers: the managed adapter in another `--mode`, or
+//! another managed binary.
+//!
+//! - `vulnera-secrets` — leaked-credential detection on every common file type.
+//! - `vulnera-iac` — Terraform / CloudFormation / Kubernetes checks, for infra
+//!   teams that want them without dependency scanning; started only in
+//!   worktrees with a root Terraform, CloudFormation, Kustomize or Helm file.
+//! - `vulnera-container` — the separate `vulnera-container-adapter` binary on
+//!   Dockerfile and compose buffers; started only in worktrees that have one.
+//!
+//! Each is opt-in and configured independently under `lsp.<id>.settings`;
+//! `lsp.<id>.binary` swaps in a different binary. Environment, trust and
+//! offline handling come from `lsp.vulnera.settings`, and managed binaries
+//! come from the same install pipeline as the main server.
+
+use serde::Deserialize;
+use zed_extension_api::serde_json::{self, Map, Value, json};
+use zed_extension_api::settings::{CommandSettings, LspSettings};
+use zed_extension_api::{Result, Worktree};
+
+use crate::adapters::{AdapterSpec, CONTAINER_ADAPTER, CORE_ADAPTER};
+use crate::error::VulneraError;
+
+/// A secondary server ID, the managed binary behind it and the mode it runs.
+pub struct ModeServer {
+    pub id: &'static str,
+    pub adapter: &'static AdapterSpec,
+    /// `--mode` passed to the core adapter; dedicated binaries take none.
+    pub mode: Option<&'static str>,
+    /// Worktree files of which at least one must exist for the server to
+    /// start (and its binary to be downloaded); empty to always start.
+    pub marker_files: &'static [&'static str],
+    /// Mode-specific keys accepted under `lsp.<id>.settings` besides the
+    /// common ones; anything else is rejected.
+    pub options: &'static [&'static str],
+}
+
+pub const SECRETS: ModeServer = ModeServer {
+    id: "vulnera-secrets",
+    adapter: &CORE_ADAPTER,
+    mode: Some("secrets"),
+    marker_files: &[],
+    options: &["entropy_checks"],
+};
+
+pub const IAC: ModeServer = ModeServer {
+    id: "vulnera-iac",
+    adapter: &CORE_ADAPTER,
+    mode: Some("iac"),
+    marker_files: &[
+        "main.tf",
+        "versions.tf",
+        ".terraform.lock.hcl",
+        "template.yaml",
+        "template.yml",
+        "template.json",
+        "kustomization.yaml",
+        "Chart.yaml",
+    ],
+    options: &["frameworks"],
+};
+
+pub const CONTAINER: ModeServer = ModeServer {
+    id: "vulnera-container",
+    adapter: &CONTAINER_ADAPTER,
+    mode: None,
+    marker_files: &[
+        "Dockerfile",
+        "Containerfile",
+        "compose.yaml",
+        "compose.yml",
+        "docker-compose.yaml",
+        "docker-compose.yml",
+    ],
+    options: &[],
+};
+
+const ALL: &[&ModeServer] = &[&SECRETS, &IAC, &CONTAINER];
+
+/// The secondary server registered as `id`, if any.
+pub fn find(id: &str) -> Option<&'static ModeServer> {
+    ALL.iter().copied().find(|server| server.id == id)
+}
+
+/// `lsp.<id>.settings` for a secondary server.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ModeSettings {
+    /// Start the server (default off). A disabled server runs an idle
+    /// placeholder rather than failing to start.
+    pub enabled: bool,
+    /// Worktree-relative globs never scanned (fixtures, vendored code).
+    pub exclude: Vec<String>,
+    /// Start even without any of the server's marker files at the worktree
+    /// root (nested infrastructure or container directories).
+    pub always_start: bool,
+    /// The server's [`ModeServer::options`] that are set, forwarded with
+    /// camelCase keys.
+    #[serde(skip)]
+    pub options: Map<String, Value>,
+    /// The `lsp.<id>.binary` block (sibling of `settings`).
+    #[serde(skip)]
+    pub binary: Option<CommandSettings>,
+}
+
+impl ModeSettings {
+    pub fn for_worktree(server: &ModeServer, worktree: &Worktree) -> Result<Self> {
+        let lsp_settings = LspSettings::for_worktree(server.id, worktree)?;
+        let mut settings = match lsp_settings.settings {
+            Some(value) => Self::parse(server, value)
+                .map_err(|e| VulneraError::config(format!("lsp.{}.settings", server.id), e))?,
+            None => Self::default(),
+        };
+        settings.binary = lsp_settings.binary;
+        Ok(settings)
+    }
+
+    /// Strict parsing: the common keys plus the server's own options.
+    fn parse(server: &ModeServer, mut value: Value) -> serde_json::Result<Self> {
+        let mut options = Map::new();
+        if let Some(object) = value.as_object_mut() {
+            for key in server.options {
+                if let Some(option) = object.remove(*key) {
+                    options.insert(key.to_string(), option);
+                }
+            }
+        }
+        let mut settings: Self = serde_json::from_value(value)?;
+        settings.options = options;
+        Ok(settings)
+    }
+
+    /// The `lsp.<id>.binary.path` override, if set (unresolved).
+    pub fn binary_path(&self) -> Option<&str> {
+        self.binary
+            .as_ref()
+            .and_then(|b| b.path.as_deref())
+            .map(str::trim)
+            .filter(|p| !p.is_empty())
+    }
+
+    /// Adapter arguments: `--mode <mode>` for the managed adapter, then
+    /// `lsp.<id>.binary.arguments`. A custom binary gets only the latter.
+    pub fn arguments(&self, server: &ModeServer) -> Vec<String> {
+        let mut args = match server.mode {
+            Some(mode) if self.binary_path().is_none() => {
+                vec!["--mode".to_string(), mode.to_string()]
+            }
+            _ => Vec::new(),
+        };
+        if let Some(extra) = self.binary.as_ref().and_then(|b| b.arguments.as_ref()) {
+            args.extend(extra.iter().cloned());
+        }
+        args
+    }
+
+    pub fn initialization_options(&self, server: &ModeServer) -> Value {
+        let mut options = json!({});
+        if let Some(mode) = server.mode {
+            options["mode"] = json!(mode);
+        }
+        if !self.exclude.is_empty() {
+            options["exclude"] = json!(self.exclude);
+        }
+        for (key, value) in &self.options {
+            options[camel_case(key)] = value.clone();
+        }
+        json!({ "vulnera": options })
+    }
+}
+
+fn camel_case(key: &str) -> String {
+    let mut out = String::with_capacity(key.len());
+    let mut upper = false;
+    for c in key.chars() {
+        if c == '_' {
+            upper = true;
+        } else if upper {
+            out.push(c.to_ascii_uppercase());
+            upper = false;
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CONTAINER, IAC, ModeSettings, SECRETS, find};
+    use zed_extension_api::serde_json::json;
+    use zed_extension_api::settings::CommandSettings;
+
+    #[test]
+    fn runs_adapter_in_mode_unless_overridden() {
+        let mut settings = ModeSettings::parse(
+            &SECRETS,
+            json!({
+                "enabled": true,
+                "always_start": true,
+                "exclude": ["fixtures/**"],
+                "entropy_checks": false
+            }),
+        )
+        .unwrap();
+        assert_eq!(settings.arguments(&SECRETS), ["--mode", "secrets"]);
+        assert_eq!(
+            settings.initialization_options(&SECRETS),
+            json!({
+                "vulnera": { "mode": "secrets", "exclude": ["fixtures/**"], "entropyChecks": false }
+            })
+        );
+
+        settings.binary = Some(CommandSettings {
+            path: Some("/opt/gitleaks-lsp".to_string()),
+            arguments: Some(vec!["--stdio".to_string()]),
+            env: None,
+        });
+        assert_eq!(settings.arguments(&SECRETS), ["--stdio"]);
+    }
+
+    #[test]
+    fn rejects_options_of_other_servers_and_typos() {
+        assert!(ModeSettings::parse(&IAC, json!({ "frameworks": ["terraform"] })).is_ok());
+        assert!(ModeSettings::parse(&IAC, json!({ "entropy_checks": true })).is_err());
+        assert!(ModeSettings::parse(&SECRETS, json!({ "enable": true })).is_err());
+    }
+
+    #[test]
+    fn finds_servers_by_id() {
+        assert_eq!(find("vulnera-iac").map(|s| s.id), Some(IAC.id));
+        assert!(find("vulnera").is_none());
+    }
+
+    #[test]
+    fn dedicated_binary_takes_no_mode() {
+        let settings = ModeSettings::default();
+        assert!(settings.arguments(&CONTAINER).is_empty());
+        assert_eq!(
+            settings.initialization_options(&CONTAINER),
+            json!({ "vulnera": {} })
+        );
+    }
+}
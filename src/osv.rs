@@ -0,0 +1,115 @@
+//! Minimal client for the public OSV API (<https://osv.dev>).
+
+use zed_extension_api::Result;
+use zed_extension_api::http_client::{HttpMethod, HttpRequest, RedirectPolicy};
+use zed_extension_api::serde_json::{self, Value, json};
+
+use crate::error::VulneraError;
+use crate::http::HttpFetch;
+
+/// OSV endpoint returning one advisory by ID (any alias format).
+const OSV_VULN_URL: &str = "https://api.osv.dev/v1/vulns";
+
+/// OSV endpoint listing the advisories affecting a package (version).
+const OSV_QUERY_URL: &str = "https://api.osv.dev/v1/query";
+
+fn lookup_failed(url: &str, reason: impl std::fmt::Display) -> String {
+    VulneraError::AdvisoryLookupFailed {
+        url: url.to_string(),
+        reason: reason.to_string(),
+    }
+    .into()
+}
+
+/// Result pages followed per package query.
+const MAX_QUERY_PAGES: usize = 10;
+
+/// Fetch one advisory by ID.
+pub fn advisory(http: &dyn HttpFetch, id: &str) -> Result<Value> {
+    let url = format!("{}/{}", OSV_VULN_URL, id);
+    let response = http
+        .fetch(&request(url.clone(), HttpMethod::Get, None))
+        .map_err(|e| lookup_failed(&url, e))?;
+    serde_json::from_slice(&response.body)
+        .map_err(|e| lookup_failed(&url, format!("invalid response: {}", e)))
+}
+
+/// Advisories affecting `name` in the OSV `ecosystem`, restricted to
+/// `version` when given.
+pub fn query(
+    http: &dyn HttpFetch,
+    name: &str,
+    ecosystem: &str,
+    version: Option<&str>,
+) -> Result<Vec<Value>> {
+    let mut vulns = Vec::new();
+    let mut page_token: Option<String> = None;
+    for _ in 0..MAX_QUERY_PAGES {
+        let mut body = json!({ "package": { "name": name, "ecosystem": ecosystem } });
+        if let Some(version) = version {
+            body["version"] = json!(version);
+        }
+        if let Some(token) = &page_token {
+            body["page_token"] = json!(token);
+        }
+
+        let response = http
+            .fetch(&request(
+                OSV_QUERY_URL.to_string(),
+                HttpMethod::Post,
+                Some(body.to_string()),
+            ))
+            .map_err(|e| lookup_failed(OSV_QUERY_URL, e))?;
+        let mut result: Value = serde_json::from_slice(&response.body)
+            .map_err(|e| lookup_failed(OSV_QUERY_URL, format!("invalid response: {}", e)))?;
+
+        if let Some(page) = result["vulns"].as_array_mut() {
+            vulns.append(page);
+        }
+        page_token = result["next_page_token"].as_str().map(str::to_string);
+        if page_token.is_none() {
+            break;
+        }
+    }
+    Ok(vulns)
+}
+
+fn request(url: String, method: HttpMethod, body: Option<String>) -> HttpRequest {
+    let mut headers = vec![(
+        "User-Agent".to_string(),
+        "vulnera-zed-extension".to_string(),
+    )];
+    if body.is_some() {
+        headers.push(("Content-Type".to_string(), "application/json".to_string()));
+    }
+    HttpRequest {
+        url,
+        method,
+        headers,
+        body: body.map(String::into_bytes),
+        redirect_policy: RedirectPolicy::FollowAll,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{OSV_QUERY_URL, query};
+    use crate::http::Canned;
+
+    #[test]
+    fn follows_query_pages() {
+        let http = Canned::default()
+            .with(
+                OSV_QUERY_URL,
+                Ok(r#"{"vulns":[{"id":"GHSA-1"}],"next_page_token":"p2"}"#),
+            )
+            .with(OSV_QUERY_URL, Ok(r#"{"vulns":[{"id":"GHSA-2"}]}"#));
+        let vulns = query(&http, "lodash", "npm", Some("4.17.20")).unwrap();
+        let ids: Vec<_> = vulns.iter().filter_map(|v| v["id"].as_str()).collect();
+        assert_eq!(ids, ["GHSA-1", "GHSA-2"]);
+        assert_eq!(http.requested.borrow().len(), 2);
+
+        let err = query(&Canned::default(), "lodash", "npm", None).unwrap_err();
+        assert!(err.starts_with("Vulnera [VUL-109]"), "{}", err);
+    }
+}
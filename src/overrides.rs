@@ -0,0 +1,156 @@
+//! Per-worktree extension overrides from `.zed/vulnera.toml`.
+//!
+//! Unlike `.vulnera.toml` (scan policy, read by the adapter) this file tunes
+//! the extension itself for one repository:
+//!
+//! ```toml
+//! adapter_version = "0.2.0"    # pin the managed adapter
+//! binary_path = "bin/adapter"  # relative to the worktree root; only honoured
+//!                              # in worktrees listed in `trusted_paths`
+//! offline = false              # force offline mode off (or on)
+//! ```
+//!
+//! Values here take precedence over `lsp.vulnera` settings; `VULNERA_*`
+//! environment variables still win over both. The release channel is a user
+//! setting only: a repository must not move its users onto prereleases.
+
+use serde::Deserialize;
+
+use crate::error::VulneraError;
+use crate::host::Checkout;
+use zed_extension_api::Result;
+use zed_extension_api::serde_json::{self, Map, Value};
+
+/// Worktree-relative location of the overrides file.
+pub const OVERRIDES_FILE: &str = ".zed/vulnera.toml";
+
+/// Extension behaviour overridden for a single worktree.
+#[derive(Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(default, deny_unknown_fields)]
+pub struct WorktreeOverrides {
+    /// Adapter version to install instead of the latest release.
+    pub adapter_version: Option<String>,
+    /// Adapter binary to launch instead of the managed one.
+    pub binary_path: Option<String>,
+    /// `false` disables offline mode even if a database is configured;
+    /// `true` requires one.
+    pub offline: Option<bool>,
+}
+
+impl WorktreeOverrides {
+    /// Load the overrides for `worktree`. A missing file yields no overrides;
+    /// a malformed one is reported as an error.
+    pub fn for_worktree(worktree: &dyn Checkout) -> Result<Self> {
+        match worktree.read_text_file(OVERRIDES_FILE) {
+            Ok(contents) => {
+                let overrides =
+                    parse(&contents).map_err(|e| VulneraError::config(OVERRIDES_FILE, e))?;
+                log!("Using worktree overrides from {}", OVERRIDES_FILE);
+                Ok(overrides)
+            }
+            Err(_) => Ok(Self::default()),
+        }
+    }
+}
+
+/// Parse the flat `key = value` subset of TOML the overrides file uses:
+/// basic strings, literal strings, booleans, and `#` comments.
+fn parse(contents: &str) -> std::result::Result<WorktreeOverrides, String> {
+    let mut table = Map::new();
+
+    for (index, line) in contents.lines().enumerate() {
+        let line_no = index + 1;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if line.starts_with('[') {
+            return Err(format!("line {}: tables are not supported", line_no));
+        }
+        let (key, raw) = line
+            .split_once('=')
+            .ok_or_else(|| format!("line {}: expected `key = value`", line_no))?;
+        let value = parse_value(raw.trim())
+            .ok_or_else(|| format!("line {}: expected a quoted string or true/false", line_no))?;
+        table.insert(key.trim().to_string(), value);
+    }
+    // Once supported; ignored rather than failing the server's start.
+    if table.remove("channel").is_some() {
+        warn!(
+            "Ignoring `channel` in {}: set lsp.vulnera.settings.channel instead",
+            OVERRIDES_FILE
+        );
+    }
+
+    let overrides: WorktreeOverrides =
+        serde_json::from_value(Value::Object(table)).map_err(|e| e.to_string())?;
+    if let Some(version) = &overrides.adapter_version
+        && !crate::adapters::is_semver(version.trim())
+    {
+        return Err(format!(
+            "adapter_version `{}` is not a version such as 0.4.0",
+            version
+        ));
+    }
+    Ok(overrides)
+}
+
+fn parse_value(raw: &str) -> Option<Value> {
+    for quote in ['"', '\''] {
+        if let Some(inner) = raw.strip_prefix(quote) {
+            let end = inner.find(quote)?;
+            let rest = inner[end + 1..].trim_start();
+            if !rest.is_empty() && !rest.starts_with('#') {
+                return None;
+            }
+            return Some(Value::String(inner[..end].to_string()));
+        }
+    }
+
+    match raw.split('#').next().unwrap_or_default().trim() {
+        "true" => Some(Value::Bool(true)),
+        "false" => Some(Value::Bool(false)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{WorktreeOverrides, parse};
+
+    #[test]
+    fn parses_overrides_file() {
+        let contents = r#"
+# Pinned for the legacy monorepo
+adapter_version = "0.2.0"
+channel = "beta"
+binary_path = 'bin/vulnera-adapter'  # vendored build
+offline = false
+"#;
+        assert_eq!(
+            parse(contents),
+            Ok(WorktreeOverrides {
+                adapter_version: Some("0.2.0".to_string()),
+                binary_path: Some("bin/vulnera-adapter".to_string()),
+                offline: Some(false),
+            })
+        );
+    }
+
+    #[test]
+    fn ignores_the_repository_channel() {
+        assert_eq!(
+            parse("channel = \"nightly\""),
+            Ok(WorktreeOverrides::default())
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_keys_and_values() {
+        assert!(parse("adapter_versoin = \"0.2.0\"").is_err());
+        assert!(parse("offline = yes").is_err());
+        assert!(parse("[adapter]").is_err());
+        assert!(parse("adapter_version = \"../../../usr/bin\"").is_err());
+        assert!(parse("adapter_version = \"latest\"").is_err());
+    }
+}
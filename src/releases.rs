@@ -0,0 +1,203 @@
+//! Release metadata shared by version resolution, asset lookup and release
+//! notes.
+//!
+//! Every GitHub response describing a release — the `/releases` list, Zed's
+//! `latest_github_release` and `github_release_by_tag_name`, a
+//! `/releases/tags/<tag>` lookup — is remembered here, so resolving a version,
+//! picking the platform asset and showing the release notes take one request
+//! between them instead of one each. Entries live in the host's
+//! [`ReleaseCache`] for the session and in `releases/<owner>_<repo>.json` across
+//! sessions. A published tag does not change, so entries do not expire; the
+//! version cache decides when to look for new releases.
+
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use zed_extension_api::serde_json;
+
+use crate::host::Host;
+
+pub const RELEASES_DIR: &str = "releases";
+
+/// Releases kept per repository, most recently seen first.
+const MAX_RELEASES: usize = 50;
+
+/// What the extension uses of one release.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReleaseInfo {
+    pub tag: String,
+    pub prerelease: bool,
+    /// Release notes, when the response carried them.
+    pub notes: Option<String>,
+    /// `(name, download URL)` of each asset.
+    pub assets: Vec<(String, String)>,
+    /// The oldest extension the release works with, from its `compat.json`
+    /// asset, once read.
+    pub min_extension_version: Option<String>,
+    /// Whether `min_extension_version` was looked for, so a release that
+    /// declares none is not asked again.
+    pub compat_checked: bool,
+}
+
+impl ReleaseInfo {
+    /// Fill in what `self` lacks from an earlier entry for the same tag.
+    fn merge(mut self, earlier: ReleaseInfo) -> Self {
+        if self.notes.is_none() {
+            self.notes = earlier.notes;
+        }
+        if self.assets.is_empty() {
+            self.assets = earlier.assets;
+        }
+        if !self.compat_checked {
+            self.min_extension_version = earlier.min_extension_version;
+            self.compat_checked = earlier.compat_checked;
+        }
+        self
+    }
+}
+
+/// Releases seen this session, per repository.
+#[derive(Debug, Default)]
+pub struct ReleaseCache {
+    repos: Mutex<BTreeMap<String, Vec<ReleaseInfo>>>,
+}
+
+impl ReleaseCache {
+    pub const fn new() -> Self {
+        ReleaseCache {
+            repos: Mutex::new(BTreeMap::new()),
+        }
+    }
+}
+
+/// The cache behind [`Host::REAL`].
+pub static SESSION: ReleaseCache = ReleaseCache::new();
+
+fn releases_path(repo: &str) -> String {
+    format!("{}/{}.json", RELEASES_DIR, repo.replace('/', "_"))
+}
+
+/// Run `f` on `repo`'s releases, loading them from disk on first use.
+fn with_repo<T>(host: &Host, repo: &str, f: impl FnOnce(&mut Vec<ReleaseInfo>) -> T) -> T {
+    let mut repos = host
+        .releases
+        .repos
+        .lock()
+        .unwrap_or_else(|e| e.into_inner());
+    let releases = repos.entry(repo.to_string()).or_insert_with(|| {
+        host.fs
+            .read_to_string(&releases_path(repo))
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    });
+    f(releases)
+}
+
+/// Remember `fetched` releases of `repo`, given newest first.
+pub fn remember(host: &Host, repo: &str, fetched: Vec<ReleaseInfo>) {
+    let json = with_repo(host, repo, |releases| {
+        for release in fetched.into_iter().rev() {
+            let release = match releases.iter().position(|r| r.tag == release.tag) {
+                Some(i) => release.merge(releases.remove(i)),
+                None => release,
+            };
+            releases.insert(0, release);
+        }
+        releases.truncate(MAX_RELEASES);
+        serde_json::to_string(releases)
+    });
+    let written = json.map_err(|e| e.to_string()).and_then(|json| {
+        host.fs
+            .create_dir_all(RELEASES_DIR)
+            .and_then(|()| host.fs.write(&releases_path(repo), &json))
+            .map_err(|e| e.to_string())
+    });
+    if let Err(e) = written {
+        debug!("Failed to cache the {} releases: {}", repo, e);
+    }
+}
+
+/// The remembered release of `repo` tagged `tag`.
+pub fn get(host: &Host, repo: &str, tag: &str) -> Option<ReleaseInfo> {
+    with_repo(host, repo, |releases| {
+        releases.iter().find(|release| release.tag == tag).cloned()
+    })
+}
+
+/// The remembered releases of `repo`, most recently seen first.
+pub fn list(host: &Host, repo: &str) -> Vec<ReleaseInfo> {
+    with_repo(host, repo, |releases| releases.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ReleaseCache, ReleaseInfo, get, remember};
+    use crate::host::{FixedClock, FixedPlatform, Host, MemFs};
+    use crate::http::Canned;
+    use zed_extension_api::{Architecture, Os};
+
+    fn release(tag: &str, notes: Option<&str>, assets: &[&str]) -> ReleaseInfo {
+        ReleaseInfo {
+            tag: tag.to_string(),
+            prerelease: false,
+            notes: notes.map(str::to_string),
+            assets: assets
+                .iter()
+                .map(|name| (name.to_string(), format!("https://dl/{}", name)))
+                .collect(),
+            ..ReleaseInfo::default()
+        }
+    }
+
+    #[test]
+    fn merges_and_persists_releases() {
+        let (http, clock) = (Canned::default(), FixedClock(1_800_000_000));
+        let platform = FixedPlatform(Os::Linux, Architecture::X8664);
+        let fs = MemFs::default();
+        let host = Host {
+            http: &http,
+            fs: &fs,
+            clock: &clock,
+            platform: &platform,
+            releases: &ReleaseCache::new(),
+            github_token: None,
+        };
+        remember(
+            &host,
+            "vulnera-rs/adapter",
+            vec![
+                release("adapter-v0.4.0", Some("Faster scans"), &[]),
+                release("adapter-v0.3.0", None, &["a"]),
+            ],
+        );
+        // A later by-tag lookup adds the assets and keeps the notes.
+        remember(
+            &host,
+            "vulnera-rs/adapter",
+            vec![release("adapter-v0.4.0", None, &["b"])],
+        );
+        let expected = release("adapter-v0.4.0", Some("Faster scans"), &["b"]);
+        assert_eq!(
+            get(&host, "vulnera-rs/adapter", "adapter-v0.4.0").as_ref(),
+            Some(&expected)
+        );
+
+        // A new session reads them back from disk.
+        let restarted = Host {
+            releases: &ReleaseCache::new(),
+            ..host
+        };
+        assert_eq!(
+            get(&restarted, "vulnera-rs/adapter", "adapter-v0.4.0"),
+            Some(expected)
+        );
+        assert!(fs.file("releases/vulnera-rs_adapter.json").is_some());
+        assert_eq!(
+            get(&restarted, "vulnera-rs/adapter", "adapter-v0.2.0"),
+            None
+        );
+    }
+}
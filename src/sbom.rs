@@ -0,0 +1,16 @@
+//! Per-worktree SBOMs shared with the context server.
+//!
+//! The core server is launched with `VULNERA_SBOM_DIR` pointing at the
+//! extension's `sbom/` directory and writes a CycloneDX SBOM of its workspace
+//! there after each full scan, off Zed's extension calls. The MCP server is
+//! pointed at the same directory (`--sbom-dir`) and exposes each file as a
+//! resource, so agents and other MCP clients get the dependency inventory
+//! without a fresh scan.
+
+pub const SBOM_DIR: &str = "sbom";
+
+/// Absolute SBOM directory, for handing to the adapter.
+pub fn sbom_dir() -> Option<String> {
+    let dir = std::env::current_dir().ok()?.join(SBOM_DIR);
+    Some(dir.to_string_lossy().into_owned())
+}
@@ -0,0 +1,1264 @@
+//! Extension settings read from the `lsp.vulnera.settings` block of Zed's
+//! `settings.json`.
+//!
+//! Keys are written in `snake_case` by the user. Scan options are forwarded to
+//! the adapter as `initializationOptions.vulnera.<camelCaseKey>`, matching the
+//! keys the adapter already accepts directly (e.g. `apiBaseUrl`).
+
+use std::collections::BTreeMap;
+use std::num::NonZeroU32;
+
+use serde::{Deserialize, Serialize};
+use zed_extension_api::serde_json::{self, Value};
+use zed_extension_api::settings::{CommandSettings, LspSettings};
+use zed_extension_api::{Result, Worktree};
+
+use crate::SERVER_ID;
+use crate::channel::Channel;
+use crate::error::VulneraError;
+use crate::host::Checkout;
+use crate::registries::RegistryCredentials;
+
+// ── Settings model ────────────────────────────────────────────────────────────
+
+/// Everything the extension understands under `lsp.vulnera.settings`.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct VulneraSettings {
+    /// Options forwarded to the adapter as initialization options.
+    #[serde(flatten)]
+    pub scan: ScanOptions,
+    /// JSON file of accepted findings; relative paths resolve against the
+    /// worktree root.
+    pub baseline_file: Option<String>,
+    /// Local OSV snapshot used instead of the Vulnera API (air-gapped mode).
+    pub offline_db_path: Option<String>,
+    /// Private registry credentials keyed by registry name (`npm`, `pypi`, …).
+    pub registries: BTreeMap<String, RegistryCredentials>,
+    /// Adapter runtime worker threads. Passed as `VULNERA_WORKER_THREADS`
+    /// because the runtime is built before initialization options arrive.
+    pub worker_threads: Option<NonZeroU32>,
+    /// Scheduling priority the adapter applies to itself. Passed as
+    /// `VULNERA_PRIORITY`.
+    pub priority: Option<ProcessPriority>,
+    /// Soft memory ceiling for the adapter, in MiB. Passed as
+    /// `VULNERA_MAX_MEMORY_MB`.
+    pub max_memory_mb: Option<NonZeroU32>,
+    /// Extra shell env vars passed to the adapter. A trailing `*` matches by
+    /// prefix (`PIP_*`).
+    pub forward_env: Vec<String>,
+    /// Pass the complete worktree shell environment to the adapter instead of
+    /// the filtered subset.
+    pub inherit_env: bool,
+    /// Verbose adapter logging (`VULNERA_LOG=debug`, `--verbose`) plus the
+    /// extension's own diagnostic output.
+    pub debug: bool,
+    /// File the adapter writes its log to; relative paths resolve against the
+    /// worktree root. Passed as `VULNERA_LOG_FILE`.
+    pub log_file: Option<String>,
+    /// How often `log_file` is rotated. Passed as `VULNERA_LOG_ROTATION`.
+    pub log_rotation: Option<LogRotation>,
+    /// Adapter log output format. Passed as `VULNERA_LOG_FORMAT`.
+    pub log_format: Option<LogFormat>,
+    /// The extension's own log filter, such as `warn,adapters=debug`; see
+    /// [`crate::logging`]. Read once per session.
+    pub extension_log: Option<String>,
+    /// Language for advisory summaries (BCP 47, e.g. `de` or `pt-BR`);
+    /// defaults to the shell locale. Passed as `VULNERA_LOCALE`.
+    pub language: Option<String>,
+    /// `false` turns off adapter telemetry (`VULNERA_TELEMETRY=off`).
+    pub telemetry: Option<bool>,
+    /// Extra command-line arguments appended when spawning the adapter.
+    pub adapter_args: Vec<String>,
+    /// Format and path of the SBOM written by the "export SBOM" task.
+    pub sbom: SbomSettings,
+    /// How Zed talks to the adapter: stdio, or a TCP / unix-socket listener
+    /// bridged to stdio.
+    pub transport: TransportSettings,
+    /// Share one adapter process per machine across worktrees.
+    pub daemon: bool,
+    /// Start the adapter even when no supported manifest is found at the
+    /// worktree root (monorepos with nested manifests, `*.csproj` projects).
+    pub always_start: bool,
+    /// Enable or disable ecosystems from the manifests and package managers
+    /// found in the worktree (default on); explicit `ecosystems` entries win.
+    pub detect_ecosystems: Option<bool>,
+    /// Forward the worktree `.gitignore` patterns to the adapter (default on).
+    pub use_gitignore: Option<bool>,
+    /// Have the adapter report scan progress as LSP work-done progress, shown
+    /// in Zed's status bar (default on).
+    pub report_progress: Option<bool>,
+    /// Per-language adapter options keyed by LSP language id (`json`, `go.mod`,
+    /// …), merged over the built-in defaults.
+    pub languages: BTreeMap<String, serde_json::Map<String, Value>>,
+    /// Treat worktrees outside `trusted_paths` as untrusted.
+    pub require_trust: bool,
+    /// Absolute directories whose worktrees (and their subdirectories) are
+    /// trusted when `require_trust` is on.
+    pub trusted_paths: Vec<String>,
+    /// What happens in an untrusted worktree.
+    pub untrusted_mode: UntrustedMode,
+    /// Whether newer adapter releases are installed as they appear.
+    pub update_mode: UpdateMode,
+    /// How a running adapter switches to a version installed mid-session.
+    pub update_restart: UpdateRestart,
+    /// Release channel the managed adapters follow.
+    pub channel: Channel,
+    /// `false` freezes the installed adapter: after the first install, GitHub
+    /// is not queried at all.
+    pub auto_update: Option<bool>,
+    /// Zed's conventional `lsp.vulnera.binary` block (sibling of `settings`).
+    #[serde(skip)]
+    pub binary: Option<CommandSettings>,
+}
+
+/// Scan behaviour options the adapter consumes. Unset options are omitted from
+/// the forwarded payload so the adapter's own defaults apply.
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default, rename_all(serialize = "camelCase"))]
+pub struct ScanOptions {
+    /// Advisory IDs (`GHSA-…`, `CVE-…`, `RUSTSEC-…`) whose findings are suppressed.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub ignore_advisories: Vec<String>,
+    /// Package names (optionally `name@version`) whose findings are suppressed.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub ignore_packages: Vec<String>,
+    /// Per-ecosystem switches (`npm`, `pypi`, `cargo`, `go`, `maven`, …);
+    /// ecosystems not listed keep the adapter default.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub ecosystems: BTreeMap<String, bool>,
+    /// Whether development dependencies (`devDependencies`, `[dev-dependencies]`, …) are scanned.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub include_dev_dependencies: Option<bool>,
+    /// Whether optional dependencies are scanned.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub include_optional: Option<bool>,
+    /// Whether peer dependencies are scanned.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub include_peer: Option<bool>,
+    /// Whether transitive dependencies are scanned, or only direct ones.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scan_transitive: Option<bool>,
+    /// Maximum dependency-graph depth followed when scanning transitively
+    /// (direct dependencies are depth 1).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_depth: Option<u32>,
+    /// Vulnerability databases backing the diagnostics; empty means all.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub sources: Vec<DataSource>,
+    /// Worktree-relative globs (`vendor/**`) whose manifests are not scanned.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub exclude: Vec<String>,
+    /// When scans run: on open (and save), only on save, or only on request.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scan_on: Option<ScanTrigger>,
+    /// Minimum quiet period after an edit before a manifest is rescanned.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scan_debounce_ms: Option<u64>,
+    /// How each Vulnera severity is reported as an LSP diagnostic severity.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub severity_map: BTreeMap<Severity, DiagnosticSeverity>,
+    /// Cap on diagnostics published per file; the most severe are kept.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_diagnostics_per_file: Option<u32>,
+    /// What the adapter does with findings beyond the cap. Always forwarded
+    /// alongside `max_diagnostics_per_file` (defaulting to `summary`) so the
+    /// behaviour does not depend on the adapter version.
+    #[serde(skip_serializing)]
+    pub diagnostic_overflow: Option<DiagnosticOverflow>,
+    /// Inlay hints (e.g. latest safe version beside a dependency line).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub inlay_hints: Option<InlayHintOptions>,
+    /// The per-manifest "N vulnerabilities" code lens.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code_lens: Option<CodeLensOptions>,
+    /// Whether "bump to fixed version" code actions are offered.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enable_fix_actions: Option<bool>,
+    /// Whether Dockerfile `FROM` lines are checked for vulnerable base-image tags.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enable_container_base_image_scan: Option<bool>,
+    /// Infrastructure-as-code scanning (Terraform, Kubernetes).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub iac: Option<IacOptions>,
+    /// Allowed / denied SPDX license identifiers for license diagnostics.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub license_policy: Option<LicensePolicy>,
+    /// Timeout for each Vulnera API request, in seconds.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub api_timeout_secs: Option<u64>,
+    /// Retries for failed or timed-out Vulnera API requests.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub api_retries: Option<u32>,
+    /// Upper bound on manifests scanned in parallel.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_concurrent_scans: Option<NonZeroU32>,
+}
+
+/// The `license_policy` settings block. Unknown keys are rejected so a typo
+/// (`allowed`) does not silently disable the policy.
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LicensePolicy {
+    /// Licenses that are always acceptable; when non-empty, anything else is flagged.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub allow: Vec<String>,
+    /// Licenses that are always flagged.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub deny: Vec<String>,
+}
+
+impl LicensePolicy {
+    fn validate(&self) -> Result<()> {
+        if let Some(blank) = self
+            .allow
+            .iter()
+            .chain(&self.deny)
+            .find(|id| id.trim().is_empty() || id.contains(char::is_whitespace))
+        {
+            return Err(format!("'{}' is not a valid SPDX identifier", blank));
+        }
+        if let Some(both) = self.allow.iter().find(|id| self.deny.contains(id)) {
+            return Err(format!("'{}' is both allowed and denied", both));
+        }
+        Ok(())
+    }
+}
+
+/// The `iac` settings block. Unknown keys are rejected, as for `license_policy`.
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct IacOptions {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub terraform: Option<TerraformOptions>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kubernetes: Option<KubernetesOptions>,
+}
+
+/// The `iac.terraform` settings block.
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields, rename_all(serialize = "camelCase"))]
+pub struct TerraformOptions {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enabled: Option<bool>,
+    /// Check `required_providers` version constraints.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub check_providers: Option<bool>,
+    /// Check registry `module` source versions.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub check_modules: Option<bool>,
+}
+
+/// The `iac.kubernetes` settings block.
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields, rename_all(serialize = "camelCase"))]
+pub struct KubernetesOptions {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enabled: Option<bool>,
+    /// Worktree-relative globs of YAML files treated as Kubernetes manifests;
+    /// [`KubernetesOptions::DEFAULT_PATHS`] when empty.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub paths: Vec<String>,
+    /// Also treat any YAML document with `apiVersion` and `kind` as a manifest.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detect_by_content: Option<bool>,
+    /// Check container `image:` references for known CVEs.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scan_images: Option<bool>,
+}
+
+impl KubernetesOptions {
+    /// Conventional manifest locations.
+    pub const DEFAULT_PATHS: &[&str] = &[
+        "k8s/**/*.{yaml,yml}",
+        "kubernetes/**/*.{yaml,yml}",
+        "deploy/**/*.{yaml,yml}",
+        "manifests/**/*.{yaml,yml}",
+        "**/*.k8s.{yaml,yml}",
+    ];
+
+    /// The configured globs, or the defaults.
+    pub fn paths(&self) -> Vec<&str> {
+        if self.paths.is_empty() {
+            Self::DEFAULT_PATHS.to_vec()
+        } else {
+            self.paths.iter().map(String::as_str).collect()
+        }
+    }
+}
+
+/// The `inlay_hints` settings block.
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct InlayHintOptions {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enabled: Option<bool>,
+}
+
+/// A vulnerability feed the adapter can query.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DataSource {
+    Osv,
+    Ghsa,
+    Nvd,
+}
+
+/// A Vulnera finding severity. Accepts the capitalized spelling used by
+/// `severityMin` as well.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    #[serde(alias = "Critical")]
+    Critical,
+    #[serde(alias = "High")]
+    High,
+    #[serde(alias = "Medium")]
+    Medium,
+    #[serde(alias = "Low")]
+    Low,
+}
+
+impl Severity {
+    /// All severities, most severe first.
+    pub const ALL: [Severity; 4] = [
+        Severity::Critical,
+        Severity::High,
+        Severity::Medium,
+        Severity::Low,
+    ];
+
+    /// Parse a severity name, ignoring case.
+    pub fn parse(name: &str) -> Option<Severity> {
+        Self::ALL
+            .into_iter()
+            .find(|s| s.as_str().eq_ignore_ascii_case(name.trim()))
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Severity::Critical => "critical",
+            Severity::High => "high",
+            Severity::Medium => "medium",
+            Severity::Low => "low",
+        }
+    }
+}
+
+/// An LSP `DiagnosticSeverity`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DiagnosticSeverity {
+    Error,
+    Warning,
+    #[serde(alias = "info")]
+    Information,
+    Hint,
+}
+
+/// The `code_lens` settings block.
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct CodeLensOptions {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enabled: Option<bool>,
+    /// Lens title template; see [`CODE_LENS_PLACEHOLDERS`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub format: Option<String>,
+}
+
+/// Placeholders the adapter substitutes in `code_lens.format`.
+const CODE_LENS_PLACEHOLDERS: &[&str] = &["count", "critical", "high", "medium", "low"];
+
+/// Overflow handling once `max_diagnostics_per_file` is reached.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DiagnosticOverflow {
+    /// Publish one extra diagnostic stating how many findings were hidden.
+    #[default]
+    Summary,
+    /// Silently drop the remaining findings.
+    Truncate,
+}
+
+/// The editor events that trigger a scan.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ScanTrigger {
+    Open,
+    Save,
+    Manual,
+}
+
+impl ScanTrigger {
+    /// The equivalent `(analyzeOnOpen, analyzeOnSave)` adapter flags.
+    fn analyze_flags(self) -> (bool, bool) {
+        match self {
+            ScanTrigger::Open => (true, true),
+            ScanTrigger::Save => (false, true),
+            ScanTrigger::Manual => (false, false),
+        }
+    }
+}
+
+/// Scheduling priority hint for the adapter process. Zed spawns the process,
+/// so the adapter lowers its own priority (nice value / priority class).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProcessPriority {
+    Normal,
+    /// Below normal (nice 10).
+    Low,
+    /// Only runs when the machine is otherwise idle (nice 19).
+    Idle,
+}
+
+impl ProcessPriority {
+    /// The value passed in `VULNERA_PRIORITY`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ProcessPriority::Normal => "normal",
+            ProcessPriority::Low => "low",
+            ProcessPriority::Idle => "idle",
+        }
+    }
+}
+
+/// Rotation schedule for the adapter log file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogRotation {
+    Never,
+    Hourly,
+    Daily,
+}
+
+impl LogRotation {
+    /// The value passed in `VULNERA_LOG_ROTATION`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            LogRotation::Never => "never",
+            LogRotation::Hourly => "hourly",
+            LogRotation::Daily => "daily",
+        }
+    }
+}
+
+/// Adapter log output format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    /// Human-readable lines.
+    Pretty,
+    /// One JSON object per line, for log shippers.
+    Json,
+}
+
+impl LogFormat {
+    /// The value passed in `VULNERA_LOG_FORMAT`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            LogFormat::Pretty => "pretty",
+            LogFormat::Json => "json",
+        }
+    }
+}
+
+/// The `sbom` settings block.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SbomSettings {
+    pub format: SbomFormat,
+    /// Worktree-relative output path; `sbom.cdx.json` or `sbom.spdx.json` by
+    /// default.
+    pub output: Option<String>,
+}
+
+impl SbomSettings {
+    pub fn output(&self) -> &str {
+        non_empty(&self.output).unwrap_or(match self.format {
+            SbomFormat::CycloneDx => "sbom.cdx.json",
+            SbomFormat::Spdx => "sbom.spdx.json",
+        })
+    }
+}
+
+/// The `transport` settings block.
+#[derive(Debug, Default, Hash, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TransportSettings {
+    pub mode: TransportMode,
+    /// Loopback port in `tcp` mode; 0 (the default) lets the adapter pick one.
+    pub port: Option<u16>,
+    /// Socket path in `socket` mode; one per worktree under the extension
+    /// directory by default.
+    pub socket_path: Option<String>,
+}
+
+/// Adapter transport.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TransportMode {
+    #[default]
+    Stdio,
+    Tcp,
+    Socket,
+}
+
+/// SBOM document format.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SbomFormat {
+    #[default]
+    #[serde(rename = "cyclonedx")]
+    CycloneDx,
+    Spdx,
+}
+
+impl SbomFormat {
+    /// The adapter's `--format` value.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SbomFormat::CycloneDx => "cyclonedx-json",
+            SbomFormat::Spdx => "spdx-json",
+        }
+    }
+}
+
+/// Adapter behaviour in worktrees that are not trusted.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UntrustedMode {
+    /// Scan against the local database only, so nothing leaves the machine.
+    #[default]
+    Offline,
+    /// Do not start the adapter at all.
+    Skip,
+}
+
+/// How newer adapter releases are picked up.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UpdateMode {
+    /// Install and run the latest release.
+    #[default]
+    Auto,
+    /// Keep running the installed version and log that an update is available.
+    Notify,
+    /// Keep running the installed version; only record available updates.
+    Manual,
+}
+
+/// How a running adapter is told to switch to a newer installed version.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UpdateRestart {
+    /// Restart once no scan is in flight.
+    #[default]
+    Idle,
+    /// Ask in a notification before restarting.
+    Prompt,
+    /// Keep running until the server is restarted by hand.
+    Never,
+}
+
+impl UpdateRestart {
+    /// The value sent in `adapterUpdate.restart`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            UpdateRestart::Idle => "idle",
+            UpdateRestart::Prompt => "prompt",
+            UpdateRestart::Never => "never",
+        }
+    }
+}
+
+impl VulneraSettings {
+    /// Load the settings that apply to `worktree`. A missing `settings` block
+    /// yields the defaults; a malformed one is reported as an error.
+    pub fn for_worktree(worktree: &Worktree) -> Result<Self> {
+        let lsp_settings = LspSettings::for_worktree(SERVER_ID, worktree)?;
+        let mut settings = match lsp_settings.settings {
+            Some(value) => Self::from_value(value)?,
+            None => Self::default(),
+        };
+        settings.binary = lsp_settings.binary;
+        settings.refuse_project_values(worktree);
+        Ok(settings)
+    }
+
+    /// Drop the [`PROTECTED_KEYS`] and `binary.path` that `worktree`'s own
+    /// `.zed/settings.json` sets, unless the worktree is explicitly trusted.
+    /// Zed merges project settings into what [`Self::for_worktree`] reads and
+    /// the user's values cannot be told apart, so each such key falls back to
+    /// its safe value: trust required, no `trusted_paths`, the managed adapter.
+    pub fn refuse_project_values(&mut self, worktree: &dyn Checkout) {
+        let keys = project_keys(worktree);
+        if keys.is_empty() {
+            return;
+        }
+        if keys.contains(&"trusted_paths") {
+            self.trusted_paths.clear();
+        }
+        let root = worktree.root_path();
+        if self.explicitly_trusts(&root) {
+            return;
+        }
+        for key in &keys {
+            match *key {
+                "require_trust" => self.require_trust = true,
+                "untrusted_mode" => self.untrusted_mode = UntrustedMode::default(),
+                "inherit_env" => self.inherit_env = false,
+                "registries" => self.registries.clear(),
+                "channel" => self.channel = Channel::default(),
+                "extension_log" => self.extension_log = None,
+                "forward_env" => self.forward_env.clear(),
+                "adapter_args" => self.adapter_args.clear(),
+                "log_file" => self.log_file = None,
+                "transport" => self.transport = TransportSettings::default(),
+                "daemon" => self.daemon = false,
+                "offline_db_path" => self.offline_db_path = None,
+                BINARY_PATH_KEY => {
+                    if let Some(binary) = &mut self.binary {
+                        binary.path = None;
+                    }
+                }
+                BINARY_ARGUMENTS_KEY => {
+                    if let Some(binary) = &mut self.binary {
+                        binary.arguments = None;
+                    }
+                }
+                _ => {}
+            }
+        }
+        warn!(
+            "Ignoring {} from {} in {}: set them in your user settings, or add the \
+             worktree to trusted_paths",
+            keys.join(", "),
+            PROJECT_SETTINGS_FILE,
+            root
+        );
+    }
+
+    /// The `lsp.vulnera.binary.path` override, if set (unresolved).
+    pub fn binary_path(&self) -> Option<&str> {
+        self.binary
+            .as_ref()
+            .and_then(|b| b.path.as_deref())
+            .map(str::trim)
+            .filter(|p| !p.is_empty())
+    }
+
+    /// Arguments from `lsp.vulnera.binary.arguments`, if set.
+    pub fn binary_arguments(&self) -> &[String] {
+        self.binary
+            .as_ref()
+            .and_then(|b| b.arguments.as_deref())
+            .unwrap_or_default()
+    }
+
+    fn from_value(value: Value) -> Result<Self> {
+        let settings: Self = serde_json::from_value(value)
+            .map_err(|e| VulneraError::config("lsp.vulnera.settings", e))?;
+        settings.validate()?;
+        Ok(settings)
+    }
+
+    /// Checks that serde cannot express on its own.
+    fn validate(&self) -> Result<()> {
+        if self.forward_env.iter().any(|pattern| pattern.trim() == "*") {
+            return Err(VulneraError::config(
+                "lsp.vulnera.settings.forward_env",
+                "`*` would forward the whole environment; use inherit_env instead",
+            )
+            .into());
+        }
+        if let Some(format) = self
+            .scan
+            .code_lens
+            .as_ref()
+            .and_then(|c| c.format.as_deref())
+        {
+            validate_template(format, CODE_LENS_PLACEHOLDERS)
+                .map_err(|e| VulneraError::config("lsp.vulnera.settings.code_lens.format", e))?;
+        }
+        if let Some(policy) = &self.scan.license_policy {
+            policy
+                .validate()
+                .map_err(|e| VulneraError::config("lsp.vulnera.settings.license_policy", e))?;
+        }
+        Ok(())
+    }
+
+    /// The `iac.kubernetes` block, if Kubernetes scanning is enabled.
+    pub fn kubernetes(&self) -> Option<&KubernetesOptions> {
+        self.scan
+            .iac
+            .as_ref()?
+            .kubernetes
+            .as_ref()
+            .filter(|k| k.enabled == Some(true))
+    }
+
+    /// Whether the worktree rooted at `worktree_root` may talk to remote
+    /// services and supply its own extension configuration.
+    pub fn trusts(&self, worktree_root: &str) -> bool {
+        !self.require_trust || self.explicitly_trusts(worktree_root)
+    }
+
+    /// Whether the worktree rooted at `worktree_root` lies under one of the
+    /// `trusted_paths`, whatever `require_trust` says. Only such worktrees
+    /// may pick the adapter executable themselves.
+    pub fn explicitly_trusts(&self, worktree_root: &str) -> bool {
+        let root = worktree_root.trim_end_matches(['/', '\\']);
+        self.trusted_paths.iter().any(|trusted| {
+            let trusted = trusted.trim().trim_end_matches(['/', '\\']);
+            !trusted.is_empty()
+                && root
+                    .strip_prefix(trusted)
+                    .is_some_and(|rest| rest.is_empty() || rest.starts_with(['/', '\\']))
+        })
+    }
+
+    /// The configured adapter log file, if any (unresolved).
+    pub fn log_file(&self) -> Option<&str> {
+        non_empty(&self.log_file)
+    }
+
+    /// The configured extension log filter, if any.
+    pub fn extension_log(&self) -> Option<&str> {
+        non_empty(&self.extension_log)
+    }
+
+    /// The configured offline database path, if any (unresolved).
+    pub fn offline_db_path(&self) -> Option<&str> {
+        non_empty(&self.offline_db_path)
+    }
+
+    /// Build the `initializationOptions` payload sent to the adapter for the
+    /// worktree rooted at `worktree_root`.
+    pub fn initialization_options(&self, worktree_root: &str) -> Result<Value> {
+        let mut scan = serde_json::to_value(&self.scan).map_err(serialize_failed)?;
+
+        // Older adapters only understand the individual analyze flags.
+        if let Some(trigger) = self.scan.scan_on {
+            let (on_open, on_save) = trigger.analyze_flags();
+            scan["analyzeOnOpen"] = Value::Bool(on_open);
+            scan["analyzeOnSave"] = Value::Bool(on_save);
+        }
+
+        if self.scan.max_diagnostics_per_file.is_some() {
+            let overflow = self.scan.diagnostic_overflow.unwrap_or_default();
+            scan["diagnosticOverflow"] =
+                serde_json::to_value(overflow).map_err(serialize_failed)?;
+        }
+
+        if let Some(kubernetes) = self.kubernetes() {
+            scan["iac"]["kubernetes"]["paths"] = Value::from(kubernetes.paths());
+        }
+
+        if let Some(baseline) = non_empty(&self.baseline_file) {
+            scan["baselineFile"] = Value::String(resolve_in_worktree(worktree_root, baseline));
+        }
+
+        Ok(serde_json::json!({ "vulnera": scan }))
+    }
+}
+
+// ── Project settings ──────────────────────────────────────────────────────────
+
+/// The project settings file Zed merges over the user's settings.
+pub const PROJECT_SETTINGS_FILE: &str = ".zed/settings.json";
+
+/// Keys under `lsp.vulnera.settings` deciding what a worktree may do, or
+/// what the adapter is launched with, which a repository must not set for
+/// itself.
+const PROTECTED_KEYS: &[&str] = &[
+    "require_trust",
+    "trusted_paths",
+    "untrusted_mode",
+    "inherit_env",
+    "forward_env",
+    "registries",
+    "channel",
+    "extension_log",
+    "adapter_args",
+    "log_file",
+    "transport",
+    "daemon",
+    "offline_db_path",
+];
+
+/// `lsp.vulnera.binary.path`, protected like [`PROTECTED_KEYS`].
+const BINARY_PATH_KEY: &str = "binary.path";
+
+/// `lsp.vulnera.binary.arguments`, protected like [`PROTECTED_KEYS`].
+const BINARY_ARGUMENTS_KEY: &str = "binary.arguments";
+
+/// The protected keys `worktree`'s `.zed/settings.json` sets for `vulnera`.
+/// A file that mentions `vulnera` but cannot be parsed counts as setting all
+/// of them.
+fn project_keys(worktree: &dyn Checkout) -> Vec<&'static str> {
+    let Ok(contents) = worktree.read_text_file(PROJECT_SETTINGS_FILE) else {
+        return Vec::new();
+    };
+    let all = || {
+        let mut keys = PROTECTED_KEYS.to_vec();
+        keys.extend([BINARY_PATH_KEY, BINARY_ARGUMENTS_KEY]);
+        keys
+    };
+    let project: Value = match serde_json::from_str(&strip_jsonc(&contents)) {
+        Ok(project) => project,
+        Err(_) if contents.contains(SERVER_ID) => return all(),
+        Err(_) => return Vec::new(),
+    };
+    let lsp = &project["lsp"][SERVER_ID];
+    let mut keys: Vec<&'static str> = PROTECTED_KEYS
+        .iter()
+        .copied()
+        .filter(|key| lsp["settings"].get(key).is_some())
+        .collect();
+    if !lsp["binary"]["path"].is_null() {
+        keys.push(BINARY_PATH_KEY);
+    }
+    if !lsp["binary"]["arguments"].is_null() {
+        keys.push(BINARY_ARGUMENTS_KEY);
+    }
+    keys
+}
+
+/// `contents` without the comments and trailing commas Zed's settings files
+/// allow, so `serde_json` can parse them.
+fn strip_jsonc(contents: &str) -> String {
+    let mut out = String::with_capacity(contents.len());
+    let mut chars = contents.chars().peekable();
+    let mut in_string = false;
+    while let Some(c) = chars.next() {
+        if in_string {
+            out.push(c);
+            match c {
+                '\\' => out.extend(chars.next()),
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match (c, chars.peek()) {
+            ('"', _) => {
+                in_string = true;
+                out.push(c);
+            }
+            ('/', Some('/')) => {
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        out.push(c);
+                        break;
+                    }
+                }
+            }
+            ('/', Some('*')) => {
+                chars.next();
+                let mut previous = ' ';
+                for c in chars.by_ref() {
+                    if previous == '*' && c == '/' {
+                        break;
+                    }
+                    previous = c;
+                }
+            }
+            ('}' | ']', _) => {
+                let kept = out.trim_end().len();
+                if out[..kept].ends_with(',') {
+                    out.truncate(kept - 1);
+                }
+                out.push(c);
+            }
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+// ── Helpers ───────────────────────────────────────────────────────────────────
+
+fn non_empty(value: &Option<String>) -> Option<&str> {
+    value.as_deref().map(str::trim).filter(|s| !s.is_empty())
+}
+
+fn serialize_failed(e: serde_json::Error) -> String {
+    VulneraError::Internal {
+        reason: format!("failed to serialize scan options: {}", e),
+    }
+    .into()
+}
+
+/// Ensure every `{placeholder}` in `template` is one of `allowed`.
+fn validate_template(template: &str, allowed: &[&str]) -> Result<()> {
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        let after = &rest[start + 1..];
+        let end = after.find('}').ok_or_else(|| "unclosed '{'".to_string())?;
+        let name = &after[..end];
+        if !allowed.contains(&name) {
+            return Err(format!(
+                "unknown placeholder '{{{}}}' (expected one of: {})",
+                name,
+                allowed.join(", ")
+            ));
+        }
+        rest = &after[end + 1..];
+    }
+    Ok(())
+}
+
+/// Resolve `path` against `worktree_root` unless it is already absolute.
+///
+/// `std::path` follows WASI (Unix) rules inside the extension, so Windows
+/// drive-letter and UNC paths coming from the host are detected by hand.
+pub fn resolve_in_worktree(worktree_root: &str, path: &str) -> String {
+    let bytes = path.as_bytes();
+    let is_absolute = path.starts_with('/')
+        || path.starts_with('\\')
+        || (bytes.len() >= 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':');
+
+    if is_absolute || worktree_root.is_empty() {
+        return path.to_string();
+    }
+
+    let root = worktree_root.trim_end_matches(['/', '\\']);
+    let relative = path.trim_start_matches("./");
+    format!("{}/{}", root, relative)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        Channel, KubernetesOptions, TransportMode, UntrustedMode, UpdateMode, UpdateRestart,
+        VulneraSettings, resolve_in_worktree, strip_jsonc,
+    };
+    use crate::host::MemWorktree;
+    use zed_extension_api::serde_json::json;
+
+    const ROOT: &str = "/work/repo";
+
+    #[test]
+    fn forwards_ignores_as_camel_case() {
+        let settings = VulneraSettings::from_value(json!({
+            "ignore_advisories": ["GHSA-xxxx-yyyy-zzzz", "CVE-2023-1234"],
+            "ignore_packages": ["lodash@4.17.20"]
+        }))
+        .unwrap();
+
+        assert_eq!(
+            settings.initialization_options(ROOT).unwrap(),
+            json!({
+                "vulnera": {
+                    "ignoreAdvisories": ["GHSA-xxxx-yyyy-zzzz", "CVE-2023-1234"],
+                    "ignorePackages": ["lodash@4.17.20"]
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn empty_settings_forward_nothing() {
+        let settings = VulneraSettings::from_value(json!({})).unwrap();
+        assert_eq!(
+            settings.initialization_options(ROOT).unwrap(),
+            json!({ "vulnera": {} })
+        );
+    }
+
+    #[test]
+    fn resolves_baseline_against_worktree() {
+        let settings =
+            VulneraSettings::from_value(json!({ "baseline_file": "./.vulnera/baseline.json" }))
+                .unwrap();
+        assert_eq!(
+            settings.initialization_options(ROOT).unwrap()["vulnera"]["baselineFile"],
+            "/work/repo/.vulnera/baseline.json"
+        );
+    }
+
+    #[test]
+    fn keeps_absolute_paths() {
+        assert_eq!(
+            resolve_in_worktree(ROOT, "/etc/baseline.json"),
+            "/etc/baseline.json"
+        );
+        assert_eq!(
+            resolve_in_worktree("C:\\repo", "D:\\baseline.json"),
+            "D:\\baseline.json"
+        );
+        assert_eq!(
+            resolve_in_worktree("C:\\repo\\", "baseline.json"),
+            "C:\\repo/baseline.json"
+        );
+    }
+
+    #[test]
+    fn forwards_ecosystem_map() {
+        let settings = VulneraSettings::from_value(json!({
+            "ecosystems": { "npm": true, "cargo": true, "pypi": false }
+        }))
+        .unwrap();
+        assert_eq!(
+            settings.initialization_options(ROOT).unwrap()["vulnera"]["ecosystems"],
+            json!({ "cargo": true, "npm": true, "pypi": false })
+        );
+    }
+
+    #[test]
+    fn forwards_only_set_dependency_toggles() {
+        let settings = VulneraSettings::from_value(json!({
+            "include_dev_dependencies": false,
+            "include_peer": true
+        }))
+        .unwrap();
+        assert_eq!(
+            settings.initialization_options(ROOT).unwrap(),
+            json!({ "vulnera": { "includeDevDependencies": false, "includePeer": true } })
+        );
+    }
+
+    #[test]
+    fn validates_sources() {
+        let settings = VulneraSettings::from_value(json!({ "sources": ["osv", "ghsa"] })).unwrap();
+        assert_eq!(
+            settings.initialization_options(ROOT).unwrap()["vulnera"]["sources"],
+            json!(["osv", "ghsa"])
+        );
+        assert!(VulneraSettings::from_value(json!({ "sources": ["snyk"] })).is_err());
+    }
+
+    #[test]
+    fn scan_on_sets_analyze_flags() {
+        let settings = VulneraSettings::from_value(json!({ "scan_on": "save" })).unwrap();
+        assert_eq!(
+            settings.initialization_options(ROOT).unwrap(),
+            json!({
+                "vulnera": { "scanOn": "save", "analyzeOnOpen": false, "analyzeOnSave": true }
+            })
+        );
+    }
+
+    #[test]
+    fn forwards_severity_map() {
+        let settings = VulneraSettings::from_value(json!({
+            "severity_map": { "Critical": "error", "medium": "hint", "low": "info" }
+        }))
+        .unwrap();
+        assert_eq!(
+            settings.initialization_options(ROOT).unwrap()["vulnera"]["severityMap"],
+            json!({ "critical": "error", "medium": "hint", "low": "information" })
+        );
+        assert!(
+            VulneraSettings::from_value(json!({ "severity_map": { "high": "fatal" } })).is_err()
+        );
+    }
+
+    #[test]
+    fn max_diagnostics_carries_overflow_behaviour() {
+        let settings =
+            VulneraSettings::from_value(json!({ "max_diagnostics_per_file": 50 })).unwrap();
+        assert_eq!(
+            settings.initialization_options(ROOT).unwrap(),
+            json!({ "vulnera": { "maxDiagnosticsPerFile": 50, "diagnosticOverflow": "summary" } })
+        );
+    }
+
+    #[test]
+    fn validates_code_lens_placeholders() {
+        let ok = json!({ "code_lens": { "format": "{count} vulnerable ({critical} critical)" } });
+        assert!(VulneraSettings::from_value(ok).is_ok());
+
+        let bad = json!({ "code_lens": { "format": "{total} vulnerable" } });
+        assert!(VulneraSettings::from_value(bad).is_err());
+    }
+
+    #[test]
+    fn forwards_terraform_options() {
+        let settings = VulneraSettings::from_value(json!({
+            "iac": { "terraform": { "enabled": true, "check_modules": false } }
+        }))
+        .unwrap();
+        assert_eq!(
+            settings.initialization_options(ROOT).unwrap()["vulnera"]["iac"],
+            json!({ "terraform": { "enabled": true, "checkModules": false } })
+        );
+        assert!(
+            VulneraSettings::from_value(json!({ "iac": { "terraform": { "modules": false } } }))
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn fills_default_kubernetes_paths() {
+        let settings = VulneraSettings::from_value(json!({
+            "iac": { "kubernetes": { "enabled": true, "scan_images": true } }
+        }))
+        .unwrap();
+        let options = settings.initialization_options(ROOT).unwrap();
+        assert_eq!(
+            options["vulnera"]["iac"]["kubernetes"]["scanImages"],
+            json!(true)
+        );
+        assert_eq!(
+            options["vulnera"]["iac"]["kubernetes"]["paths"],
+            json!(KubernetesOptions::DEFAULT_PATHS)
+        );
+    }
+
+    #[test]
+    fn validates_license_policy() {
+        let ok = json!({ "license_policy": { "allow": ["MIT", "Apache-2.0"], "deny": ["GPL-3.0-only"] } });
+        let settings = VulneraSettings::from_value(ok).unwrap();
+        assert_eq!(
+            settings.initialization_options(ROOT).unwrap()["vulnera"]["licensePolicy"],
+            json!({ "allow": ["MIT", "Apache-2.0"], "deny": ["GPL-3.0-only"] })
+        );
+
+        let overlap = json!({ "license_policy": { "allow": ["MIT"], "deny": ["MIT"] } });
+        assert!(VulneraSettings::from_value(overlap).is_err());
+
+        let typo = json!({ "license_policy": { "allowed": ["MIT"] } });
+        assert!(VulneraSettings::from_value(typo).is_err());
+    }
+
+    #[test]
+    fn rejects_wrong_types() {
+        assert!(VulneraSettings::from_value(json!({ "ignore_advisories": "CVE-1" })).is_err());
+    }
+
+    #[test]
+    fn trusts_worktrees_under_trusted_paths() {
+        let settings = VulneraSettings::from_value(json!({
+            "require_trust": true,
+            "trusted_paths": ["/work/", "/home/me/src/app"]
+        }))
+        .unwrap();
+
+        assert!(settings.trusts(ROOT));
+        assert!(settings.trusts("/home/me/src/app"));
+        assert!(!settings.trusts("/home/me/src/application"));
+        assert!(!settings.trusts("/tmp/clone"));
+        assert!(VulneraSettings::default().trusts("/tmp/clone"));
+        assert!(!VulneraSettings::default().explicitly_trusts("/tmp/clone"));
+        assert!(settings.explicitly_trusts("/work/repo/sub"));
+    }
+
+    #[test]
+    fn parses_update_mode() {
+        let settings = VulneraSettings::from_value(json!({ "update_mode": "notify" })).unwrap();
+        assert_eq!(settings.update_mode, UpdateMode::Notify);
+        assert_eq!(VulneraSettings::default().update_mode, UpdateMode::Auto);
+        assert!(VulneraSettings::from_value(json!({ "update_mode": "nightly" })).is_err());
+        let prompt = VulneraSettings::from_value(json!({ "update_restart": "prompt" })).unwrap();
+        assert_eq!(prompt.update_restart, UpdateRestart::Prompt);
+        assert_eq!(
+            VulneraSettings::default().update_restart,
+            UpdateRestart::Idle
+        );
+        let frozen = VulneraSettings::from_value(json!({ "auto_update": false })).unwrap();
+        assert_eq!(frozen.auto_update, Some(false));
+    }
+
+    #[test]
+    fn parses_channel() {
+        let settings = VulneraSettings::from_value(json!({ "channel": "nightly" })).unwrap();
+        assert_eq!(settings.channel, Channel::Nightly);
+        assert_eq!(VulneraSettings::default().channel, Channel::Stable);
+        assert!(VulneraSettings::from_value(json!({ "channel": "canary" })).is_err());
+    }
+
+    #[test]
+    fn refuses_protected_keys_from_project_settings() {
+        let project = r#"{
+            "lsp": {
+                "vulnera": {
+                    /* from the repository */
+                    "settings": { "require_trust": false, "trusted_paths": ["/"], "untrusted_mode": "skip", "channel": "nightly" },
+                    "binary": { "path": "./evil" },
+                },
+            },
+        }"#;
+        let worktree = MemWorktree {
+            root: ROOT.to_string(),
+            ..MemWorktree::default()
+        }
+        .with_file(".zed/settings.json", project);
+        let mut settings = VulneraSettings::from_value(json!({
+            "require_trust": false,
+            "trusted_paths": ["/"],
+            "untrusted_mode": "skip",
+            "channel": "nightly",
+        }))
+        .unwrap();
+        settings.binary = Some(zed_extension_api::settings::CommandSettings {
+            path: Some("./evil".to_string()),
+            arguments: None,
+            env: None,
+        });
+        settings.refuse_project_values(&worktree);
+        assert!(settings.require_trust);
+        assert!(settings.trusted_paths.is_empty());
+        assert_eq!(settings.untrusted_mode, UntrustedMode::Offline);
+        assert_eq!(settings.channel, Channel::Stable);
+        assert_eq!(settings.binary_path(), None);
+        assert!(!settings.trusts(ROOT));
+    }
+
+    #[test]
+    fn refuses_launch_keys_from_project_settings() {
+        let launch = json!({
+            "forward_env": ["AWS_*"],
+            "adapter_args": ["--exec", "sh"],
+            "log_file": "/home/me/.bashrc",
+            "transport": { "mode": "tcp", "port": 9257 },
+            "daemon": true,
+            "offline_db_path": "/tmp/db",
+        });
+        let project = json!({
+            "lsp": {
+                "vulnera": {
+                    "settings": launch,
+                    "binary": { "arguments": ["--exec", "sh"] },
+                },
+            },
+        });
+        let worktree = MemWorktree {
+            root: ROOT.to_string(),
+            ..MemWorktree::default()
+        }
+        .with_file(".zed/settings.json", &project.to_string());
+        let mut settings = VulneraSettings::from_value(launch).unwrap();
+        settings.binary = Some(zed_extension_api::settings::CommandSettings {
+            path: None,
+            arguments: Some(vec!["--exec".to_string(), "sh".to_string()]),
+            env: None,
+        });
+        settings.refuse_project_values(&worktree);
+        assert!(settings.forward_env.is_empty());
+        assert!(settings.adapter_args.is_empty());
+        assert!(settings.binary_arguments().is_empty());
+        assert_eq!(settings.log_file(), None);
+        assert_eq!(settings.transport.mode, TransportMode::Stdio);
+        assert_eq!(settings.transport.port, None);
+        assert!(!settings.daemon);
+        assert_eq!(settings.offline_db_path(), None);
+    }
+
+    #[test]
+    fn rejects_forwarding_the_whole_environment() {
+        assert!(VulneraSettings::from_value(json!({ "forward_env": ["*"] })).is_err());
+        assert!(VulneraSettings::from_value(json!({ "forward_env": ["PIP_*"] })).is_ok());
+    }
+
+    #[test]
+    fn strips_settings_comments_and_trailing_commas() {
+        assert_eq!(
+            strip_jsonc("{ // note\n \"a\": \"//x\", /* b */ \"c\": [1,], }"),
+            "{ \n \"a\": \"//x\",  \"c\": [1]}"
+        );
+    }
+}
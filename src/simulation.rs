@@ -0,0 +1,674 @@
+//! End-to-end tests of adapter resolution against a simulated Zed host.
+//!
+//! [`Sim`] stands in for everything `language_server_command` gets from Zed:
+//! the platform, HTTP and GitHub lookups, downloads into the work dir, the
+//! clock, and the worktree's files and shell environment. Each scenario
+//! builds the server command the way `language_server_command` does — the
+//! launch context from the worktree, then
+//! [`VulneraExtension::core_server_command`] or
+//! [`VulneraExtension::mode_server_command`] — runs the update check that
+//! follows it, and checks what was requested and written.
+//!
+//! `LanguageServerId` is an opaque host resource, so installation status is
+//! not reported, and `lsp.*` settings are given directly instead of read
+//! through Zed.
+
+use zed_extension_api::serde_json::{self, Value, json};
+use zed_extension_api::{
+    Architecture, Command, Extension, GithubRelease, GithubReleaseAsset, Os, Result,
+};
+
+use crate::adapters::CORE_ADAPTER;
+use crate::host::{FixedClock, FixedPlatform, Host, MemFs, MemWorktree};
+use crate::http::Canned;
+use crate::mode_servers::{IAC, ModeServer, ModeSettings};
+use crate::releases::ReleaseCache;
+use crate::settings::{UpdateRestart, VulneraSettings};
+use crate::{LaunchContext, VulneraExtension};
+
+/// When scenarios run, in seconds since the epoch.
+const NOW: u64 = 1_800_000_000;
+
+const ASSET: &str = "vulnera-adapter-x86_64-unknown-linux-gnu";
+
+/// A simulated host: a Linux x86-64 machine with an empty work dir and no
+/// network, serving an npm project with default settings and no shell
+/// variables until a scenario adds them.
+struct Sim {
+    http: Canned,
+    fs: MemFs,
+    clock: FixedClock,
+    platform: FixedPlatform,
+    releases: ReleaseCache,
+    worktree: MemWorktree,
+    /// `lsp.vulnera.settings`.
+    settings: Value,
+}
+
+impl Sim {
+    fn new() -> Self {
+        Sim {
+            http: Canned::default(),
+            fs: MemFs::default(),
+            clock: FixedClock(NOW),
+            platform: FixedPlatform(Os::Linux, Architecture::X8664),
+            releases: ReleaseCache::new(),
+            worktree: MemWorktree {
+                id: 1,
+                root: "/work/project".to_string(),
+                ..MemWorktree::default()
+            }
+            .with_file("package.json", "{}"),
+            settings: json!({}),
+        }
+    }
+
+    fn host(&self) -> Host<'_> {
+        Host {
+            http: &self.http,
+            fs: &self.fs,
+            clock: &self.clock,
+            platform: &self.platform,
+            releases: &self.releases,
+            github_token: None,
+        }
+    }
+
+    /// A variable in the user's shell.
+    fn env(mut self, key: &str, value: &str) -> Self {
+        self.worktree.env.push((key.to_string(), value.to_string()));
+        self
+    }
+
+    /// A file checked into the worktree.
+    fn project_file(mut self, path: &str, contents: &str) -> Self {
+        self.worktree = self.worktree.with_file(path, contents);
+        self
+    }
+
+    fn setting(mut self, key: &str, value: Value) -> Self {
+        self.settings[key] = value;
+        self
+    }
+
+    /// A worktree outside the `trusted_paths` of a `require_trust` setup,
+    /// scanned offline.
+    fn untrusted(self) -> Self {
+        self.setting("require_trust", json!(true))
+            .setting("offline_db_path", json!("/data/osv"))
+    }
+
+    /// Publish `version` on GitHub with `binary` as this platform's asset.
+    fn publish(mut self, version: &str, binary: &[u8]) -> Self {
+        let tag = format!("adapter-v{}", version);
+        let url = format!("https://dl/{}/{}", tag, ASSET);
+        let release = GithubRelease {
+            version: tag.clone(),
+            assets: vec![GithubReleaseAsset {
+                name: ASSET.to_string(),
+                download_url: url.clone(),
+            }],
+        };
+        self.http
+            .releases
+            .push(("vulnera-rs/adapter".to_string(), release.clone()));
+        self.http
+            .releases
+            .push((format!("vulnera-rs/adapter@{}", tag), release));
+        self.fs.downloads.push((url, binary.to_vec()));
+        self
+    }
+
+    /// Declare in `compat.json` that published `version` needs extension
+    /// `required` or newer.
+    fn requires_extension(mut self, version: &str, required: &str) -> Self {
+        let tag = format!("adapter-v{}", version);
+        let url = format!("https://dl/{}/compat.json", tag);
+        for (_, release) in &mut self.http.releases {
+            if release.version == tag {
+                release.assets.push(GithubReleaseAsset {
+                    name: "compat.json".to_string(),
+                    download_url: url.clone(),
+                });
+            }
+        }
+        let compat = format!(r#"{{"min_extension_version": "{}"}}"#, required);
+        self.http = self.http.with(&url, Ok(&compat));
+        self
+    }
+
+    /// Serve the raw `/releases` list of everything published so far.
+    fn list_releases(mut self) -> Self {
+        let list: Vec<Value> = self
+            .http
+            .releases
+            .iter()
+            .filter(|(key, _)| key == "vulnera-rs/adapter")
+            .map(|(_, release)| {
+                let assets: Vec<Value> = release
+                    .assets
+                    .iter()
+                    .map(|a| json!({"name": a.name, "browser_download_url": a.download_url}))
+                    .collect();
+                json!({"tag_name": release.version, "assets": assets})
+            })
+            .collect();
+        let body = Value::Array(list).to_string();
+        self.http = self.http.with(
+            "https://api.github.com/repos/vulnera-rs/adapter/releases",
+            Ok(&body),
+        );
+        self
+    }
+
+    /// A version cache recording `version` as latest, `age` seconds ago.
+    fn cached(self, version: &str, age: u64) -> Self {
+        let fetched_at = (NOW - age).to_string();
+        self.with_file("server/cached-version.txt", version.as_bytes())
+            .with_file("server/cached-version-timestamp.txt", fetched_at.as_bytes())
+    }
+
+    /// A completed install of `version`.
+    fn installed(self, version: &str) -> Self {
+        self.with_file(&binary(version), b"\x7fELF\x02\x01\x01")
+            .with_file(
+                &format!("server/{}/installed-version.txt", version),
+                version.as_bytes(),
+            )
+    }
+
+    fn with_file(mut self, path: &str, contents: &[u8]) -> Self {
+        self.fs = self.fs.with_file(path, contents);
+        self
+    }
+
+    fn try_ctx(&self) -> Result<LaunchContext> {
+        let mut settings: VulneraSettings =
+            serde_json::from_value(self.settings.clone()).map_err(|e| e.to_string())?;
+        settings.refuse_project_values(&self.worktree);
+        LaunchContext::resolve(settings, &self.worktree)
+    }
+
+    fn ctx(&self) -> LaunchContext {
+        self.try_ctx().unwrap()
+    }
+
+    /// The `vulnera` server's command.
+    fn start(&self, extension: &mut VulneraExtension) -> Result<Command> {
+        let ctx = self.try_ctx()?;
+        let (command, _) =
+            extension.core_server_command(&ctx.host(&self.host()), &ctx, None, &self.worktree)?;
+        Ok(command)
+    }
+
+    /// The adapter binary the `vulnera` server launches.
+    fn launch(&self, extension: &mut VulneraExtension) -> Result<String> {
+        self.start(extension).map(|command| command.command)
+    }
+
+    /// `server`'s command with `lsp.<id>.settings` enabling it.
+    fn start_mode(&self, extension: &mut VulneraExtension, server: &ModeServer) -> Result<Command> {
+        let ctx = self.try_ctx()?;
+        let settings = ModeSettings {
+            enabled: true,
+            ..ModeSettings::default()
+        };
+        extension.mode_server_command(
+            &ctx.host(&self.host()),
+            &ctx,
+            server,
+            settings,
+            None,
+            &self.worktree,
+        )
+    }
+
+    /// The update check that follows a launch, off the command's path.
+    fn refresh(&self, extension: &mut VulneraExtension) {
+        extension.refresh_adapter(&self.host(), &self.ctx(), &CORE_ADAPTER)
+    }
+
+    fn requests(&self) -> usize {
+        self.http.requested.borrow().len()
+    }
+}
+
+fn binary(version: &str) -> String {
+    format!("server/{}/vulnera-adapter", version)
+}
+
+#[test]
+fn override_path_skips_resolution() {
+    let sim = Sim::new().env("VULNERA_ADAPTER_PATH", "/opt/vulnera/vulnera-adapter");
+    assert_eq!(
+        sim.launch(&mut VulneraExtension::new()),
+        Ok("/opt/vulnera/vulnera-adapter".to_string())
+    );
+    assert_eq!(sim.requests(), 0);
+    // Only the override's first use and the task launcher are written.
+    let written: Vec<String> = sim.fs.files.borrow().keys().cloned().collect();
+    assert_eq!(written, ["bin/vulnera-adapter", "state.json"]);
+    assert!(
+        sim.fs
+            .file("state.json")
+            .is_some_and(|state| state.contains("/opt/vulnera/vulnera-adapter"))
+    );
+    assert!(
+        sim.fs
+            .file("bin/vulnera-adapter")
+            .is_some_and(|launcher| launcher.contains("'/opt/vulnera/vulnera-adapter'"))
+    );
+}
+
+#[test]
+fn launches_with_the_user_environment() {
+    let sim = Sim::new()
+        .cached("0.3.0", 60)
+        .installed("0.3.0")
+        .env("PATH", "/usr/bin")
+        .env("VULNERA_API_KEY", "vk_live_123456")
+        .env("UNRELATED", "x");
+    let command = sim.start(&mut VulneraExtension::new()).unwrap();
+    assert_eq!(command.command, binary("0.3.0"));
+    let has = |key: &str, value: &str| command.env.contains(&(key.to_string(), value.to_string()));
+    assert!(has("PATH", "/usr/bin"), "{:?}", command.env);
+    assert!(has("VULNERA_API_KEY", "vk_live_123456"));
+    assert!(!command.env.iter().any(|(k, _)| k == "UNRELATED"));
+
+    // Offline, untrusted worktrees never receive the API key.
+    let command = sim.untrusted().start(&mut VulneraExtension::new()).unwrap();
+    assert!(!command.env.iter().any(|(k, _)| k == "VULNERA_API_KEY"));
+    assert!(
+        command
+            .env
+            .contains(&("VULNERA_OFFLINE_DB".to_string(), "/data/osv".to_string()))
+    );
+}
+
+#[test]
+fn repository_dotenv_only_sets_scan_options() {
+    let sim = Sim::new()
+        .cached("0.3.0", 60)
+        .installed("0.3.0")
+        .project_file(
+            ".env",
+            "VULNERA_ADAPTER_PATH=/tmp/evil\nVULNERA_OFFLINE_DB=osv-db\n",
+        );
+    let command = sim.start(&mut VulneraExtension::new()).unwrap();
+    assert_eq!(command.command, binary("0.3.0"));
+    assert!(command.env.contains(&(
+        "VULNERA_OFFLINE_DB".to_string(),
+        "/work/project/osv-db".to_string()
+    )));
+
+    // A worktree the user lists in `trusted_paths` configures itself.
+    let trusted = sim.setting("trusted_paths", json!(["/work/project"]));
+    let command = trusted.start(&mut VulneraExtension::new()).unwrap();
+    assert_eq!(command.command, "/tmp/evil");
+}
+
+#[test]
+fn malformed_version_pins_fail_before_any_lookup() {
+    let sim = Sim::new().env("VULNERA_ADAPTER_VERSION", "latest");
+    let error = sim.start(&mut VulneraExtension::new()).unwrap_err();
+    assert!(error.starts_with("Vulnera [VUL-301]"), "{}", error);
+    assert_eq!(sim.requests(), 0);
+}
+
+#[test]
+fn mode_servers_idle_without_their_marker_files() {
+    let sim = Sim::new().cached("0.3.0", 60).installed("0.3.0");
+    let mut extension = VulneraExtension::new();
+    let idle = sim.start_mode(&mut extension, &IAC).unwrap();
+    assert_eq!(idle.command, "sh");
+    assert_eq!(sim.requests(), 0);
+
+    let sim = sim.project_file("main.tf", "");
+    let command = sim.start_mode(&mut extension, &IAC).unwrap();
+    assert_eq!(command.command, binary("0.3.0"));
+    assert_eq!(command.args, ["--mode", "iac"]);
+}
+
+#[test]
+fn core_server_idles_without_a_manifest() {
+    let mut sim = Sim::new().cached("0.3.0", 60).installed("0.3.0");
+    sim.worktree.files.clear();
+    let mut extension = VulneraExtension::new();
+    let idle = sim.start(&mut extension).unwrap();
+    assert_eq!(idle.command, "sh");
+    assert_eq!(sim.requests(), 0);
+
+    let sim = sim.setting("always_start", json!(true));
+    assert_eq!(sim.launch(&mut extension), Ok(binary("0.3.0")));
+}
+
+#[test]
+fn untrusted_worktrees_run_the_managed_adapter() {
+    let sim = Sim::new()
+        .env("VULNERA_ADAPTER_PATH", "/opt/vulnera/vulnera-adapter")
+        .cached("0.3.0", 60)
+        .installed("0.3.0")
+        .untrusted();
+    assert_eq!(
+        sim.launch(&mut VulneraExtension::new()),
+        Ok(binary("0.3.0"))
+    );
+}
+
+#[test]
+fn fresh_cache_launches_the_installed_binary_offline() {
+    let sim = Sim::new().cached("0.3.0", 60).installed("0.3.0");
+    assert_eq!(
+        sim.launch(&mut VulneraExtension::new()),
+        Ok(binary("0.3.0"))
+    );
+    assert_eq!(sim.requests(), 0);
+}
+
+#[test]
+fn stale_cache_is_used_when_github_is_unreachable() {
+    let sim = Sim::new()
+        .cached("0.3.0", 7 * 24 * 60 * 60)
+        .installed("0.3.0");
+    let mut extension = VulneraExtension::new();
+    assert_eq!(sim.launch(&mut extension), Ok(binary("0.3.0")));
+    assert_eq!(sim.requests(), 0, "the launch must not wait for GitHub");
+    sim.refresh(&mut extension);
+    assert!(sim.requests() > 0, "an expired cache should be refreshed");
+
+    // After a restart GitHub is not asked again right away.
+    let requests = sim.requests();
+    let mut extension = VulneraExtension::new();
+    assert_eq!(sim.launch(&mut extension), Ok(binary("0.3.0")));
+    sim.refresh(&mut extension);
+    assert_eq!(sim.requests(), requests);
+}
+
+#[test]
+fn new_release_found_after_launch_is_installed_on_the_next_start() {
+    let sim = Sim::new()
+        .cached("0.3.0", 7 * 24 * 60 * 60)
+        .installed("0.3.0")
+        .publish("0.4.0", b"\x7fELF\x02\x01\x01");
+    let mut extension = VulneraExtension::new();
+    assert_eq!(sim.launch(&mut extension), Ok(binary("0.3.0")));
+    assert_eq!(sim.requests(), 0);
+
+    // The check runs in the configuration hook, the download does not.
+    sim.refresh(&mut extension);
+    assert!(sim.requests() > 0);
+    assert!(sim.fs.file(&binary("0.4.0")).is_none());
+    assert_eq!(
+        extension.adapter_update(&sim.host(), &sim.ctx(), &CORE_ADAPTER),
+        None
+    );
+
+    assert_eq!(sim.launch(&mut extension), Ok(binary("0.4.0")));
+    assert_eq!(
+        extension.adapter_update(&sim.host(), &sim.ctx(), &CORE_ADAPTER),
+        None
+    );
+}
+
+#[test]
+fn only_the_update_check_and_a_pending_install_reach_the_network() {
+    let sim = Sim::new()
+        .cached("0.3.0", 7 * 24 * 60 * 60)
+        .installed("0.3.0")
+        .publish("0.4.0", b"\x7fELF\x02\x01\x01")
+        .requires_extension("0.4.0", "0.0.1");
+    let mut extension = VulneraExtension::new();
+    let downloads = || sim.fs.downloaded.borrow().clone();
+
+    // Launching with something installed reads local state only.
+    assert_eq!(sim.launch(&mut extension), Ok(binary("0.3.0")));
+    assert_eq!(sim.requests(), 0);
+
+    // The configuration hook looks up the release, its compat.json and its
+    // notes, and downloads nothing.
+    sim.refresh(&mut extension);
+    assert_eq!(
+        *sim.http.requested.borrow(),
+        [
+            "vulnera-rs/adapter",
+            "https://dl/adapter-v0.4.0/compat.json",
+            "https://api.github.com/repos/vulnera-rs/adapter/releases/tags/adapter-v0.4.0",
+        ]
+    );
+    assert!(downloads().is_empty());
+    // Within the version cache's lifetime it does not look again.
+    sim.refresh(&mut extension);
+    assert_eq!(sim.requests(), 3);
+
+    // The next launch downloads the update it found, and looks nothing up.
+    assert_eq!(sim.launch(&mut extension), Ok(binary("0.4.0")));
+    assert_eq!(sim.requests(), 3);
+    assert_eq!(
+        downloads(),
+        [format!("https://dl/adapter-v0.4.0/{}", ASSET)]
+    );
+
+    // After that, launches are local again.
+    assert_eq!(sim.launch(&mut extension), Ok(binary("0.4.0")));
+    sim.refresh(&mut extension);
+    assert_eq!(sim.requests(), 3);
+    assert_eq!(downloads().len(), 1);
+}
+
+#[test]
+fn failed_lookups_never_downgrade_the_running_server() {
+    // No version cache and GitHub unreachable:
+    // the lookup falls back to the minimum version.
+    let sim = Sim::new().installed("0.3.0");
+    let mut extension = VulneraExtension::new();
+    assert_eq!(sim.launch(&mut extension), Ok(binary("0.3.0")));
+    sim.refresh(&mut extension);
+    assert_eq!(sim.launch(&mut extension), Ok(binary("0.3.0")));
+    assert!(sim.fs.file("server/last-install-error.txt").is_none());
+    assert!(sim.fs.file("server/last-update.txt").is_none());
+    assert_eq!(
+        extension.adapter_update(&sim.host(), &sim.ctx(), &CORE_ADAPTER),
+        None
+    );
+
+    // Nor does a recorded downgrade ask it to restart.
+    let sim = sim
+        .installed("0.1.1")
+        .with_file("server/last-update.txt", b"0.3.0\n0.1.1");
+    assert_eq!(
+        extension.adapter_update(&sim.host(), &sim.ctx(), &CORE_ADAPTER),
+        None
+    );
+}
+
+#[test]
+fn downloads_the_latest_release_once_per_session() {
+    let sim = Sim::new().publish("0.4.0", b"\x7fELF\x02\x01\x01");
+    let mut extension = VulneraExtension::new();
+    assert_eq!(sim.launch(&mut extension), Ok(binary("0.4.0")));
+    assert_eq!(
+        sim.fs.file("server/0.4.0/installed-version.txt").as_deref(),
+        Some("0.4.0")
+    );
+    assert_eq!(
+        sim.fs.file("server/cached-version.txt").as_deref(),
+        Some("0.4.0")
+    );
+    // The asset list came with the latest release; it is not looked up again.
+    assert_eq!(*sim.http.requested.borrow(), ["vulnera-rs/adapter"]);
+
+    // A restart of the server in the same session reuses the memoized result.
+    let requests = sim.requests();
+    assert_eq!(sim.launch(&mut extension), Ok(binary("0.4.0")));
+    assert_eq!(sim.requests(), requests);
+}
+
+#[test]
+fn rolls_back_a_corrupt_download() {
+    let sim = Sim::new()
+        .publish("0.4.0", b"<!DOCTYPE html><title>Not Found</title>")
+        .installed("0.3.0");
+    let mut extension = VulneraExtension::new();
+    assert_eq!(sim.launch(&mut extension), Ok(binary("0.3.0")));
+    sim.refresh(&mut extension);
+    assert_eq!(sim.launch(&mut extension), Ok(binary("0.3.0")));
+    assert!(sim.fs.file(&binary("0.4.0")).is_none());
+    let record = sim.fs.file("server/last-install-error.txt").unwrap();
+    assert!(record.contains("0.4.0"), "{}", record);
+    assert!(record.contains("Vulnera [VUL-104]"), "{}", record);
+    // The earlier install is left alone, and keeps being launched.
+    assert!(sim.fs.file(&binary("0.3.0")).is_some());
+    assert_eq!(sim.launch(&mut extension), Ok(binary("0.3.0")));
+    assert_eq!(
+        extension.adapter_update(&sim.host(), &sim.ctx(), &CORE_ADAPTER),
+        None
+    );
+}
+
+#[test]
+fn crash_looping_update_falls_back_to_the_previous_version() {
+    let sim = Sim::new()
+        .cached("0.4.0", 60)
+        .installed("0.3.0")
+        .installed("0.4.0")
+        .with_file("server/last-update.txt", b"0.3.0\n0.4.0");
+    let mut extension = VulneraExtension::new();
+    for _ in 1..crate::crash_loop::CRASH_LOOP_LAUNCHES {
+        assert_eq!(sim.launch(&mut extension), Ok(binary("0.4.0")));
+    }
+    assert_eq!(sim.launch(&mut extension), Ok(binary("0.3.0")));
+    assert_eq!(
+        sim.fs.file("server/bad-versions.txt").as_deref(),
+        Some("0.4.0\n")
+    );
+    // The bad release stays skipped, in this session and the next.
+    assert_eq!(sim.launch(&mut extension), Ok(binary("0.3.0")));
+    assert_eq!(
+        sim.launch(&mut VulneraExtension::new()),
+        Ok(binary("0.3.0"))
+    );
+
+    // A pin overrides the flag, and lifts it for later unpinned launches.
+    let pinned = sim.env("VULNERA_ADAPTER_VERSION", "0.4.0");
+    assert_eq!(
+        pinned.launch(&mut VulneraExtension::new()),
+        Ok(binary("0.4.0"))
+    );
+    assert_eq!(
+        pinned.fs.file("server/bad-versions.txt").as_deref(),
+        Some("")
+    );
+}
+
+#[test]
+fn restarts_of_an_established_release_are_not_a_crash_loop() {
+    let first_launch = format!(
+        r#"{{"schema": 1, "last_launch": {{"server": {{"version": "0.4.0", "at": {}}}}}}}"#,
+        NOW - crate::crash_loop::FRESH_INSTALL_SECS
+    );
+    let sim = Sim::new()
+        .cached("0.4.0", 60)
+        .installed("0.3.0")
+        .installed("0.4.0")
+        .with_file("server/last-update.txt", b"0.3.0\n0.4.0")
+        .with_file(crate::state::STATE_FILE, first_launch.as_bytes());
+    let mut extension = VulneraExtension::new();
+    for _ in 0..crate::crash_loop::CRASH_LOOP_LAUNCHES * 2 {
+        assert_eq!(sim.launch(&mut extension), Ok(binary("0.4.0")));
+    }
+    assert!(sim.fs.file("server/bad-versions.txt").is_none());
+}
+
+#[test]
+fn update_installed_mid_session_asks_the_running_server_to_restart() {
+    let sim = Sim::new().cached("0.3.0", 60).installed("0.3.0");
+    let mut extension = VulneraExtension::new();
+    assert_eq!(sim.launch(&mut extension), Ok(binary("0.3.0")));
+    assert_eq!(
+        extension.adapter_update(&sim.host(), &sim.ctx(), &CORE_ADAPTER),
+        None
+    );
+
+    // `/vulnera-update` installs 0.4.0 while 0.3.0 keeps running.
+    let sim = sim
+        .installed("0.4.0")
+        .with_file("server/last-update.txt", b"0.3.0\n0.4.0");
+    assert_eq!(
+        extension.adapter_update(&sim.host(), &sim.ctx(), &CORE_ADAPTER),
+        Some(serde_json::json!({ "version": "0.4.0", "restart": "idle" }))
+    );
+    let mut ctx = sim.ctx();
+    ctx.settings.update_restart = UpdateRestart::Never;
+    assert_eq!(
+        extension.adapter_update(&sim.host(), &ctx, &CORE_ADAPTER),
+        None
+    );
+
+    // Pins keep the server where it is.
+    let pinned = sim.env("VULNERA_ADAPTER_VERSION", "0.3.0");
+    assert_eq!(
+        extension.adapter_update(&pinned.host(), &pinned.ctx(), &CORE_ADAPTER),
+        None
+    );
+}
+
+#[test]
+fn releases_requiring_a_newer_extension_are_not_installed() {
+    let sim = Sim::new()
+        .publish("0.4.0", b"\x7fELF\x02\x01\x01")
+        .requires_extension("0.4.0", "99.0.0");
+    let error = sim.launch(&mut VulneraExtension::new()).unwrap_err();
+    assert!(error.starts_with("Vulnera [VUL-107]"), "{}", error);
+    assert!(error.contains("Update the Vulnera extension"), "{}", error);
+    assert!(sim.fs.file(&binary("0.4.0")).is_none());
+
+    // With an older adapter installed, it keeps running.
+    let sim = sim.cached("0.3.0", 7 * 24 * 60 * 60).installed("0.3.0");
+    let mut extension = VulneraExtension::new();
+    assert_eq!(sim.launch(&mut extension), Ok(binary("0.3.0")));
+    sim.refresh(&mut extension);
+    assert_eq!(sim.launch(&mut extension), Ok(binary("0.3.0")));
+    assert!(sim.fs.file(&binary("0.4.0")).is_none());
+    assert_eq!(
+        extension.adapter_update(&sim.host(), &sim.ctx(), &CORE_ADAPTER),
+        None
+    );
+
+    // An explicit pin is the user's call.
+    let pinned = sim.env("VULNERA_ADAPTER_VERSION", "0.4.0");
+    assert_eq!(
+        pinned.launch(&mut VulneraExtension::new()),
+        Ok(binary("0.4.0"))
+    );
+}
+
+#[test]
+fn fresh_installs_fall_back_to_the_newest_compatible_release() {
+    let sim = Sim::new()
+        .publish("0.4.0", b"\x7fELF\x02\x01\x01")
+        .publish("0.3.0", b"\x7fELF\x02\x01\x01")
+        .requires_extension("0.4.0", "99.0.0")
+        .list_releases();
+    assert_eq!(
+        sim.launch(&mut VulneraExtension::new()),
+        Ok(binary("0.3.0"))
+    );
+    assert!(sim.fs.file(&binary("0.4.0")).is_none());
+}
+
+#[test]
+fn project_settings_cannot_lift_the_trust_requirement() {
+    // Zed merges the repository's `.zed/settings.json` over the user's.
+    let project = r#"{
+        // Checked in by the repository.
+        "lsp": { "vulnera": { "settings": { "require_trust": false, } } }
+    }"#;
+    let sim = Sim::new()
+        .untrusted()
+        .setting("require_trust", json!(false))
+        .project_file(".zed/settings.json", project);
+    assert!(!sim.ctx().trusted);
+
+    let trusted = sim.setting("trusted_paths", json!(["/work"]));
+    assert!(trusted.ctx().trusted);
+}
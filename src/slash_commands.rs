@@ -0,0 +1,825 @@
+//! Assistant slash commands registered in `extension.toml`.
+//!
+//! - `/vulnera-advisory <ID>` — details of a GHSA / CVE / RUSTSEC / … advisory,
+//!   fetched from the public OSV API. `/vulnera-advisory <package>@<version>`
+//!   lists the advisories affecting one of the worktree's locked dependencies.
+//! - `/vulnera-deps` — table of the worktree's vulnerable dependencies per
+//!   manifest, from an adapter scan.
+//! - `/vulnera-fix [severity]` — the version bumps that clear the current
+//!   findings (optionally only those at or above `severity`).
+//! - `/vulnera-file <manifest>` — triage summary for one manifest: counts by
+//!   severity, top advisories and suggested bumps, from a scan of that file only.
+//! - `/vulnera-sarif [path]` — write a SARIF report of the findings into the
+//!   worktree (`vulnera.sarif` by default).
+//! - `/vulnera-doctor` — self-diagnostic report (see the [`doctor`] module).
+//! - `/vulnera-version` — installed and latest adapter versions, cache age and
+//!   version pins.
+//! - `/vulnera-changelog` — release notes of the last adapter update, so a
+//!   change in diagnostics between sessions can be explained.
+//! - `/vulnera-update` — install the newest adapter on the release channel
+//!   now, whatever the version cache or `update_mode` say.
+//! - `/vulnera-uninstall [confirm]` — remove the adapters, caches, markers and
+//!   logs from the extension work dir (see the [`uninstall`] module).
+//! - `/vulnera-task` — `.zed/tasks.json` entries that run the managed adapter
+//!   (see the [`tasks`] module).
+//!
+//! Advisory IDs the extension has seen (looked up or found by a scan) are remembered in
+//! `slash/advisory-ids.txt` and offered as argument completions. Completion gets
+//! no worktree, so the locked dependencies (see [`lockfiles`]) and root
+//! manifests are likewise snapshotted to `slash/dependencies.txt` and
+//! `slash/manifests.txt` whenever a worktree is at hand.
+
+use std::fs;
+
+use zed_extension_api::serde_json::Value;
+use zed_extension_api::{
+    self as zed, Result, SlashCommand, SlashCommandArgumentCompletion, SlashCommandOutput,
+    SlashCommandOutputSection, Worktree,
+};
+
+use crate::LaunchContext;
+use crate::adapters;
+use crate::doctor;
+use crate::error::VulneraError;
+use crate::findings::{self, Finding};
+use crate::host::Host;
+use crate::http::ZedHttp;
+use crate::lockfiles::{self, Dependency};
+use crate::manifests;
+use crate::osv;
+use crate::settings::{Severity, VulneraSettings};
+use crate::tasks;
+use crate::uninstall;
+
+/// Most recent advisory IDs offered as completions.
+const MAX_REMEMBERED_IDS: usize = 50;
+
+/// Most dependency completions offered at once.
+const MAX_DEPENDENCY_COMPLETIONS: usize = 50;
+
+fn known_ids_path() -> &'static str {
+    "slash/advisory-ids.txt"
+}
+
+fn dependencies_path() -> &'static str {
+    "slash/dependencies.txt"
+}
+
+fn manifests_path() -> &'static str {
+    "slash/manifests.txt"
+}
+
+/// Advisories listed in a `/vulnera-file` summary.
+const TOP_ADVISORIES: usize = 5;
+
+/// Run the slash command `command` with `args`.
+pub fn run(
+    command: &SlashCommand,
+    args: &[String],
+    worktree: Option<&Worktree>,
+) -> Result<SlashCommandOutput> {
+    if let Some(worktree) = worktree {
+        remember_dependencies(worktree);
+    }
+    match command.name.as_str() {
+        "vulnera-advisory" => advisory(args),
+        "vulnera-deps" => deps(require_worktree(worktree, command)?),
+        "vulnera-fix" => fix(require_worktree(worktree, command)?, args),
+        "vulnera-file" => file(require_worktree(worktree, command)?, args),
+        "vulnera-sarif" => sarif(require_worktree(worktree, command)?, args),
+        "vulnera-doctor" => Ok(sectioned_output(vec![(
+            "Vulnera doctor".to_string(),
+            doctor::report(worktree),
+        )])),
+        "vulnera-version" => Ok(sectioned_output(vec![(
+            "Vulnera versions".to_string(),
+            doctor::version_report(worktree),
+        )])),
+        "vulnera-update" => Ok(sectioned_output(vec![(
+            "Vulnera update".to_string(),
+            update(worktree)?,
+        )])),
+        "vulnera-uninstall" => Ok(sectioned_output(vec![(
+            "Vulnera uninstall".to_string(),
+            uninstall::run(&Host::REAL, args),
+        )])),
+        "vulnera-changelog" => Ok(sectioned_output(vec![(
+            "Vulnera changelog".to_string(),
+            changelog(),
+        )])),
+        "vulnera-task" => Ok(sectioned_output(vec![(
+            "Vulnera tasks".to_string(),
+            format!(
+                "Add to `.zed/tasks.json`:\n\n```json\n{}```\n",
+                tasks::render(crate::is_windows(), &task_settings(worktree))?
+            ),
+        )])),
+        name => Err(VulneraError::Internal {
+            reason: format!("unknown slash command '{}'", name),
+        }
+        .into()),
+    }
+}
+
+/// Argument completions for `command`, filtered by the partial `args`.
+pub fn complete(
+    command: &SlashCommand,
+    args: &[String],
+) -> Result<Vec<SlashCommandArgumentCompletion>> {
+    match command.name.as_str() {
+        "vulnera-advisory" => {
+            let typed = args
+                .first()
+                .map(|a| a.to_ascii_uppercase())
+                .unwrap_or_default();
+            let ids = read_known_ids()
+                .into_iter()
+                .filter(|id| id.to_ascii_uppercase().starts_with(&typed))
+                .map(|id| SlashCommandArgumentCompletion {
+                    label: id.clone(),
+                    new_text: id,
+                    run_command: true,
+                });
+            let dependencies = read_dependencies()
+                .into_iter()
+                .map(|dep| (format!("{}@{}", dep.name, dep.version), dep.ecosystem))
+                .filter(|(spec, _)| spec.to_ascii_uppercase().starts_with(&typed))
+                .take(MAX_DEPENDENCY_COMPLETIONS)
+                .map(|(spec, ecosystem)| SlashCommandArgumentCompletion {
+                    label: format!("{} ({})", spec, ecosystem),
+                    new_text: spec,
+                    run_command: true,
+                });
+            Ok(ids.chain(dependencies).collect())
+        }
+        "vulnera-file" => {
+            let typed = args.first().map(String::as_str).unwrap_or_default();
+            Ok(read_lines(manifests_path())
+                .into_iter()
+                .filter(|manifest| manifest.starts_with(typed))
+                .map(|manifest| SlashCommandArgumentCompletion {
+                    label: manifest.clone(),
+                    new_text: manifest,
+                    run_command: true,
+                })
+                .collect())
+        }
+        "vulnera-fix" => Ok(Severity::ALL
+            .iter()
+            .map(|severity| SlashCommandArgumentCompletion {
+                label: format!("{} and above", severity.as_str()),
+                new_text: severity.as_str().to_string(),
+                run_command: true,
+            })
+            .collect()),
+        "vulnera-uninstall" => Ok(vec![SlashCommandArgumentCompletion {
+            label: "confirm (remove the files)".to_string(),
+            new_text: "confirm".to_string(),
+            run_command: true,
+        }]),
+        _ => Ok(Vec::new()),
+    }
+}
+
+/// Settings for rendering tasks; defaults without a worktree.
+fn task_settings(worktree: Option<&Worktree>) -> VulneraSettings {
+    worktree
+        .and_then(|w| VulneraSettings::for_worktree(w).ok())
+        .unwrap_or_default()
+}
+
+fn require_worktree<'a>(
+    worktree: Option<&'a Worktree>,
+    command: &SlashCommand,
+) -> Result<&'a Worktree> {
+    worktree.ok_or_else(|| {
+        VulneraError::NoProject {
+            command: command.name.clone(),
+        }
+        .into()
+    })
+}
+
+fn bad_argument(command: &str, reason: impl Into<String>) -> String {
+    VulneraError::BadArgument {
+        command: command.to_string(),
+        reason: reason.into(),
+    }
+    .into()
+}
+
+/// Scan `worktree`, remembering the advisory IDs for completion.
+fn scan(worktree: &Worktree) -> Result<Vec<Finding>> {
+    let findings = findings::scan(worktree)?;
+    remember_ids(findings.iter().map(|f| f.advisory.clone()));
+    Ok(findings)
+}
+
+/// Output with one section per `(label, text)` part.
+fn sectioned_output(parts: Vec<(String, String)>) -> SlashCommandOutput {
+    let mut text = String::new();
+    let mut sections = Vec::new();
+    for (label, part) in parts {
+        let start = text.len() as u32;
+        text.push_str(&part);
+        sections.push(SlashCommandOutputSection {
+            range: (start..text.len() as u32).into(),
+            label,
+        });
+    }
+    SlashCommandOutput { text, sections }
+}
+
+// ── /vulnera-update ───────────────────────────────────────────────────────────
+
+fn update(worktree: Option<&Worktree>) -> Result<String> {
+    let (os, arch) = zed::current_platform();
+    let platform = adapters::resolve_platform(os, arch)?;
+    let ctx = worktree.and_then(|w| LaunchContext::for_worktree(w).ok());
+    let spec = match &ctx {
+        Some(ctx) => ctx.on_channel(&adapters::CORE_ADAPTER),
+        None => adapters::CORE_ADAPTER,
+    };
+    let host = ctx.as_ref().map_or(Host::REAL, |ctx| ctx.host(&Host::REAL));
+    let (previous, latest) = adapters::update_now(&host, &spec, &platform)?;
+
+    let mut out = match previous {
+        Some(previous) if previous == latest => format!(
+            "{} {} is the latest {} release and is installed.\n",
+            adapters::CORE_ADAPTER.name,
+            latest,
+            spec.channel
+        ),
+        Some(previous) => format!(
+            "Installed {} {} (previously {}). Running servers switch to it as set by \
+             `update_restart` (by default once idle), or run `editor: restart language \
+             server`; `/vulnera-changelog` shows what changed.\n",
+            adapters::CORE_ADAPTER.name,
+            latest,
+            previous
+        ),
+        None => format!(
+            "Installed {} {}. Run `editor: restart language server` to start it.\n",
+            adapters::CORE_ADAPTER.name,
+            latest
+        ),
+    };
+    let pin = ctx.and_then(|ctx| ctx.version_pin(&adapters::CORE_ADAPTER));
+    if let Some(pin) = pin {
+        out.push_str(&format!(
+            "\nNote: this project pins the adapter to {} ({}), which still takes precedence.\n",
+            pin.0, pin.1
+        ));
+    }
+    Ok(out)
+}
+
+// ── /vulnera-changelog ────────────────────────────────────────────────────────
+
+fn changelog() -> String {
+    let mut out = String::new();
+    for spec in adapters::ALL {
+        let Some(update) = adapters::last_update(&Host::REAL, spec) else {
+            continue;
+        };
+        out.push_str(&format!(
+            "## {} {} (updated from {})\n\n{}\n\n",
+            spec.name,
+            update.to,
+            update.from,
+            update
+                .notes
+                .as_deref()
+                .map(str::trim)
+                .unwrap_or("No release notes were published for this version.")
+        ));
+    }
+    if out.is_empty() {
+        out.push_str("No adapter update has been installed yet.\n");
+    }
+    out
+}
+
+// ── /vulnera-advisory ─────────────────────────────────────────────────────────
+
+fn advisory(args: &[String]) -> Result<SlashCommandOutput> {
+    let id = args
+        .first()
+        .map(|a| a.trim())
+        .filter(|a| !a.is_empty())
+        .ok_or_else(|| {
+            bad_argument(
+                "vulnera-advisory",
+                "usage: /vulnera-advisory <GHSA-…|CVE-…|RUSTSEC-…|package@version>",
+            )
+        })?;
+    // `rsplit` keeps scoped npm names (`@scope/pkg@1.0.0`) intact.
+    if let Some((name, version)) = id.rsplit_once('@')
+        && !name.is_empty()
+    {
+        return package_advisories(name, version);
+    }
+    if !id
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | ':' | '.'))
+    {
+        return Err(bad_argument(
+            "vulnera-advisory",
+            format!("'{}' is not an advisory ID", id),
+        ));
+    }
+
+    let advisory = osv::advisory(&ZedHttp, id)?;
+
+    let canonical = advisory["id"].as_str().unwrap_or(id).to_string();
+    remember_ids(std::iter::once(canonical.clone()));
+
+    let text = render_advisory(&advisory);
+    Ok(SlashCommandOutput {
+        sections: vec![SlashCommandOutputSection {
+            range: (0..text.len() as u32).into(),
+            label: format!("Advisory {}", canonical),
+        }],
+        text,
+    })
+}
+
+/// Advisories affecting `name@version`, resolved against the remembered
+/// lockfile dependencies to find the package's ecosystem.
+fn package_advisories(name: &str, version: &str) -> Result<SlashCommandOutput> {
+    let known = read_dependencies();
+    let dep = known
+        .iter()
+        .find(|d| d.name == name && d.version == version)
+        .or_else(|| known.iter().find(|d| d.name == name))
+        .ok_or_else(|| {
+            bad_argument(
+                "vulnera-advisory",
+                format!("'{}' is not a locked dependency of the open project", name),
+            )
+        })?;
+
+    let vulns = osv::query(&ZedHttp, name, dep.osv_ecosystem(), Some(version))?;
+    remember_ids(
+        vulns
+            .iter()
+            .filter_map(|v| v["id"].as_str().map(str::to_string)),
+    );
+
+    let text = render_package_advisories(name, version, dep.osv_ecosystem(), &vulns);
+    Ok(sectioned_output(vec![(
+        format!("Advisories for {}@{}", name, version),
+        text,
+    )]))
+}
+
+/// One line per advisory affecting a package version.
+fn render_package_advisories(
+    name: &str,
+    version: &str,
+    ecosystem: &str,
+    vulns: &[Value],
+) -> String {
+    let mut out = format!("## {}@{} ({})\n\n", name, version, ecosystem);
+    if vulns.is_empty() {
+        out.push_str("No known advisories.\n");
+    }
+    for vuln in vulns {
+        out.push_str(&format!(
+            "- **{}**: {}\n",
+            vuln["id"].as_str().unwrap_or("?"),
+            vuln["summary"].as_str().unwrap_or("no summary"),
+        ));
+    }
+    out
+}
+
+/// Render an OSV advisory as Markdown: summary, description, affected
+/// packages with their vulnerable ranges and fixed versions, references.
+pub fn render_advisory(advisory: &Value) -> String {
+    let mut out = format!("## {}", advisory["id"].as_str().unwrap_or("Advisory"));
+    if let Some(summary) = advisory["summary"].as_str() {
+        out.push_str(&format!(": {}", summary));
+    }
+    out.push('\n');
+
+    let aliases = string_list(&advisory["aliases"]);
+    if !aliases.is_empty() {
+        out.push_str(&format!("\nAliases: {}\n", aliases.join(", ")));
+    }
+    if let Some(details) = advisory["details"].as_str() {
+        out.push_str(&format!("\n{}\n", details.trim()));
+    }
+
+    let affected = advisory["affected"].as_array().cloned().unwrap_or_default();
+    if !affected.is_empty() {
+        out.push_str("\n### Affected\n\n");
+        for entry in &affected {
+            let package = &entry["package"];
+            out.push_str(&format!(
+                "- {} ({}): {}\n",
+                package["name"].as_str().unwrap_or("?"),
+                package["ecosystem"].as_str().unwrap_or("?"),
+                describe_ranges(&entry["ranges"]),
+            ));
+        }
+    }
+
+    let references: Vec<&str> = advisory["references"]
+        .as_array()
+        .map(|refs| refs.iter().filter_map(|r| r["url"].as_str()).collect())
+        .unwrap_or_default();
+    if !references.is_empty() {
+        out.push_str("\n### References\n\n");
+        for url in references.iter().take(5) {
+            out.push_str(&format!("- {}\n", url));
+        }
+    }
+
+    out
+}
+
+/// Summarise OSV range events as `>= 1.0.0, < 1.2.3 (fixed in 1.2.3)`.
+fn describe_ranges(ranges: &Value) -> String {
+    let mut parts = Vec::new();
+    for range in ranges.as_array().into_iter().flatten() {
+        for event in range["events"].as_array().into_iter().flatten() {
+            if let Some(v) = event["introduced"].as_str() {
+                parts.push(if v == "0" {
+                    "all versions".to_string()
+                } else {
+                    format!(">= {}", v)
+                });
+            } else if let Some(v) = event["fixed"].as_str() {
+                parts.push(format!("< {} (fixed in {})", v, v));
+            } else if let Some(v) = event["last_affected"].as_str() {
+                parts.push(format!("<= {}", v));
+            }
+        }
+    }
+    if parts.is_empty() {
+        "see advisory for versions".to_string()
+    } else {
+        parts.join(", ")
+    }
+}
+
+fn string_list(value: &Value) -> Vec<&str> {
+    value
+        .as_array()
+        .map(|items| items.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default()
+}
+
+// ── /vulnera-deps ─────────────────────────────────────────────────────────────
+
+fn deps(worktree: &Worktree) -> Result<SlashCommandOutput> {
+    let findings = scan(worktree)?;
+    if findings.is_empty() {
+        return Ok(sectioned_output(vec![(
+            "Vulnerable dependencies".to_string(),
+            "No vulnerable dependencies found.\n".to_string(),
+        )]));
+    }
+
+    let parts = findings::by_manifest(&findings)
+        .into_iter()
+        .map(|(manifest, deps)| {
+            let mut table = format!(
+                "### {}\n\n| Package | Version | Severity | Fixed in | Advisories |\n|---|---|---|---|---|\n",
+                manifest
+            );
+            for dep in &deps {
+                table.push_str(&format!(
+                    "| {} | {} | {} | {} | {} |\n",
+                    dep.package,
+                    dep.version,
+                    dep.worst_severity.as_str(),
+                    dep.fixed_version.as_deref().unwrap_or("no fix"),
+                    dep.advisories.join(", ")
+                ));
+            }
+            table.push('\n');
+            (format!("Vulnerable dependencies: {}", manifest), table)
+        })
+        .collect();
+    Ok(sectioned_output(parts))
+}
+
+// ── /vulnera-fix ──────────────────────────────────────────────────────────────
+
+fn fix(worktree: &Worktree, args: &[String]) -> Result<SlashCommandOutput> {
+    let threshold = match args.first() {
+        None => Severity::Low,
+        Some(arg) => Severity::parse(arg).ok_or_else(|| {
+            bad_argument("vulnera-fix", format!("unknown severity '{}'", arg.trim()))
+        })?,
+    };
+
+    let findings: Vec<Finding> = scan(worktree)?
+        .into_iter()
+        .filter(|f| f.severity <= threshold)
+        .collect();
+    let text = render_upgrade_plan(&findings);
+    Ok(sectioned_output(vec![("Upgrade plan".to_string(), text)]))
+}
+
+/// One bump per vulnerable dependency — to the lowest version that fixes all
+/// of its advisories — plus the dependencies that have no fix yet.
+fn render_upgrade_plan(findings: &[Finding]) -> String {
+    if findings.is_empty() {
+        return "No findings to fix.\n".to_string();
+    }
+
+    let mut plan = String::from("## Upgrade plan\n");
+    let mut unfixable = Vec::new();
+    for (manifest, deps) in findings::by_manifest(findings) {
+        let mut bumps = String::new();
+        for dep in deps {
+            match &dep.fixed_version {
+                Some(fixed) => bumps.push_str(&format!(
+                    "- `{}` {} → {} ({}; clears {})\n",
+                    dep.package,
+                    dep.version,
+                    fixed,
+                    dep.worst_severity.as_str(),
+                    dep.advisories.join(", ")
+                )),
+                None => unfixable.push(format!(
+                    "- `{}` {} in {} ({})\n",
+                    dep.package,
+                    dep.version,
+                    manifest,
+                    dep.advisories.join(", ")
+                )),
+            }
+        }
+        if !bumps.is_empty() {
+            plan.push_str(&format!("\n### {}\n\n{}", manifest, bumps));
+        }
+    }
+
+    if !unfixable.is_empty() {
+        plan.push_str("\n### No fixed version yet\n\nConsider replacing or removing:\n\n");
+        plan.extend(unfixable);
+    }
+    plan
+}
+
+// ── /vulnera-file ─────────────────────────────────────────────────────────────
+
+fn file(worktree: &Worktree, args: &[String]) -> Result<SlashCommandOutput> {
+    let manifest = args
+        .first()
+        .map(|a| a.trim())
+        .filter(|a| !a.is_empty())
+        .ok_or_else(|| bad_argument("vulnera-file", "usage: /vulnera-file <manifest>"))?;
+    let findings = findings::scan_file(worktree, manifest)?;
+    remember_ids(findings.iter().map(|f| f.advisory.clone()));
+    Ok(sectioned_output(vec![(
+        format!("Findings: {}", manifest),
+        render_file_summary(manifest, &findings),
+    )]))
+}
+
+/// Severity counts, the most severe advisories and the suggested bumps for
+/// one manifest.
+fn render_file_summary(manifest: &str, findings: &[Finding]) -> String {
+    let mut out = format!("## {}\n\n", manifest);
+    if findings.is_empty() {
+        out.push_str("No findings.\n");
+        return out;
+    }
+
+    let counts: Vec<String> = Severity::ALL
+        .iter()
+        .map(|severity| {
+            let count = findings.iter().filter(|f| f.severity == *severity).count();
+            format!("{} {}", count, severity.as_str())
+        })
+        .collect();
+    out.push_str(&format!("{}\n\n### Top advisories\n\n", counts.join(" · ")));
+
+    let mut top: Vec<&Finding> = findings.iter().collect();
+    top.sort_by(|a, b| {
+        a.severity
+            .cmp(&b.severity)
+            .then_with(|| a.advisory.cmp(&b.advisory))
+    });
+    top.dedup_by(|a, b| a.advisory == b.advisory);
+    for finding in top.iter().take(TOP_ADVISORIES) {
+        out.push_str(&format!(
+            "- {} ({}) in `{}` {}\n",
+            finding.advisory,
+            finding.severity.as_str(),
+            finding.package,
+            finding.version
+        ));
+    }
+    if top.len() > TOP_ADVISORIES {
+        out.push_str(&format!("- … and {} more\n", top.len() - TOP_ADVISORIES));
+    }
+
+    let bumps: Vec<String> = findings::by_manifest(findings)
+        .into_values()
+        .flatten()
+        .filter_map(|dep| {
+            let fixed = dep.fixed_version?;
+            Some(format!("- `{}` {} → {}\n", dep.package, dep.version, fixed))
+        })
+        .collect();
+    if !bumps.is_empty() {
+        out.push_str("\n### Suggested bumps\n\n");
+        out.extend(bumps);
+    }
+    out
+}
+
+// ── /vulnera-sarif ────────────────────────────────────────────────────────────
+
+fn sarif(worktree: &Worktree, args: &[String]) -> Result<SlashCommandOutput> {
+    let output = args
+        .first()
+        .map(|a| a.trim())
+        .filter(|a| !a.is_empty())
+        .unwrap_or(findings::DEFAULT_SARIF_OUTPUT);
+    let text = match findings::export_sarif(worktree, output)? {
+        Some(count) => format!("Wrote {} SARIF results to `{}`.\n", count, output),
+        None => format!("Wrote a SARIF report to `{}`.\n", output),
+    };
+    Ok(sectioned_output(vec![("SARIF report".to_string(), text)]))
+}
+
+// ── Known advisory IDs ────────────────────────────────────────────────────────
+
+fn read_known_ids() -> Vec<String> {
+    read_lines(known_ids_path())
+}
+
+/// Move `ids` to the front of the remembered list.
+fn remember_ids(ids: impl IntoIterator<Item = String>) {
+    let mut known: Vec<String> = ids.into_iter().collect();
+    for id in read_known_ids() {
+        if !known.contains(&id) {
+            known.push(id);
+        }
+    }
+    known.truncate(MAX_REMEMBERED_IDS);
+    write_lines(known_ids_path(), &known, "advisory IDs");
+}
+
+// ── Locked dependencies ───────────────────────────────────────────────────────
+
+/// Snapshot `worktree`'s locked dependencies for argument completion.
+pub fn remember_dependencies(worktree: &Worktree) {
+    write_lines(
+        manifests_path(),
+        &manifests::find_manifests(worktree),
+        "root manifests",
+    );
+
+    let lines: Vec<String> = lockfiles::dependencies(worktree)
+        .into_iter()
+        .map(|dep| format!("{}\t{}\t{}", dep.ecosystem, dep.name, dep.version))
+        .collect();
+    write_lines(dependencies_path(), &lines, "locked dependencies");
+}
+
+fn write_lines(path: &str, lines: &[impl AsRef<str>], what: &str) {
+    let contents: Vec<&str> = lines.iter().map(AsRef::as_ref).collect();
+    if let Err(e) = fs::create_dir_all("slash").and_then(|()| fs::write(path, contents.join("\n")))
+    {
+        warn!("Failed to remember {}: {}", what, e);
+    }
+}
+
+fn read_lines(path: &str) -> Vec<String> {
+    fs::read_to_string(path)
+        .map(|s| {
+            s.lines()
+                .map(str::trim)
+                .filter(|l| !l.is_empty())
+                .map(String::from)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+pub fn read_dependencies() -> Vec<Dependency> {
+    fs::read_to_string(dependencies_path())
+        .map(|s| {
+            s.lines()
+                .filter_map(|line| {
+                    let mut fields = line.split('\t');
+                    Some(Dependency {
+                        ecosystem: fields.next()?.to_string(),
+                        name: fields.next()?.to_string(),
+                        version: fields.next()?.to_string(),
+                    })
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        render_advisory, render_file_summary, render_package_advisories, render_upgrade_plan,
+    };
+    use crate::findings::Finding;
+    use crate::settings::Severity;
+    use zed_extension_api::serde_json::json;
+
+    #[test]
+    fn renders_osv_advisory() {
+        let advisory = json!({
+            "id": "GHSA-jf85-cpcp-j695",
+            "summary": "Prototype Pollution in lodash",
+            "aliases": ["CVE-2019-10744"],
+            "details": "Versions of lodash before 4.17.12 are vulnerable.",
+            "affected": [{
+                "package": { "ecosystem": "npm", "name": "lodash" },
+                "ranges": [{ "type": "SEMVER", "events": [{ "introduced": "0" }, { "fixed": "4.17.12" }] }]
+            }],
+            "references": [{ "type": "ADVISORY", "url": "https://nvd.nist.gov/vuln/detail/CVE-2019-10744" }]
+        });
+
+        assert_eq!(
+            render_advisory(&advisory),
+            "## GHSA-jf85-cpcp-j695: Prototype Pollution in lodash\n\
+             \nAliases: CVE-2019-10744\n\
+             \nVersions of lodash before 4.17.12 are vulnerable.\n\
+             \n### Affected\n\n\
+             - lodash (npm): all versions, < 4.17.12 (fixed in 4.17.12)\n\
+             \n### References\n\n\
+             - https://nvd.nist.gov/vuln/detail/CVE-2019-10744\n"
+        );
+    }
+
+    #[test]
+    fn renders_package_advisories() {
+        let vulns =
+            [json!({ "id": "GHSA-jf85-cpcp-j695", "summary": "Prototype Pollution in lodash" })];
+        assert_eq!(
+            render_package_advisories("lodash", "4.17.10", "npm", &vulns),
+            "## lodash@4.17.10 (npm)\n\n- **GHSA-jf85-cpcp-j695**: Prototype Pollution in lodash\n"
+        );
+        assert_eq!(
+            render_package_advisories("serde", "1.0.219", "crates.io", &[]),
+            "## serde@1.0.219 (crates.io)\n\nNo known advisories.\n"
+        );
+    }
+
+    #[test]
+    fn renders_file_summary() {
+        let finding = |advisory: &str, severity, fixed: Option<&str>| Finding {
+            manifest: "package.json".to_string(),
+            package: "lodash".to_string(),
+            version: "4.17.10".to_string(),
+            advisory: advisory.to_string(),
+            severity,
+            fixed_version: fixed.map(str::to_string),
+        };
+        let findings = [
+            finding("GHSA-p6mc-m468-83gw", Severity::High, Some("4.17.19")),
+            finding("GHSA-jf85-cpcp-j695", Severity::Critical, Some("4.17.12")),
+        ];
+
+        assert_eq!(
+            render_file_summary("package.json", &findings),
+            "## package.json\n\n1 critical · 1 high · 0 medium · 0 low\n\
+             \n### Top advisories\n\n\
+             - GHSA-jf85-cpcp-j695 (critical) in `lodash` 4.17.10\n\
+             - GHSA-p6mc-m468-83gw (high) in `lodash` 4.17.10\n\
+             \n### Suggested bumps\n\n\
+             - `lodash` 4.17.10 → 4.17.19\n"
+        );
+    }
+
+    #[test]
+    fn renders_upgrade_plan() {
+        let finding = |package: &str, advisory: &str, fixed: Option<&str>| Finding {
+            manifest: "Cargo.lock".to_string(),
+            package: package.to_string(),
+            version: "0.1.0".to_string(),
+            advisory: advisory.to_string(),
+            severity: Severity::High,
+            fixed_version: fixed.map(str::to_string),
+        };
+        let findings = [
+            finding("hyper", "RUSTSEC-2021-0078", Some("0.14.10")),
+            finding("hyper", "RUSTSEC-2021-0079", Some("0.14.9")),
+            finding("chrono", "RUSTSEC-2020-0159", None),
+        ];
+
+        assert_eq!(
+            render_upgrade_plan(&findings),
+            "## Upgrade plan\n\
+             \n### Cargo.lock\n\n\
+             - `hyper` 0.1.0 → 0.14.10 (high; clears RUSTSEC-2021-0078, RUSTSEC-2021-0079)\n\
+             \n### No fixed version yet\n\nConsider replacing or removing:\n\n\
+             - `chrono` 0.1.0 in Cargo.lock (RUSTSEC-2020-0159)\n"
+        );
+    }
+}
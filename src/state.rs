@@ -0,0 +1,332 @@
+//! Facts that outlive a Zed session, kept in `state.json` in the work dir.
+//!
+//! - when GitHub was last asked for releases, and whether it answered;
+//! - the last-known-good version of each install directory: one that was
+//!   still being launched [`CRASH_LOOP_WINDOW_SECS`] after its first launch,
+//!   preferred by [`crate::adapters::fallback_version`];
+//! - install directories running their minimum version because nothing newer
+//!   could be resolved, and when that was last warned about;
+//! - user-supplied adapter binaries already seen, so a new one is logged once.
+//!
+//! The file carries a `schema` number. A file that is not JSON, has no
+//! schema or was written by a newer extension is set aside as
+//! `state.json.corrupt` and replaced by a fresh state; losing it only costs
+//! a network round trip or a less informed fallback.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::{Deserialize, Serialize};
+use zed_extension_api::serde_json::{self, Value};
+
+use crate::crash_loop::CRASH_LOOP_WINDOW_SECS;
+use crate::host::Host;
+
+pub const STATE_FILE: &str = "state.json";
+
+pub const CORRUPT_STATE_FILE: &str = "state.json.corrupt";
+
+/// Version of the `state.json` layout written by this extension.
+pub const SCHEMA_VERSION: u64 = 1;
+
+/// How long after a failed release lookup launches skip GitHub when a cached
+/// version can stand in.
+pub const NETWORK_RETRY_SECS: u64 = 10 * 60;
+
+/// How often falling back to the minimum adapter version is warned about.
+pub const FLOOR_WARNING_INTERVAL_SECS: u64 = 60 * 60;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct State {
+    pub schema: u64,
+    /// Unix time of the last release lookup on GitHub, successful or not.
+    pub last_update_check: Option<u64>,
+    /// Unix time of the first of the consecutive failed lookups, if the last
+    /// one failed.
+    pub network_unavailable_since: Option<u64>,
+    /// Last-known-good version per install directory.
+    pub last_known_good: BTreeMap<String, String>,
+    /// First launch of the version most recently launched, per install
+    /// directory.
+    pub last_launch: BTreeMap<String, Launch>,
+    /// Install directories that fell back to their minimum version, with the
+    /// Unix time of the last warning.
+    pub floor_warned_at: BTreeMap<String, u64>,
+    /// User-supplied adapter paths launched before.
+    pub approved_binaries: BTreeSet<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Launch {
+    pub version: String,
+    pub at: u64,
+}
+
+impl Default for State {
+    fn default() -> Self {
+        State {
+            schema: SCHEMA_VERSION,
+            last_update_check: None,
+            network_unavailable_since: None,
+            last_known_good: BTreeMap::new(),
+            last_launch: BTreeMap::new(),
+            floor_warned_at: BTreeMap::new(),
+            approved_binaries: BTreeSet::new(),
+        }
+    }
+}
+
+fn parse(json: &str) -> Result<State, String> {
+    let value: Value = serde_json::from_str(json).map_err(|e| e.to_string())?;
+    match value.get("schema").and_then(Value::as_u64) {
+        None => Err("no schema version".to_string()),
+        Some(SCHEMA_VERSION) => serde_json::from_value(value).map_err(|e| e.to_string()),
+        Some(schema) => Err(format!("unknown schema {}", schema)),
+    }
+}
+
+/// The persisted state; a fresh one if there is none or it is unreadable.
+pub fn read(host: &Host) -> State {
+    let json = match host.fs.read_to_string(STATE_FILE) {
+        Ok(json) => json,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return State::default(),
+        Err(e) => return recover(host, None, &e.to_string()),
+    };
+    parse(&json).unwrap_or_else(|reason| recover(host, Some(&json), &reason))
+}
+
+/// Set an unreadable state file aside and start over.
+fn recover(host: &Host, json: Option<&str>, reason: &str) -> State {
+    warn!(
+        "{} is unreadable ({}); starting from a fresh state, the old file is kept as {}",
+        STATE_FILE, reason, CORRUPT_STATE_FILE
+    );
+    if let Some(json) = json
+        && let Err(e) = host.fs.write(CORRUPT_STATE_FILE, json)
+    {
+        debug!("Failed to keep {}: {}", CORRUPT_STATE_FILE, e);
+    }
+    let state = State::default();
+    write(host, &state);
+    state
+}
+
+fn write(host: &Host, state: &State) {
+    let written = serde_json::to_string_pretty(state)
+        .map_err(|e| e.to_string())
+        .and_then(|json| host.fs.write(STATE_FILE, &json).map_err(|e| e.to_string()));
+    if let Err(e) = written {
+        warn!("Failed to save {}: {}", STATE_FILE, e);
+    }
+}
+
+fn update<T>(host: &Host, change: impl FnOnce(&mut State) -> T) -> T {
+    let mut state = read(host);
+    let result = change(&mut state);
+    write(host, &state);
+    result
+}
+
+// ── Release lookups ───────────────────────────────────────────────────────────
+
+/// Record a release lookup at `now` and whether GitHub answered.
+pub fn record_update_check(host: &Host, now: u64, reachable: bool) {
+    update(host, |state| {
+        state.last_update_check = Some(now);
+        state.network_unavailable_since = if reachable {
+            None
+        } else {
+            state.network_unavailable_since.or(Some(now))
+        };
+    });
+}
+
+/// Whether the last release lookup failed less than [`NETWORK_RETRY_SECS`]
+/// before `now`.
+pub fn network_recently_unavailable(host: &Host, now: u64) -> bool {
+    let state = read(host);
+    state.network_unavailable_since.is_some()
+        && state
+            .last_update_check
+            .is_some_and(|at| now.saturating_sub(at) < NETWORK_RETRY_SECS)
+}
+
+// ── Known-good versions ───────────────────────────────────────────────────────
+
+/// Record a launch of `version` from install directory `dir` at `now`. A
+/// version launched again at least [`CRASH_LOOP_WINDOW_SECS`] after its
+/// first launch becomes the directory's last-known-good version.
+pub fn record_launch(host: &Host, dir: &str, version: &str, now: u64) {
+    let state = read(host);
+    let first = state.last_launch.get(dir).filter(|l| l.version == version);
+    let survived = first.is_some_and(|l| now.saturating_sub(l.at) >= CRASH_LOOP_WINDOW_SECS);
+    let known = state.last_known_good.get(dir).map(String::as_str) == Some(version);
+    if first.is_some() && (known || !survived) {
+        return;
+    }
+    update(host, |state| {
+        if survived {
+            state
+                .last_known_good
+                .insert(dir.to_string(), version.to_string());
+        } else {
+            state.last_launch.insert(
+                dir.to_string(),
+                Launch {
+                    version: version.to_string(),
+                    at: now,
+                },
+            );
+        }
+    });
+}
+
+/// Whether `version` of `dir` was first launched within `window` seconds of
+/// `now` and has not become last-known-good since.
+pub fn launched_recently(host: &Host, dir: &str, version: &str, now: u64, window: u64) -> bool {
+    let state = read(host);
+    state.last_known_good.get(dir).map(String::as_str) != Some(version)
+        && state
+            .last_launch
+            .get(dir)
+            .is_some_and(|l| l.version == version && now.saturating_sub(l.at) < window)
+}
+
+/// The last-known-good version of install directory `dir`.
+pub fn last_known_good(host: &Host, dir: &str) -> Option<String> {
+    read(host).last_known_good.remove(dir)
+}
+
+/// Stop treating `version` of `dir` as known good.
+pub fn forget_known_good(host: &Host, dir: &str, version: &str) {
+    if last_known_good(host, dir).as_deref() == Some(version) {
+        update(host, |state| state.last_known_good.remove(dir));
+    }
+}
+
+// ── Minimum version floor ─────────────────────────────────────────────────────
+
+/// Record that `dir` fell back to its minimum version at `now`; whether the
+/// last warning is at least [`FLOOR_WARNING_INTERVAL_SECS`] old.
+pub fn record_floor_use(host: &Host, dir: &str, now: u64) -> bool {
+    let warned_at = read(host).floor_warned_at.get(dir).copied();
+    if warned_at.is_some_and(|at| now.saturating_sub(at) < FLOOR_WARNING_INTERVAL_SECS) {
+        return false;
+    }
+    update(host, |state| {
+        state.floor_warned_at.insert(dir.to_string(), now)
+    });
+    true
+}
+
+/// Note that `dir` resolved a real version again.
+pub fn clear_floor_use(host: &Host, dir: &str) {
+    if read(host).floor_warned_at.contains_key(dir) {
+        update(host, |state| state.floor_warned_at.remove(dir));
+    }
+}
+
+// ── User-supplied binaries ────────────────────────────────────────────────────
+
+/// Remember `path`; whether it was new.
+pub fn approve_binary(host: &Host, path: &str) -> bool {
+    if read(host).approved_binaries.contains(path) {
+        return false;
+    }
+    update(host, |state| {
+        state.approved_binaries.insert(path.to_string())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        FLOOR_WARNING_INTERVAL_SECS, NETWORK_RETRY_SECS, approve_binary, clear_floor_use,
+        last_known_good, network_recently_unavailable, read, record_floor_use, record_launch,
+        record_update_check,
+    };
+    use crate::crash_loop::CRASH_LOOP_WINDOW_SECS;
+    use crate::host::{FixedClock, FixedPlatform, Host, MemFs};
+    use crate::http::Canned;
+    use crate::releases::ReleaseCache;
+    use zed_extension_api::{Architecture, Os};
+
+    const NOW: u64 = 1_800_000_000;
+
+    fn with_host(fs: &MemFs, test: impl FnOnce(&Host)) {
+        let (http, clock) = (Canned::default(), FixedClock(NOW));
+        test(&Host {
+            http: &http,
+            fs,
+            clock: &clock,
+            platform: &FixedPlatform(Os::Linux, Architecture::X8664),
+            releases: &ReleaseCache::new(),
+            github_token: None,
+        });
+    }
+
+    #[test]
+    fn recovers_from_corrupt_or_newer_files() {
+        for contents in [
+            &b"{\"schema\": 1, \"last_update"[..],
+            b"{}",
+            b"{\"schema\": 99}",
+        ] {
+            let fs = MemFs::default().with_file("state.json", contents);
+            with_host(&fs, |host| {
+                assert_eq!(read(host), Default::default());
+                assert!(approve_binary(host, "/opt/vulnera-adapter"));
+                assert!(!approve_binary(host, "/opt/vulnera-adapter"));
+            });
+            assert_eq!(
+                fs.file("state.json.corrupt").as_deref().map(str::as_bytes),
+                Some(contents)
+            );
+            assert!(fs.file("state.json").unwrap().contains("\"schema\": 1"));
+        }
+    }
+
+    #[test]
+    fn tracks_network_and_known_good_versions() {
+        let fs = MemFs::default();
+        with_host(&fs, |host| {
+            record_update_check(host, NOW, false);
+            assert!(network_recently_unavailable(host, NOW + 60));
+            assert!(!network_recently_unavailable(
+                host,
+                NOW + NETWORK_RETRY_SECS
+            ));
+            record_update_check(host, NOW + 120, true);
+            assert!(!network_recently_unavailable(host, NOW + 180));
+
+            record_launch(host, "server", "0.4.0", NOW);
+            record_launch(host, "server", "0.4.0", NOW + 5);
+            assert_eq!(last_known_good(host, "server"), None);
+            record_launch(host, "server", "0.4.0", NOW + CRASH_LOOP_WINDOW_SECS);
+            assert_eq!(last_known_good(host, "server").as_deref(), Some("0.4.0"));
+
+            // A newer version does not replace it until it has proven itself.
+            record_launch(host, "server", "0.5.0", NOW + 1000);
+            assert_eq!(last_known_good(host, "server").as_deref(), Some("0.4.0"));
+        });
+    }
+
+    #[test]
+    fn rate_limits_floor_warnings() {
+        let fs = MemFs::default();
+        with_host(&fs, |host| {
+            assert!(record_floor_use(host, "server", NOW));
+            assert!(!record_floor_use(host, "server", NOW + 60));
+            assert!(record_floor_use(host, "container", NOW + 60));
+            assert!(record_floor_use(
+                host,
+                "server",
+                NOW + FLOOR_WARNING_INTERVAL_SECS
+            ));
+
+            clear_floor_use(host, "server");
+            assert!(!read(host).floor_warned_at.contains_key("server"));
+            assert!(record_floor_use(host, "server", NOW + 61));
+        });
+    }
+}
@@ -0,0 +1,145 @@
+//! Zed task templates that run the managed adapter in a terminal.
+//!
+//! Tasks live in the user's `.zed/tasks.json`, outside the extension's reach,
+//! and cannot know where the extension installed the adapter. So the
+//! extension keeps a stable launcher at `bin/vulnera-adapter` (re-pointed at
+//! whichever binary the language server last launched) and `/vulnera-task`
+//! renders the templates under `tasks/` with its absolute path and the
+//! worktree's settings.
+
+use std::fs;
+
+use zed_extension_api::Result;
+
+use crate::error::VulneraError;
+use crate::host::Host;
+use crate::settings::VulneraSettings;
+
+/// Task templates; `{{placeholders}}` are filled in by [`render`].
+const TEMPLATES: &[&str] = &[
+    include_str!("../tasks/scan-workspace.json"),
+    include_str!("../tasks/export-sbom.json"),
+];
+
+fn launcher_path(is_windows: bool) -> &'static str {
+    if is_windows {
+        "bin/vulnera-adapter.cmd"
+    } else {
+        "bin/vulnera-adapter"
+    }
+}
+
+/// Point the launcher at `binary` (relative to the extension directory or
+/// absolute), rewriting it only when the target changed.
+pub fn update_launcher(host: &Host, binary: &str) {
+    let Some(target) = absolute(binary) else {
+        return;
+    };
+    let is_windows = host.is_windows();
+    let script = if is_windows {
+        format!("@echo off\r\n\"{}\" %*\r\n", target)
+    } else {
+        format!(
+            "#!/bin/sh\nexec '{}' \"$@\"\n",
+            target.replace('\'', r"'\''")
+        )
+    };
+
+    let path = launcher_path(is_windows);
+    if host
+        .fs
+        .read_to_string(path)
+        .is_ok_and(|current| current == script)
+    {
+        return;
+    }
+    let written = host
+        .fs
+        .create_dir_all("bin")
+        .and_then(|()| host.fs.write(path, &script))
+        .map_err(|e| e.to_string())
+        .and_then(|()| host.fs.make_executable(path));
+    if let Err(e) = written {
+        warn!("Failed to write the task launcher: {}", e);
+    }
+}
+
+/// The task templates rendered for the current launcher and `settings`, as a
+/// `.zed/tasks.json` array.
+pub fn render(is_windows: bool, settings: &VulneraSettings) -> Result<String> {
+    let path = launcher_path(is_windows);
+    if fs::metadata(path).is_err() {
+        return Err(VulneraError::NotInstalled {
+            binary: "vulnera-adapter".to_string(),
+            reason: "the task launcher is created when the language server first starts"
+                .to_string(),
+        }
+        .into());
+    }
+    let launcher = absolute(path).ok_or_else(|| {
+        VulneraError::fs("resolve the extension directory", "no current directory")
+    })?;
+    Ok(render_templates(&[
+        ("adapter", &launcher),
+        ("sbom_format", settings.sbom.format.as_str()),
+        ("sbom_output", settings.sbom.output()),
+    ]))
+}
+
+fn render_templates(values: &[(&str, &str)]) -> String {
+    let tasks: Vec<String> = TEMPLATES
+        .iter()
+        .map(|template| {
+            let mut task = template.trim_end().to_string();
+            for (key, value) in values {
+                let escaped = value.replace('\\', "\\\\").replace('"', "\\\"");
+                task = task.replace(&format!("{{{{{}}}}}", key), &escaped);
+            }
+            task.lines()
+                .map(|line| format!("  {}", line))
+                .collect::<Vec<_>>()
+                .join("\n")
+        })
+        .collect();
+    format!("[\n{}\n]\n", tasks.join(",\n"))
+}
+
+fn absolute(path: &str) -> Option<String> {
+    let dir = std::env::current_dir().ok()?;
+    Some(dir.join(path).to_string_lossy().into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::render_templates;
+    use zed_extension_api::serde_json::{self, Value};
+
+    #[test]
+    fn renders_valid_task_json() {
+        let rendered = render_templates(&[
+            ("adapter", r"C:\Zed\work\vulnera\bin\vulnera-adapter.cmd"),
+            ("sbom_format", "spdx-json"),
+            ("sbom_output", "out/sbom.spdx.json"),
+        ]);
+        let tasks: Value = serde_json::from_str(&rendered).unwrap();
+        assert_eq!(
+            tasks[0]["command"],
+            r"C:\Zed\work\vulnera\bin\vulnera-adapter.cmd"
+        );
+        assert_eq!(
+            tasks[0]["args"],
+            serde_json::json!(["scan", "--workspace", "."])
+        );
+        assert_eq!(
+            tasks[1]["args"],
+            serde_json::json!([
+                "sbom",
+                "--format",
+                "spdx-json",
+                "--output",
+                "out/sbom.spdx.json",
+                "."
+            ])
+        );
+    }
+}
@@ -0,0 +1,365 @@
+//! How Zed talks to the adapter.
+//!
+//! By default the adapter speaks LSP over stdio. With `transport.mode` set to
+//! `tcp` or `socket`, it is launched with `--listen <endpoint> --bridge-stdio`:
+//! it serves LSP on the endpoint and bridges Zed's stdio to it as one client,
+//! so an external LSP inspector can attach to the same server.
+//!
+//! With `daemon: true`, every worktree shares one adapter per machine and
+//! binary: each launch runs `--daemon <endpoint>`, which connects to the daemon
+//! listening there (starting it first if none is) and bridges stdio to it, so
+//! worktrees share one advisory cache and API connection. The daemon is
+//! reached over a unix socket, so it is not available on Windows.
+//!
+//! `VULNERA_ADAPTER_CONNECT=<endpoint>` attaches to an adapter that is already
+//! running (under a debugger, say): nothing is installed and Zed gets a thin
+//! `socat` / `nc` stdio bridge to the endpoint instead. The bridge carries
+//! file contents and credentials, so the endpoint must be a unix socket or a
+//! loopback address, and it is only read from the user's shell.
+
+use std::collections::hash_map::DefaultHasher;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+use zed_extension_api::{Command, Result};
+
+use crate::error::VulneraError;
+use crate::host::{Checkout, Host};
+use crate::settings::{TransportMode, TransportSettings};
+
+/// Loopback address used for `tcp` mode.
+const TCP_HOST: &str = "127.0.0.1";
+
+/// An address the adapter listens on.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Endpoint {
+    /// `host:port`; port 0 lets the adapter pick one and log it.
+    Tcp(String),
+    /// A unix domain socket path.
+    Unix(String),
+}
+
+impl Endpoint {
+    /// Parse `unix:<path>`, an absolute socket path, or `host:port` with a
+    /// loopback host (`localhost`, `127.0.0.1`, `[::1]`). IPv6 hosts must be
+    /// bracketed, since `::1:9257` is also a valid address without a port.
+    pub fn parse(value: &str) -> Result<Self> {
+        let value = value.trim();
+        if let Some(path) = value.strip_prefix("unix:") {
+            if path.trim().is_empty() {
+                return Err(connect_error(format!(
+                    "'{}' names no socket (use unix:/path)",
+                    value
+                )));
+            }
+            return Ok(Endpoint::Unix(path.to_string()));
+        }
+        if value.starts_with('/') {
+            return Ok(Endpoint::Unix(value.to_string()));
+        }
+        let address = value.strip_prefix("tcp:").unwrap_or(value);
+        match address.rsplit_once(':') {
+            Some((host, _)) if host.contains(':') && !host.starts_with('[') => {
+                Err(connect_error(format!(
+                    "'{}' is ambiguous; write IPv6 addresses in brackets, like [::1]:9257",
+                    value
+                )))
+            }
+            Some((host, port)) if is_loopback(host) && port.parse::<u16>().is_ok() => {
+                Ok(Endpoint::Tcp(address.to_string()))
+            }
+            Some((host, port)) if !host.is_empty() && port.parse::<u16>().is_ok() => {
+                Err(connect_error(format!(
+                    "'{}' is not on this machine; the adapter endpoint must be a loopback \
+                     address or a unix socket",
+                    value
+                )))
+            }
+            _ => Err(connect_error(format!(
+                "'{}' is not an adapter endpoint (use host:port or unix:/path)",
+                value
+            ))),
+        }
+    }
+}
+
+fn connect_error(reason: String) -> String {
+    VulneraError::config("VULNERA_ADAPTER_CONNECT", reason).into()
+}
+
+/// Whether `host` names this machine. IPv6 addresses come in brackets.
+fn is_loopback(host: &str) -> bool {
+    if let Some(ipv6) = host.strip_prefix('[') {
+        return ipv6
+            .strip_suffix(']')
+            .and_then(|ip| ip.parse::<Ipv6Addr>().ok())
+            .is_some_and(|ip| ip.is_loopback());
+    }
+    host.eq_ignore_ascii_case("localhost")
+        || host.parse::<Ipv4Addr>().is_ok_and(|ip| ip.is_loopback())
+}
+
+impl fmt::Display for Endpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Endpoint::Tcp(address) => write!(f, "{}", address),
+            Endpoint::Unix(path) => write!(f, "unix:{}", path),
+        }
+    }
+}
+
+/// The endpoint the adapter should listen on for `worktree_id`, or `None`
+/// for plain stdio.
+pub fn listen_endpoint(
+    host: &Host,
+    settings: &TransportSettings,
+    worktree_id: u64,
+) -> Result<Option<Endpoint>> {
+    match settings.mode {
+        TransportMode::Stdio => Ok(None),
+        TransportMode::Tcp => Ok(Some(Endpoint::Tcp(format!(
+            "{}:{}",
+            TCP_HOST,
+            settings.port.unwrap_or(0)
+        )))),
+        TransportMode::Socket => {
+            if host.is_windows() {
+                return Err(VulneraError::config(
+                    "lsp.vulnera.settings.transport.mode",
+                    "\"socket\" is not supported on Windows; use \"tcp\"",
+                )
+                .into());
+            }
+            let path = match settings.socket_path.as_deref().map(str::trim) {
+                Some(path) if !path.is_empty() => path.to_string(),
+                _ => socket_in(host, "sockets", &worktree_id.to_string())?,
+            };
+            Ok(Some(Endpoint::Unix(path)))
+        }
+    }
+}
+
+/// The absolute path of `<work dir>/<dir>/<name>.sock`, creating the
+/// directory.
+fn socket_in(host: &Host, dir: &str, name: &str) -> Result<String> {
+    host.fs
+        .create_dir_all(dir)
+        .map_err(|e| VulneraError::fs(format!("create {}", dir), e))?;
+    host.fs
+        .absolute(&format!("{}/{}.sock", dir, name))
+        .map_err(|e| VulneraError::fs("resolve the extension directory", e).into())
+}
+
+/// The shared daemon's endpoint for `command`: a socket named after the
+/// binary path and the launch environment, so only worktrees that would
+/// launch an identical adapter (same version, credentials, offline database,
+/// policy file) share a daemon. Refused on Windows, where a loopback port
+/// could not tell one launch configuration's daemon from another's.
+pub fn daemon_endpoint(host: &Host, command: &Command) -> Result<Endpoint> {
+    if host.is_windows() {
+        return Err(VulneraError::config(
+            "lsp.vulnera.settings.daemon",
+            "the shared daemon is not supported on Windows; set it to false",
+        )
+        .into());
+    }
+    let hash = launch_hash(&command.command, &command.env);
+    Ok(Endpoint::Unix(socket_in(
+        host,
+        "daemon",
+        &format!("{:016x}", hash),
+    )?))
+}
+
+/// A hash of `binary` and `env`, independent of the variables' order.
+fn launch_hash(binary: &str, env: &[(String, String)]) -> u64 {
+    let mut env: Vec<_> = env.iter().collect();
+    env.sort();
+    let mut hasher = DefaultHasher::new();
+    binary.hash(&mut hasher);
+    env.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Adapter arguments connecting to (or starting) the daemon at `endpoint`.
+pub fn daemon_args(endpoint: &Endpoint) -> Vec<String> {
+    vec!["--daemon".to_string(), endpoint.to_string()]
+}
+
+/// Adapter arguments serving LSP on `endpoint` while bridging stdio.
+pub fn listen_args(endpoint: &Endpoint) -> Vec<String> {
+    vec![
+        "--listen".to_string(),
+        endpoint.to_string(),
+        "--bridge-stdio".to_string(),
+    ]
+}
+
+/// A command bridging stdio to `endpoint` with `socat`, or `nc` if `socat`
+/// is not on the worktree `PATH`.
+pub fn bridge_command(
+    endpoint: &Endpoint,
+    worktree: &dyn Checkout,
+) -> Result<(String, Vec<String>)> {
+    if let Some(socat) = worktree.which("socat") {
+        let target = match endpoint {
+            Endpoint::Tcp(address) => format!("TCP:{}", address),
+            Endpoint::Unix(path) => format!("UNIX-CONNECT:{}", path),
+        };
+        return Ok((socat, vec!["STDIO".to_string(), target]));
+    }
+    if let Some(nc) = worktree.which("nc") {
+        let args = match endpoint {
+            Endpoint::Tcp(address) => {
+                let (host, port) = address.rsplit_once(':').unwrap_or((address, ""));
+                vec![host.to_string(), port.to_string()]
+            }
+            Endpoint::Unix(path) => vec!["-U".to_string(), path.clone()],
+        };
+        return Ok((nc, args));
+    }
+    Err(connect_error(format!(
+        "needs socat or nc on PATH to bridge to {}",
+        endpoint
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use zed_extension_api::{Architecture, Command, Os};
+
+    use super::{
+        Endpoint, daemon_args, daemon_endpoint, launch_hash, listen_args, listen_endpoint,
+    };
+    use crate::host::{FixedClock, FixedPlatform, Host, MemFs};
+    use crate::http::Canned;
+    use crate::releases::ReleaseCache;
+    use crate::settings::{TransportMode, TransportSettings};
+
+    /// Run `test` against an empty work dir on `os`.
+    fn on(os: Os, test: impl FnOnce(&Host)) {
+        let fs = MemFs::default();
+        let host = Host {
+            http: &Canned::default(),
+            fs: &fs,
+            clock: &FixedClock(0),
+            platform: &FixedPlatform(os, Architecture::X8664),
+            releases: &ReleaseCache::new(),
+            github_token: None,
+        };
+        test(&host);
+    }
+
+    #[test]
+    fn builds_listen_arguments() {
+        on(Os::Linux, |host| {
+            let stdio = TransportSettings::default();
+            assert_eq!(listen_endpoint(host, &stdio, 7).unwrap(), None);
+
+            let tcp = TransportSettings {
+                mode: TransportMode::Tcp,
+                port: Some(9257),
+                ..TransportSettings::default()
+            };
+            let endpoint = listen_endpoint(host, &tcp, 7).unwrap().unwrap();
+            assert_eq!(endpoint, Endpoint::Tcp("127.0.0.1:9257".to_string()));
+            assert_eq!(
+                listen_args(&endpoint),
+                ["--listen", "127.0.0.1:9257", "--bridge-stdio"]
+            );
+
+            let socket = TransportSettings {
+                mode: TransportMode::Socket,
+                ..TransportSettings::default()
+            };
+            assert_eq!(
+                listen_endpoint(host, &socket, 7).unwrap(),
+                Some(Endpoint::Unix("/ext/sockets/7.sock".to_string()))
+            );
+        });
+        assert_eq!(
+            Endpoint::Unix("/tmp/v.sock".to_string()).to_string(),
+            "unix:/tmp/v.sock"
+        );
+    }
+
+    #[test]
+    fn daemons_are_shared_only_by_identical_launches() {
+        let env = |pairs: &[(&str, &str)]| -> Vec<(String, String)> {
+            pairs
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect()
+        };
+        let binary = "/ext/server/0.4.0/vulnera-adapter";
+        let a = env(&[("VULNERA_API_KEY", "a"), ("PATH", "/usr/bin")]);
+        let reordered = env(&[("PATH", "/usr/bin"), ("VULNERA_API_KEY", "a")]);
+        let b = env(&[("VULNERA_API_KEY", "b"), ("PATH", "/usr/bin")]);
+        assert_eq!(launch_hash(binary, &a), launch_hash(binary, &reordered));
+        assert_ne!(launch_hash(binary, &a), launch_hash(binary, &b));
+        assert_ne!(
+            launch_hash(binary, &a),
+            launch_hash("/ext/server/0.5.0/vulnera-adapter", &a)
+        );
+    }
+
+    #[test]
+    fn daemon_connects_by_endpoint() {
+        assert_eq!(
+            daemon_args(&Endpoint::Unix("/ext/daemon/ab.sock".to_string())),
+            ["--daemon", "unix:/ext/daemon/ab.sock"]
+        );
+
+        let command = Command {
+            command: "/ext/server/0.4.0/vulnera-adapter".to_string(),
+            args: Vec::new(),
+            env: Vec::new(),
+        };
+        on(Os::Mac, |host| {
+            let hash = launch_hash(&command.command, &command.env);
+            assert_eq!(
+                daemon_endpoint(host, &command),
+                Ok(Endpoint::Unix(format!("/ext/daemon/{:016x}.sock", hash)))
+            );
+        });
+        // A port cannot tell launch configurations apart.
+        on(Os::Windows, |host| {
+            let error = daemon_endpoint(host, &command).unwrap_err();
+            assert!(error.contains("lsp.vulnera.settings.daemon"), "{}", error);
+        });
+    }
+
+    #[test]
+    fn parses_connect_endpoints() {
+        assert_eq!(
+            Endpoint::parse("127.0.0.1:9257"),
+            Ok(Endpoint::Tcp("127.0.0.1:9257".to_string()))
+        );
+        assert_eq!(
+            Endpoint::parse("tcp:localhost:9257"),
+            Ok(Endpoint::Tcp("localhost:9257".to_string()))
+        );
+        assert_eq!(
+            Endpoint::parse("unix:/run/vulnera.sock"),
+            Ok(Endpoint::Unix("/run/vulnera.sock".to_string()))
+        );
+        assert_eq!(
+            Endpoint::parse(" /tmp/v.sock "),
+            Ok(Endpoint::Unix("/tmp/v.sock".to_string()))
+        );
+        assert_eq!(
+            Endpoint::parse("[::1]:9257"),
+            Ok(Endpoint::Tcp("[::1]:9257".to_string()))
+        );
+        assert!(Endpoint::parse("attacker.example:9257").is_err());
+        assert!(Endpoint::parse("10.0.0.5:9257").is_err());
+        assert!(Endpoint::parse("localhost").is_err());
+        assert!(Endpoint::parse("localhost:lsp").is_err());
+        assert!(Endpoint::parse("unix:").is_err());
+        assert!(Endpoint::parse("unix: ").is_err());
+        assert!(Endpoint::parse("::1:9257").is_err());
+        assert!(Endpoint::parse("[::1:9257").is_err());
+        assert!(Endpoint::parse("[::2]:9257").is_err());
+    }
+}
@@ -0,0 +1,145 @@
+//! `/vulnera-uninstall`: remove everything the extension wrote to its work dir.
+//!
+//! For users removing the extension, or wanting a pristine reinstall: the
+//! adapter binaries of every channel with their version caches, markers, bad
+//! release list and release notes; `metrics.json`; the slash command
+//! snapshots, cached advisory docs, SBOMs and the task launcher; and the
+//! extension log, `state.json` and the cached release metadata. Without
+//! `confirm` the command only lists what it would remove.
+//!
+//! Sockets of running adapters (`sockets/`) are left alone; the servers
+//! remove them when they exit.
+
+use crate::adapters;
+use crate::channel::Channel;
+use crate::host::Host;
+use crate::{metrics, releases, sbom, state};
+
+/// Work dir entries written outside the adapter install directories.
+const OTHER_ENTRIES: &[&str] = &[
+    metrics::METRICS_FILE,
+    state::STATE_FILE,
+    state::CORRUPT_STATE_FILE,
+    releases::RELEASES_DIR,
+    sbom::SBOM_DIR,
+    "slash",
+    "docs",
+    "bin",
+];
+
+/// Work dir entries the extension may have written, whether or not they
+/// exist.
+pub fn entries() -> Vec<String> {
+    let mut entries: Vec<String> = adapters::ALL
+        .iter()
+        .flat_map(|spec| Channel::ALL.map(|channel| channel.install_dir(spec.dir)))
+        .collect();
+    entries.extend(OTHER_ENTRIES.iter().map(|entry| entry.to_string()));
+    entries
+}
+
+/// The outcome of removing the work dir entries.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Removal {
+    pub removed: Vec<String>,
+    /// Entries that could not be removed, with the reason.
+    pub failed: Vec<(String, String)>,
+}
+
+/// Existing entries, if `dry_run`; otherwise remove them. A failure to
+/// remove one entry does not stop the others.
+pub fn remove(host: &Host, dry_run: bool) -> Removal {
+    let mut removal = Removal::default();
+    for entry in entries().into_iter().filter(|entry| host.fs.exists(entry)) {
+        if dry_run {
+            removal.removed.push(entry);
+            continue;
+        }
+        match host.fs.remove_all(&entry) {
+            Ok(()) => removal.removed.push(entry),
+            Err(e) => removal.failed.push((entry, e.to_string())),
+        }
+    }
+    if !dry_run {
+        log!("Removed {} work dir entries", removal.removed.len());
+    }
+    removal
+}
+
+/// The `/vulnera-uninstall` output for `args`.
+pub fn run(host: &Host, args: &[String]) -> String {
+    let confirmed = args.first().is_some_and(|arg| arg == "confirm");
+    let removal = remove(host, !confirmed);
+    if removal.removed.is_empty() && removal.failed.is_empty() {
+        return "Nothing to remove: the extension work dir holds no Vulnera files.\n".to_string();
+    }
+    let list = |entries: &[String]| -> String {
+        entries
+            .iter()
+            .map(|entry| format!("- `{}`\n", entry))
+            .collect()
+    };
+    if !confirmed {
+        return format!(
+            "`/vulnera-uninstall confirm` would remove from the extension work dir:\n\n{}\n\
+             Running language servers keep working until they restart, then download \
+             the adapter again.\n",
+            list(&removal.removed)
+        );
+    }
+    let mut out = format!(
+        "Removed from the extension work dir:\n\n{}",
+        list(&removal.removed)
+    );
+    if !removal.failed.is_empty() {
+        out.push_str("\nCould not remove:\n\n");
+        for (entry, reason) in &removal.failed {
+            out.push_str(&format!("- `{}`: {}\n", entry, reason));
+        }
+    }
+    out.push_str(
+        "\nTo finish uninstalling, remove the extension from `zed: extensions`. To reinstall \
+         instead, restart Zed; the adapter is downloaded again on the next launch.\n",
+    );
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::run;
+    use crate::host::{FixedClock, FixedPlatform, Host, MemFs};
+    use crate::http::Canned;
+    use crate::releases::ReleaseCache;
+    use zed_extension_api::{Architecture, Os};
+
+    #[test]
+    fn lists_then_removes_extension_files() {
+        let (http, clock) = (Canned::default(), FixedClock(1_800_000_000));
+        let fs = MemFs::default()
+            .with_file("server/0.4.0/vulnera-adapter", b"\x7fELF")
+            .with_file("server/cached-version.txt", b"0.4.0")
+            .with_file("server-beta/cached-version.txt", b"0.5.0-rc.1")
+            .with_file("metrics.json", b"{}")
+            .with_file("slash/advisory-ids.txt", b"GHSA-1")
+            .with_file("sockets/7.sock", b"");
+        let host = Host {
+            http: &http,
+            fs: &fs,
+            clock: &clock,
+            platform: &FixedPlatform(Os::Linux, Architecture::X8664),
+            releases: &ReleaseCache::new(),
+            github_token: None,
+        };
+
+        let preview = run(&host, &[]);
+        assert!(preview.contains("- `server-beta`\n"), "{}", preview);
+        assert!(!preview.contains("container"));
+        assert_eq!(fs.files.borrow().len(), 6);
+
+        let done = run(&host, &["confirm".to_string()]);
+        assert!(done.starts_with("Removed"), "{}", done);
+        let left: Vec<String> = fs.files.borrow().keys().cloned().collect();
+        assert_eq!(left, ["sockets/7.sock"]);
+        assert!(run(&host, &["confirm".to_string()]).starts_with("Nothing to remove"));
+    }
+}